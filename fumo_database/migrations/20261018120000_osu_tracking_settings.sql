@@ -0,0 +1,6 @@
+-- Add migration script here
+
+create table osu_tracking_settings (
+	channel_id int8 primary key references discord_channels(channel_id) on delete cascade,
+	style int2 not null default 0
+);
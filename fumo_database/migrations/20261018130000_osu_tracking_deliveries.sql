@@ -0,0 +1,18 @@
+-- Add migration script here
+
+create table osu_tracking_deliveries (
+	id bigserial primary key,
+	channel_id int8 not null references discord_channels(channel_id) on delete cascade,
+	osu_id int8 not null,
+	osu_username text not null,
+	avatar_url text not null,
+	embed text not null,
+	attempts int2 not null default 0,
+	next_attempt_at timestamp not null default now(),
+	created_at timestamp not null default now()
+);
+
+create index osu_tracking_deliveries_channel_index on osu_tracking_deliveries(channel_id, id);
+
+alter table osu_tracking_settings add column webhook_id int8;
+alter table osu_tracking_settings add column webhook_token text;
@@ -0,0 +1,9 @@
+-- Add migration script here
+
+alter table osu_tracking_settings add column guild_id int8;
+alter table osu_tracking_settings add column failures int2 not null default 0;
+alter table osu_tracking_settings add column disabled bool not null default false;
+
+alter table osu_players add column restricted bool not null default false;
+alter table osu_players add column deleted bool not null default false;
+alter table osu_players add column updated_at timestamp;
@@ -0,0 +1,25 @@
+-- Add migration script here
+
+create table osu_tracking_scores (
+	score_id int8 primary key,
+	osu_id int8 not null references osu_players(osu_id) on delete cascade,
+	mode int2 not null,
+	beatmap_id int8 not null,
+	beatmap_title text not null,
+	pp float4 not null,
+	top_score_pos int2,
+	user_pp float4 not null,
+	played_at timestamp not null
+);
+
+create index osu_tracking_scores_played_at_index on osu_tracking_scores(played_at);
+create index osu_tracking_scores_osu_id_index on osu_tracking_scores(osu_id, played_at);
+
+create table osu_tracking_digests (
+	guild_id int8 primary key,
+	weekday int2 not null default 0,
+	hour int2 not null default 18,
+	last_sent_at timestamp
+);
+
+alter table osu_tracking_settings add column digest bool not null default false;
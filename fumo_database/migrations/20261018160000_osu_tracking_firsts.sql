@@ -0,0 +1,14 @@
+-- Add migration script here
+
+alter table osu_tracking_settings add column top_plays bool not null default true;
+alter table osu_tracking_settings add column firsts int2 not null default 0;
+
+create table osu_tracking_firsts (
+	beatmap_id int8 not null,
+	country bool not null,
+	osu_id int8 not null,
+	osu_username text not null,
+	score_id int8 not null,
+	updated_at timestamp not null default now(),
+	primary key (beatmap_id, country)
+);
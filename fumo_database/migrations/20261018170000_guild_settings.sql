@@ -0,0 +1,6 @@
+-- Add migration script here
+
+create table guild_settings (
+	guild_id int8 primary key,
+	country varchar(2)
+);
@@ -0,0 +1,33 @@
+-- Add migration script here
+
+create table osu_player_countries (
+	osu_id int8 primary key,
+	osu_username text not null,
+	country varchar(2) not null,
+	updated_at timestamp not null default now()
+);
+
+create table osu_country_scores (
+	score_id int8 not null,
+	osu_id int8 not null,
+	osu_username text not null,
+	country varchar(2) not null,
+	beatmap_id int8 not null,
+	mode int2 not null,
+	mods text not null,
+	speed float4,
+	total_score int8 not null,
+	legacy_total_score int8 not null,
+	accuracy float4 not null,
+	max_combo int4 not null,
+	pp float4,
+	grade text not null,
+	count_300 int4 not null,
+	count_100 int4 not null,
+	count_50 int4 not null,
+	count_miss int4 not null,
+	played_at timestamp not null,
+	primary key (osu_id, beatmap_id, mode, mods)
+);
+
+create index osu_country_scores_beatmap_index on osu_country_scores(beatmap_id, country);
@@ -0,0 +1,9 @@
+-- Add migration script here
+
+create table osu_user_guilds (
+	discord_id int8 not null references osu_users(discord_id) on delete cascade,
+	guild_id int8 not null,
+	primary key (discord_id, guild_id)
+);
+
+create index osu_user_guilds_guild_id_idx on osu_user_guilds(guild_id);
@@ -0,0 +1,17 @@
+-- Add migration script here
+
+alter table guild_settings add column snipe_channel_id int8;
+
+create table country_firsts (
+	beatmap_id int8 not null,
+	country varchar(2) not null,
+	mode int2 not null,
+	osu_id int8 not null,
+	osu_username text not null,
+	score_id int8 not null,
+	total_score int8 not null,
+	checked_at timestamp not null default (now() at time zone 'utc'),
+	primary key (beatmap_id, country, mode)
+);
+
+create index country_firsts_checked_at_idx on country_firsts(checked_at);
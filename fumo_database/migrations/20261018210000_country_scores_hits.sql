@@ -0,0 +1,5 @@
+-- Add migration script here
+
+alter table osu_country_scores
+	add column count_geki int4 not null default 0,
+	add column count_katu int4 not null default 0;
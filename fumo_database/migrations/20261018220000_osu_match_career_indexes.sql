@@ -0,0 +1,5 @@
+-- Add migration script here
+
+create index osu_match_game_scores_game_id_idx on osu_match_game_scores(game_id);
+create index osu_match_game_scores_match_id_idx on osu_match_game_scores(match_id);
+create index osu_match_games_match_id_idx on osu_match_games(match_id);
@@ -0,0 +1,12 @@
+-- Add migration script here
+create table osu_tournaments (
+	acronym text not null,
+	constraint osu_tournament_acronym primary key (acronym)
+);
+
+alter table osu_matches
+	add column tournament_acronym text references osu_tournaments(acronym),
+	add column red_team text,
+	add column blue_team text;
+
+create index osu_matches_tournament_acronym_idx on osu_matches(tournament_acronym);
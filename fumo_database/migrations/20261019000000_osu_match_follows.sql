@@ -0,0 +1,12 @@
+-- Add migration script here
+create table osu_match_follows (
+	match_id int8 not null,
+	channel_id int8 not null,
+	last_event_id int8 not null,
+	red_wins int4 not null default 0,
+	blue_wins int4 not null default 0,
+	created_at timestamp not null default now(),
+	primary key (match_id, channel_id)
+);
+
+create index osu_match_follows_channel_id_idx on osu_match_follows(channel_id);
@@ -0,0 +1,14 @@
+-- Add migration script here
+
+create table osu_tracking_first_scores (
+	score_id int8 not null,
+	country bool not null,
+	osu_id int8 not null references osu_players(osu_id) on delete cascade,
+	beatmap_id int8 not null,
+	beatmap_title text not null,
+	pp float4 not null,
+	played_at timestamp not null,
+	primary key (score_id, country)
+);
+
+create index osu_tracking_first_scores_played_at_index on osu_tracking_first_scores(played_at);
@@ -0,0 +1,13 @@
+-- Add migration script here
+
+-- Country leaderboards that were fetched completely from osu!,
+-- feed scores alone never make local leaderboard complete
+create table osu_country_leaderboards (
+	beatmap_id int8 not null,
+	country varchar(2) not null,
+	mode int2 not null,
+	-- Normalized mods filter of the leaderboard, `*` stands for any mods
+	mods text not null,
+	fetched_at timestamp not null,
+	primary key (beatmap_id, country, mode, mods)
+);
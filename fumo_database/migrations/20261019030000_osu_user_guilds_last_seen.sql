@@ -0,0 +1,4 @@
+-- Add migration script here
+
+alter table osu_user_guilds
+	add column last_seen timestamp not null default (now() at time zone 'utc');
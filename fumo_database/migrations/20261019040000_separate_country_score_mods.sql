@@ -0,0 +1,15 @@
+-- Add migration script here
+
+-- Normalized mods were stored as acronyms written together,
+-- now they are separated by `,` since keys like `10K` are longer
+update osu_country_scores
+set mods = array_to_string(array(
+	select (regexp_matches(mods, '[0-9]+K|SV2|[A-Z0-9]{2}', 'g'))[1]
+), ',')
+where mods <> '';
+
+update osu_country_leaderboards
+set mods = array_to_string(array(
+	select (regexp_matches(mods, '[0-9]+K|SV2|[A-Z0-9]{2}', 'g'))[1]
+), ',')
+where mods not in ('', '*');
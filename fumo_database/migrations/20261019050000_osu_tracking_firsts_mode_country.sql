@@ -0,0 +1,14 @@
+-- Add migration script here
+
+drop table osu_tracking_firsts;
+
+create table osu_tracking_firsts (
+	beatmap_id int8 not null,
+	mode int2 not null,
+	country_code text not null,
+	osu_id int8 not null,
+	osu_username text not null,
+	score_id int8 not null,
+	updated_at timestamp not null default now(),
+	primary key (beatmap_id, mode, country_code)
+);
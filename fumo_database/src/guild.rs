@@ -0,0 +1,116 @@
+use eyre::Result;
+
+use crate::Database;
+
+impl Database {
+    /// Sets default country of the guild, `None` removes it
+    pub async fn set_guild_country(
+        &self,
+        guild_id: i64,
+        country: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO guild_settings (guild_id, country)
+            VALUES ($1, $2)
+            ON CONFLICT (guild_id) DO UPDATE SET country = EXCLUDED.country",
+            guild_id,
+            country
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_guild_country(
+        &self,
+        guild_id: i64,
+    ) -> Result<Option<String>> {
+        Ok(sqlx::query_scalar!(
+            "SELECT country FROM guild_settings WHERE guild_id = $1",
+            guild_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .flatten())
+    }
+
+    /// Remembers that the user is a member of the guild,
+    /// does nothing if user has no linked osu! account
+    /// Returns amount of stored rows, nothing
+    /// is stored if user didn't link osu! account
+    pub async fn add_osu_user_guild(
+        &self,
+        discord_id: i64,
+        guild_id: i64,
+    ) -> Result<u64> {
+        let res = sqlx::query!(
+            "INSERT INTO osu_user_guilds (discord_id, guild_id, last_seen)
+            SELECT discord_id, $2, now() AT TIME ZONE 'utc'
+            FROM osu_users WHERE discord_id = $1
+            ON CONFLICT (discord_id, guild_id) DO UPDATE
+            SET last_seen = EXCLUDED.last_seen",
+            discord_id,
+            guild_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected())
+    }
+
+    /// Returns osu! ids linked by the known members of the guild,
+    /// most recently seen first. Members that didn't use commands
+    /// there for 90 days are considered to have left the guild
+    pub async fn select_guild_osu_users(
+        &self,
+        guild_id: i64,
+    ) -> Result<Vec<i64>> {
+        Ok(sqlx::query_scalar!(
+            "SELECT ou.osu_id FROM osu_user_guilds oug
+            JOIN osu_users ou ON ou.discord_id = oug.discord_id
+            WHERE oug.guild_id = $1
+            AND oug.last_seen > now() AT TIME ZONE 'utc' - INTERVAL '90 days'
+            GROUP BY ou.osu_id
+            ORDER BY MAX(oug.last_seen) DESC",
+            guild_id
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Sets channel that gets country #1 snipes, `None` disables them
+    pub async fn set_guild_snipe_channel(
+        &self,
+        guild_id: i64,
+        channel_id: Option<i64>,
+    ) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO guild_settings (guild_id, snipe_channel_id)
+            VALUES ($1, $2)
+            ON CONFLICT (guild_id) DO UPDATE
+            SET snipe_channel_id = EXCLUDED.snipe_channel_id",
+            guild_id,
+            channel_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Snipe channels of the guilds with provided country
+    pub async fn select_snipe_channels(
+        &self,
+        country: &str,
+    ) -> Result<Vec<i64>> {
+        Ok(sqlx::query_scalar!(
+            r#"SELECT snipe_channel_id as "snipe_channel_id!"
+            FROM guild_settings
+            WHERE country = $1 AND snipe_channel_id IS NOT NULL"#,
+            country
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+}
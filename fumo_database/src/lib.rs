@@ -1,6 +1,7 @@
 use eyre::Result;
 use sqlx::postgres::{PgPool, PgPoolOptions};
 
+pub mod guild;
 pub mod osu;
 pub mod twitch;
 
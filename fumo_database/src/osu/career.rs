@@ -0,0 +1,339 @@
+use chrono::NaiveDateTime;
+use eyre::Result;
+
+use crate::Database;
+
+/// Overall multiplayer stats of the player.
+/// Team games are TeamVs and TagTeamVs, head to head
+/// game is won if player has the best passed score.
+/// Games are decided by their scoring kind value,
+/// accuracy of the team is averaged over its players
+#[derive(Debug)]
+pub struct OsuCareerTotals {
+    pub matches: i64,
+    pub maps_played: i64,
+    pub team_maps: i64,
+    pub team_wins: i64,
+    pub head_to_head_maps: i64,
+    pub head_to_head_wins: i64,
+    pub average_score: f64,
+    pub average_accuracy: f64,
+}
+
+#[derive(Debug)]
+pub struct OsuCareerBeatmap {
+    pub beatmap_id: i64,
+    pub maps_played: i64,
+}
+
+#[derive(Debug)]
+pub struct OsuCareerMods {
+    /// Legacy mods bits
+    pub mods: i64,
+    pub maps_played: i64,
+}
+
+/// Player that was in the same games with the user
+#[derive(Debug)]
+pub struct OsuCareerPlayer {
+    pub user_id: i64,
+    pub osu_username: Option<String>,
+    pub maps_played: i64,
+}
+
+#[derive(Debug)]
+pub struct OsuCareerYear {
+    pub year: i32,
+    pub matches: i64,
+    pub maps_played: i64,
+}
+
+/// Game where both players played, `1` and `2` suffixes
+/// stand for the first and second player
+#[derive(Debug)]
+pub struct OsuVersusGame {
+    pub game_id: i64,
+    pub match_id: i64,
+    pub match_name: String,
+    pub beatmap_id: i64,
+    pub start_time: NaiveDateTime,
+    pub game_mods: i64,
+    pub scoring_kind: i16,
+    pub score1: i64,
+    pub accuracy1: f64,
+    pub max_combo1: i32,
+    pub pass1: bool,
+    pub mods1: i64,
+    pub team1: i16,
+    pub score2: i64,
+    pub accuracy2: f64,
+    pub max_combo2: i32,
+    pub pass2: bool,
+    pub mods2: i64,
+    pub team2: i16,
+}
+
+impl Database {
+    pub async fn select_career_totals(
+        &self,
+        user_id: i64,
+        is_tournament: bool,
+        tournament: Option<&str>,
+    ) -> Result<OsuCareerTotals> {
+        let res = sqlx::query_as!(
+            OsuCareerTotals,
+            r#"
+            with user_scores as (
+                select s.game_id, s.match_id, s.team, s.score, s.pass, s.accuracy
+                from osu_match_game_scores s
+                join osu_matches m on m.id = s.match_id
+                where s.user_id = $1 and (not $2 or m.tournament_acronym is not null)
+                and ($3::text is null or m.tournament_acronym = $3)
+            ),
+            game_values as (
+                select
+                    o.game_id,
+                    o.user_id,
+                    o.team,
+                    o.pass,
+                    case g.scoring_kind
+                        when 0 then o.accuracy
+                        when 1 then o.max_combo::float8
+                        else o.score::float8
+                    end as value
+                from osu_match_game_scores o
+                join osu_match_games g on g.id = o.game_id
+                where o.game_id in (select game_id from user_scores)
+            ),
+            game_results as (
+                select
+                    g.team_kind,
+                    case when g.team_kind in (2, 3) then (
+                        select
+                            case when g.scoring_kind = 0 then
+                                coalesce(avg(v.value) filter (where v.team = us.team), 0)
+                                > coalesce(avg(v.value) filter (where v.team <> us.team), 0)
+                            else
+                                coalesce(sum(v.value) filter (where v.team = us.team), 0)
+                                > coalesce(sum(v.value) filter (where v.team <> us.team), 0)
+                            end
+                        from game_values v
+                        where v.game_id = us.game_id and v.pass
+                    ) else
+                        us.pass and (
+                            select v.value from game_values v
+                            where v.game_id = us.game_id and v.user_id = $1
+                        ) >= (
+                            select max(v.value)
+                            from game_values v
+                            where v.game_id = us.game_id and v.pass
+                        )
+                    end as won
+                from user_scores us
+                join osu_match_games g on g.id = us.game_id
+            )
+            select
+                (select count(distinct match_id) from user_scores) as "matches!",
+                (select count(*) from user_scores) as "maps_played!",
+                (select count(*) from game_results where team_kind in (2, 3)) as "team_maps!",
+                (select count(*) from game_results where team_kind in (2, 3) and won) as "team_wins!",
+                (select count(*) from game_results where team_kind = 0) as "head_to_head_maps!",
+                (select count(*) from game_results where team_kind = 0 and won) as "head_to_head_wins!",
+                (select coalesce(avg(score), 0)::float8 from user_scores) as "average_score!",
+                (select coalesce(avg(accuracy), 0)::float8 from user_scores) as "average_accuracy!"
+            "#,
+            user_id,
+            is_tournament,
+            tournament
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    pub async fn select_career_beatmaps(
+        &self,
+        user_id: i64,
+        is_tournament: bool,
+        tournament: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<OsuCareerBeatmap>> {
+        let res = sqlx::query_as!(
+            OsuCareerBeatmap,
+            r#"
+            select s.beatmap_id, count(*) as "maps_played!"
+            from osu_match_game_scores s
+            join osu_matches m on m.id = s.match_id
+            where s.user_id = $1 and (not $2 or m.tournament_acronym is not null)
+                and ($3::text is null or m.tournament_acronym = $3)
+            group by s.beatmap_id
+            order by 2 desc
+            limit $4
+            "#,
+            user_id,
+            is_tournament,
+            tournament,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    pub async fn select_career_mods(
+        &self,
+        user_id: i64,
+        is_tournament: bool,
+        tournament: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<OsuCareerMods>> {
+        let res = sqlx::query_as!(
+            OsuCareerMods,
+            r#"
+            select s.mods, count(*) as "maps_played!"
+            from osu_match_game_scores s
+            join osu_matches m on m.id = s.match_id
+            where s.user_id = $1 and (not $2 or m.tournament_acronym is not null)
+                and ($3::text is null or m.tournament_acronym = $3)
+            group by s.mods
+            order by 2 desc
+            limit $4
+            "#,
+            user_id,
+            is_tournament,
+            tournament,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    /// Teammates are players of the same team in team games,
+    /// opponents are the other team or everyone in head to head
+    pub async fn select_career_players(
+        &self,
+        user_id: i64,
+        is_tournament: bool,
+        tournament: Option<&str>,
+        teammates: bool,
+        limit: i64,
+    ) -> Result<Vec<OsuCareerPlayer>> {
+        let res = sqlx::query_as!(
+            OsuCareerPlayer,
+            r#"
+            select
+                o.user_id,
+                kv.osu_username as "osu_username?",
+                count(*) as "maps_played!"
+            from osu_match_game_scores s
+            join osu_matches m on m.id = s.match_id
+            join osu_match_game_scores o
+                on o.game_id = s.game_id and o.user_id <> s.user_id
+            left join osu_username_kv kv on kv.osu_id = o.user_id
+            where s.user_id = $1 and (not $2 or m.tournament_acronym is not null)
+                and ($3::text is null or m.tournament_acronym = $3)
+                and case when $4
+                    then s.team <> 0 and o.team = s.team
+                    else s.team = 0 or o.team <> s.team
+                end
+            group by o.user_id, kv.osu_username
+            order by 3 desc
+            limit $5
+            "#,
+            user_id,
+            is_tournament,
+            tournament,
+            teammates,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    pub async fn select_career_years(
+        &self,
+        user_id: i64,
+        is_tournament: bool,
+        tournament: Option<&str>,
+    ) -> Result<Vec<OsuCareerYear>> {
+        let res = sqlx::query_as!(
+            OsuCareerYear,
+            r#"
+            select
+                extract(year from m.start_time)::int4 as "year!",
+                count(distinct s.match_id) as "matches!",
+                count(*) as "maps_played!"
+            from osu_match_game_scores s
+            join osu_matches m on m.id = s.match_id
+            where s.user_id = $1 and (not $2 or m.tournament_acronym is not null)
+                and ($3::text is null or m.tournament_acronym = $3)
+            group by 1
+            order by 1
+            "#,
+            user_id,
+            is_tournament,
+            tournament
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    /// Games in the order they were played
+    pub async fn select_versus_games(
+        &self,
+        user1: i64,
+        user2: i64,
+        is_tournament: bool,
+        tournament: Option<&str>,
+    ) -> Result<Vec<OsuVersusGame>> {
+        let res = sqlx::query_as!(
+            OsuVersusGame,
+            r#"
+            select
+                g.id as game_id,
+                m.id as match_id,
+                m.name as match_name,
+                g.beatmap_id,
+                g.start_time,
+                g.mods as game_mods,
+                g.scoring_kind,
+                s1.score as score1,
+                s1.accuracy as accuracy1,
+                s1.max_combo as max_combo1,
+                s1.pass as pass1,
+                s1.mods as mods1,
+                s1.team as team1,
+                s2.score as score2,
+                s2.accuracy as accuracy2,
+                s2.max_combo as max_combo2,
+                s2.pass as pass2,
+                s2.mods as mods2,
+                s2.team as team2
+            from osu_match_game_scores s1
+            join osu_match_game_scores s2
+                on s2.game_id = s1.game_id and s2.user_id = $2
+            join osu_match_games g on g.id = s1.game_id
+            join osu_matches m on m.id = s1.match_id
+            where s1.user_id = $1 and (not $3 or m.tournament_acronym is not null)
+                and ($4::text is null or m.tournament_acronym = $4)
+            order by g.start_time
+            "#,
+            user1,
+            user2,
+            is_tournament,
+            tournament
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(res)
+    }
+}
@@ -0,0 +1,421 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use eyre::Result;
+
+use crate::Database;
+
+/// Score that was announced by the tracking
+#[derive(Debug)]
+pub struct OsuTrackingScore<'a> {
+    pub score_id: i64,
+    pub osu_id: i64,
+    pub mode: i16,
+    pub beatmap_id: i64,
+    pub beatmap_title: &'a str,
+    pub pp: f32,
+    pub top_score_pos: Option<i16>,
+    /// Total pp of the player right after the score
+    pub user_pp: f32,
+    pub played_at: DateTime<Utc>,
+}
+
+/// Announced #1 of the tracked player
+#[derive(Debug)]
+pub struct OsuTrackingFirstScore<'a> {
+    pub score_id: i64,
+    /// Country or global leaderboard
+    pub country: bool,
+    pub osu_id: i64,
+    pub beatmap_id: i64,
+    pub beatmap_title: &'a str,
+    pub pp: f32,
+    pub played_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct OsuTrackingDigestSchedule {
+    pub guild_id: i64,
+    /// Days since monday
+    pub weekday: i16,
+    /// Hour in UTC
+    pub hour: i16,
+    pub last_sent_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug)]
+pub struct OsuDigestScore {
+    pub osu_username: String,
+    pub beatmap_id: i64,
+    pub beatmap_title: String,
+    pub pp: f32,
+    pub top_score_pos: Option<i16>,
+}
+
+#[derive(Debug)]
+pub struct OsuDigestPpGain {
+    pub osu_username: String,
+    pub mode: i16,
+    pub user_pp: f32,
+    pub gain: f32,
+}
+
+#[derive(Debug)]
+pub struct OsuDigestBeatmap {
+    pub beatmap_id: i64,
+    pub beatmap_title: String,
+    pub plays: i64,
+    pub players: i64,
+}
+
+/// Everything that is going into the weekly digest of a single channel
+#[derive(Debug)]
+pub struct OsuTrackingDigest {
+    pub top_plays: Vec<OsuDigestScore>,
+    pub pp_gains: Vec<OsuDigestPpGain>,
+    pub beatmaps: Vec<OsuDigestBeatmap>,
+    pub new_firsts: Vec<OsuDigestScore>,
+}
+
+impl OsuTrackingDigest {
+    pub fn is_empty(&self) -> bool {
+        self.top_plays.is_empty()
+            && self.pp_gains.is_empty()
+            && self.beatmaps.is_empty()
+            && self.new_firsts.is_empty()
+    }
+}
+
+impl Database {
+    /// Saves announced score into the history, same score
+    /// is stored only once no matter how many channels got it
+    pub async fn add_osu_tracking_score(
+        &self,
+        score: &OsuTrackingScore<'_>,
+    ) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO osu_tracking_scores
+            (score_id, osu_id, mode, beatmap_id, beatmap_title,
+            pp, top_score_pos, user_pp, played_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (score_id) DO NOTHING",
+            score.score_id,
+            score.osu_id,
+            score.mode,
+            score.beatmap_id,
+            score.beatmap_title,
+            score.pp,
+            score.top_score_pos,
+            score.user_pp,
+            score.played_at.naive_utc()
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Saves announced #1 into the history
+    pub async fn add_osu_tracking_first_score(
+        &self,
+        score: &OsuTrackingFirstScore<'_>,
+    ) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO osu_tracking_first_scores
+            (score_id, country, osu_id, beatmap_id,
+            beatmap_title, pp, played_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (score_id, country) DO NOTHING",
+            score.score_id,
+            score.country,
+            score.osu_id,
+            score.beatmap_id,
+            score.beatmap_title,
+            score.pp,
+            score.played_at.naive_utc()
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Removes history older than `days`
+    pub async fn remove_old_osu_tracking_scores(
+        &self,
+        days: i32,
+    ) -> Result<u64> {
+        let res = sqlx::query!(
+            "DELETE FROM osu_tracking_scores
+            WHERE played_at < now() AT TIME ZONE 'utc' - make_interval(days => $1)",
+            days
+        )
+        .execute(&self.pool)
+        .await?;
+
+        let firsts = sqlx::query!(
+            "DELETE FROM osu_tracking_first_scores
+            WHERE played_at < now() AT TIME ZONE 'utc' - make_interval(days => $1)",
+            days
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected() + firsts.rows_affected())
+    }
+
+    /// Enables or disables digest for the channel
+    pub async fn set_osu_tracking_digest(
+        &self,
+        channel_id: i64,
+        guild_id: i64,
+        enabled: bool,
+    ) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO osu_tracking_settings (channel_id, guild_id, digest)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (channel_id) DO UPDATE
+            SET guild_id = EXCLUDED.guild_id,
+                digest = EXCLUDED.digest",
+            channel_id,
+            guild_id,
+            enabled
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_osu_tracking_digest(
+        &self,
+        channel_id: i64,
+    ) -> Result<Option<bool>> {
+        Ok(sqlx::query_scalar!(
+            "SELECT digest FROM osu_tracking_settings WHERE channel_id = $1",
+            channel_id
+        )
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    /// Updates provided parts of the guild digest schedule
+    /// and returns resulting schedule.
+    ///
+    /// Digest is considered sent at the moment of the change,
+    /// so the next one is posted only on the upcoming schedule
+    pub async fn set_osu_tracking_digest_schedule(
+        &self,
+        guild_id: i64,
+        weekday: Option<i16>,
+        hour: Option<i16>,
+    ) -> Result<OsuTrackingDigestSchedule> {
+        Ok(sqlx::query_as!(
+            OsuTrackingDigestSchedule,
+            "INSERT INTO osu_tracking_digests
+            (guild_id, weekday, hour, last_sent_at)
+            VALUES (
+                $1,
+                COALESCE($2::INT2, 0),
+                COALESCE($3::INT2, 18),
+                now() AT TIME ZONE 'utc'
+            )
+            ON CONFLICT (guild_id) DO UPDATE
+            SET weekday = COALESCE($2::INT2, osu_tracking_digests.weekday),
+                hour = COALESCE($3::INT2, osu_tracking_digests.hour),
+                last_sent_at = EXCLUDED.last_sent_at
+            RETURNING guild_id, weekday, hour, last_sent_at",
+            guild_id,
+            weekday,
+            hour
+        )
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    /// Selects schedules of guilds that have at least one digest channel
+    pub async fn select_osu_tracking_digest_schedules(
+        &self,
+    ) -> Result<Vec<OsuTrackingDigestSchedule>> {
+        Ok(sqlx::query_as!(
+            OsuTrackingDigestSchedule,
+            "SELECT guild_id, weekday, hour, last_sent_at
+            FROM osu_tracking_digests d
+            WHERE EXISTS (
+                SELECT 1 FROM osu_tracking_settings s
+                WHERE s.guild_id = d.guild_id
+                AND s.digest AND NOT s.disabled
+            )"
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn set_osu_tracking_digest_sent(
+        &self,
+        guild_id: i64,
+        sent_at: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query!(
+            "UPDATE osu_tracking_digests SET last_sent_at = $2
+            WHERE guild_id = $1",
+            guild_id,
+            sent_at.naive_utc()
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn select_osu_tracking_digest_channels(
+        &self,
+        guild_id: i64,
+    ) -> Result<Vec<i64>> {
+        Ok(sqlx::query_scalar!(
+            "SELECT channel_id FROM osu_tracking_settings
+            WHERE guild_id = $1 AND digest AND NOT disabled",
+            guild_id
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Collects digest of the players tracked on the channel
+    /// for the scores played in `from..to`.
+    /// Every section contains at most `limit` entries
+    pub async fn select_osu_tracking_digest(
+        &self,
+        channel_id: i64,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<OsuTrackingDigest> {
+        let (from, to) = (from.naive_utc(), to.naive_utc());
+
+        let top_plays = sqlx::query_as!(
+            OsuDigestScore,
+            "SELECT
+                op.osu_username, s.beatmap_id, s.beatmap_title,
+                s.pp, s.top_score_pos
+            FROM osu_tracking_scores s
+            JOIN osu_tracking ot
+                ON ot.osu_id = s.osu_id AND ot.channel_id = $1
+            JOIN osu_players op ON op.osu_id = s.osu_id
+            WHERE s.played_at >= $2 AND s.played_at < $3
+            ORDER BY s.pp DESC
+            LIMIT $4",
+            channel_id,
+            from,
+            to,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        // Gain is counted from the last score before the period,
+        // if there is none then from the first score of the period
+        let pp_gains = sqlx::query_as!(
+            OsuDigestPpGain,
+            r#"
+            WITH period AS (
+                SELECT
+                    s.osu_id, s.mode,
+                    (ARRAY_AGG(s.user_pp ORDER BY s.played_at ASC))[1]
+                        AS first_pp,
+                    (ARRAY_AGG(s.user_pp ORDER BY s.played_at DESC))[1]
+                        AS last_pp
+                FROM osu_tracking_scores s
+                JOIN osu_tracking ot
+                    ON ot.osu_id = s.osu_id AND ot.channel_id = $1
+                WHERE s.played_at >= $2 AND s.played_at < $3
+                GROUP BY s.osu_id, s.mode
+            ), gains AS (
+                SELECT
+                    p.osu_id, p.mode, p.last_pp,
+                    p.last_pp - COALESCE((
+                        SELECT b.user_pp FROM osu_tracking_scores b
+                        WHERE b.osu_id = p.osu_id AND b.mode = p.mode
+                        AND b.played_at < $2
+                        ORDER BY b.played_at DESC
+                        LIMIT 1
+                    ), p.first_pp) AS gain
+                FROM period p
+            )
+            SELECT
+                op.osu_username,
+                g.mode,
+                g.last_pp as "user_pp!",
+                g.gain as "gain!"
+            FROM gains g
+            JOIN osu_players op ON op.osu_id = g.osu_id
+            WHERE g.gain > 0
+            ORDER BY g.gain DESC
+            LIMIT $4
+            "#,
+            channel_id,
+            from,
+            to,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let beatmaps = sqlx::query_as!(
+            OsuDigestBeatmap,
+            r#"
+            SELECT
+                s.beatmap_id,
+                MAX(s.beatmap_title) as "beatmap_title!",
+                COUNT(*) as "plays!",
+                COUNT(DISTINCT s.osu_id) as "players!"
+            FROM osu_tracking_scores s
+            JOIN osu_tracking ot
+                ON ot.osu_id = s.osu_id AND ot.channel_id = $1
+            WHERE s.played_at >= $2 AND s.played_at < $3
+            GROUP BY s.beatmap_id
+            HAVING COUNT(*) > 1
+            ORDER BY 3 DESC, 4 DESC
+            LIMIT $4
+            "#,
+            channel_id,
+            from,
+            to,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        // Score that took both global and country #1 is listed once
+        let new_firsts = sqlx::query_as!(
+            OsuDigestScore,
+            r#"
+            SELECT
+                op.osu_username, f.beatmap_id, f.beatmap_title,
+                f.pp, NULL::INT2 as top_score_pos
+            FROM (
+                SELECT DISTINCT ON (s.score_id) s.*
+                FROM osu_tracking_first_scores s
+                JOIN osu_tracking ot
+                    ON ot.osu_id = s.osu_id AND ot.channel_id = $1
+                WHERE s.played_at >= $2 AND s.played_at < $3
+                ORDER BY s.score_id
+            ) f
+            JOIN osu_players op ON op.osu_id = f.osu_id
+            ORDER BY f.played_at ASC
+            LIMIT $4
+            "#,
+            channel_id,
+            from,
+            to,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(OsuTrackingDigest {
+            top_plays,
+            pp_gains,
+            beatmaps,
+            new_firsts,
+        })
+    }
+}
@@ -0,0 +1,108 @@
+use eyre::Result;
+
+use crate::Database;
+
+/// Live match followed in the channel, `last_event_id` is the last
+/// event that was processed, games after it weren't posted yet
+#[derive(Debug)]
+pub struct OsuMatchFollow {
+    pub match_id: i64,
+    pub channel_id: i64,
+    pub last_event_id: i64,
+    pub red_wins: i32,
+    pub blue_wins: i32,
+}
+
+impl Database {
+    /// Returns `false` if match is already followed in the channel
+    pub async fn add_osu_match_follow(
+        &self,
+        follow: &OsuMatchFollow,
+    ) -> Result<bool> {
+        let res = sqlx::query!(
+            "insert into osu_match_follows
+            (match_id, channel_id, last_event_id, red_wins, blue_wins)
+            values($1, $2, $3, $4, $5)
+            on conflict do nothing",
+            follow.match_id,
+            follow.channel_id,
+            follow.last_event_id,
+            follow.red_wins,
+            follow.blue_wins
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Returns `false` if match wasn't followed in the channel
+    pub async fn remove_osu_match_follow(
+        &self,
+        match_id: i64,
+        channel_id: i64,
+    ) -> Result<bool> {
+        let res = sqlx::query!(
+            "delete from osu_match_follows
+            where match_id = $1 and channel_id = $2",
+            match_id,
+            channel_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    pub async fn select_osu_match_follows(
+        &self,
+    ) -> Result<Vec<OsuMatchFollow>> {
+        let res = sqlx::query_as!(
+            OsuMatchFollow,
+            "select match_id, channel_id, last_event_id, red_wins, blue_wins
+            from osu_match_follows
+            order by created_at"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    pub async fn count_osu_match_follows(
+        &self,
+        channel_id: i64,
+    ) -> Result<i64> {
+        let res = sqlx::query_scalar!(
+            r#"select count(*) as "count!" from osu_match_follows
+            where channel_id = $1"#,
+            channel_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    pub async fn update_osu_match_follow(
+        &self,
+        follow: &OsuMatchFollow,
+    ) -> Result<()> {
+        sqlx::query!(
+            "update osu_match_follows set
+                last_event_id = $3,
+                red_wins = $4,
+                blue_wins = $5
+            where match_id = $1 and channel_id = $2",
+            follow.match_id,
+            follow.channel_id,
+            follow.last_event_id,
+            follow.red_wins,
+            follow.blue_wins
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
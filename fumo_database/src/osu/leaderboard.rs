@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use eyre::Result;
+
+use crate::Database;
+
+/// Best score of the player on the beatmap with specific mods.
+///
+/// `mods` are normalized acronyms separated by `,` (e.g. `DT,HD`),
+/// empty string stands for nomod. `accuracy` is in `0..=1` range.
+///
+/// Hits are stored in the stable layout: `count_geki` and `count_katu`
+/// are MAX and 200 in mania, `count_katu` is missed droplets in catch.
+#[derive(Debug, Clone)]
+pub struct OsuCountryScore {
+    pub score_id: i64,
+    pub osu_id: i64,
+    pub osu_username: String,
+    pub country: String,
+    pub beatmap_id: i64,
+    pub mode: i16,
+    pub mods: String,
+    pub speed: Option<f32>,
+    pub total_score: i64,
+    pub legacy_total_score: i64,
+    pub accuracy: f32,
+    pub max_combo: i32,
+    pub pp: Option<f32>,
+    pub grade: String,
+    pub count_300: i32,
+    pub count_100: i32,
+    pub count_50: i32,
+    pub count_geki: i32,
+    pub count_katu: i32,
+    pub count_miss: i32,
+    pub played_at: NaiveDateTime,
+}
+
+impl Database {
+    /// Countries that have country leaderboards stored locally,
+    /// which are the ones configured as server defaults
+    pub async fn select_leaderboard_countries(&self) -> Result<Vec<String>> {
+        Ok(sqlx::query_scalar!(
+            r#"SELECT DISTINCT country as "country!" FROM guild_settings
+            WHERE country IS NOT NULL"#
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Returns known `(username, country)` of the players
+    pub async fn select_osu_player_countries(
+        &self,
+        osu_ids: &[i64],
+    ) -> Result<HashMap<i64, (String, String)>> {
+        let rows = sqlx::query!(
+            "SELECT osu_id, osu_username, country FROM osu_player_countries
+            WHERE osu_id = ANY($1::INT8[])",
+            osu_ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|x| (x.osu_id, (x.osu_username, x.country)))
+            .collect())
+    }
+
+    /// All slices should have equal length
+    pub async fn add_osu_player_countries(
+        &self,
+        osu_ids: &[i64],
+        usernames: &[String],
+        countries: &[String],
+    ) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO osu_player_countries (osu_id, osu_username, country)
+            SELECT * FROM UNNEST($1::INT8[], $2::TEXT[], $3::VARCHAR[])
+            ON CONFLICT (osu_id) DO UPDATE
+            SET osu_username = EXCLUDED.osu_username,
+                country = EXCLUDED.country,
+                updated_at = now()",
+            osu_ids,
+            usernames,
+            countries
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Saves the scores that are better than the stored ones
+    /// for the same player, beatmap and mods. Only the best
+    /// one of the duplicates in `scores` is saved
+    pub async fn add_osu_country_scores(
+        &self,
+        scores: &[OsuCountryScore],
+    ) -> Result<()> {
+        if scores.is_empty() {
+            return Ok(());
+        }
+
+        let len = scores.len();
+
+        let mut score_ids = Vec::with_capacity(len);
+        let mut osu_ids = Vec::with_capacity(len);
+        let mut usernames = Vec::with_capacity(len);
+        let mut countries = Vec::with_capacity(len);
+        let mut beatmap_ids = Vec::with_capacity(len);
+        let mut modes = Vec::with_capacity(len);
+        let mut mods = Vec::with_capacity(len);
+        let mut speeds = Vec::with_capacity(len);
+        let mut total_scores = Vec::with_capacity(len);
+        let mut legacy_total_scores = Vec::with_capacity(len);
+        let mut accuracies = Vec::with_capacity(len);
+        let mut max_combos = Vec::with_capacity(len);
+        let mut pps = Vec::with_capacity(len);
+        let mut grades = Vec::with_capacity(len);
+        let mut counts_300 = Vec::with_capacity(len);
+        let mut counts_100 = Vec::with_capacity(len);
+        let mut counts_50 = Vec::with_capacity(len);
+        let mut counts_geki = Vec::with_capacity(len);
+        let mut counts_katu = Vec::with_capacity(len);
+        let mut counts_miss = Vec::with_capacity(len);
+        let mut played_at = Vec::with_capacity(len);
+
+        for score in scores {
+            score_ids.push(score.score_id);
+            osu_ids.push(score.osu_id);
+            usernames.push(score.osu_username.clone());
+            countries.push(score.country.clone());
+            beatmap_ids.push(score.beatmap_id);
+            modes.push(score.mode);
+            mods.push(score.mods.clone());
+            speeds.push(score.speed);
+            total_scores.push(score.total_score);
+            legacy_total_scores.push(score.legacy_total_score);
+            accuracies.push(score.accuracy);
+            max_combos.push(score.max_combo);
+            pps.push(score.pp);
+            grades.push(score.grade.clone());
+            counts_300.push(score.count_300);
+            counts_100.push(score.count_100);
+            counts_50.push(score.count_50);
+            counts_geki.push(score.count_geki);
+            counts_katu.push(score.count_katu);
+            counts_miss.push(score.count_miss);
+            played_at.push(score.played_at);
+        }
+
+        sqlx::query!(
+            "INSERT INTO osu_country_scores (
+                score_id, osu_id, osu_username, country, beatmap_id, mode,
+                mods, speed, total_score, legacy_total_score, accuracy,
+                max_combo, pp, grade, count_300, count_100, count_50,
+                count_geki, count_katu, count_miss, played_at
+            )
+            SELECT DISTINCT ON (osu_id, beatmap_id, mode, mods) *
+            FROM UNNEST(
+                $1::INT8[], $2::INT8[], $3::TEXT[], $4::VARCHAR[],
+                $5::INT8[], $6::INT2[], $7::TEXT[], $8::FLOAT4[],
+                $9::INT8[], $10::INT8[], $11::FLOAT4[], $12::INT4[],
+                $13::FLOAT4[], $14::TEXT[], $15::INT4[], $16::INT4[],
+                $17::INT4[], $18::INT4[], $19::INT4[], $20::INT4[],
+                $21::TIMESTAMP[]
+            ) AS t(
+                score_id, osu_id, osu_username, country, beatmap_id, mode,
+                mods, speed, total_score, legacy_total_score, accuracy,
+                max_combo, pp, grade, count_300, count_100, count_50,
+                count_geki, count_katu, count_miss, played_at
+            )
+            ORDER BY osu_id, beatmap_id, mode, mods, total_score DESC
+            ON CONFLICT (osu_id, beatmap_id, mode, mods) DO UPDATE
+            SET score_id = EXCLUDED.score_id,
+                osu_username = EXCLUDED.osu_username,
+                country = EXCLUDED.country,
+                speed = EXCLUDED.speed,
+                total_score = EXCLUDED.total_score,
+                legacy_total_score = EXCLUDED.legacy_total_score,
+                accuracy = EXCLUDED.accuracy,
+                max_combo = EXCLUDED.max_combo,
+                pp = EXCLUDED.pp,
+                grade = EXCLUDED.grade,
+                count_300 = EXCLUDED.count_300,
+                count_100 = EXCLUDED.count_100,
+                count_50 = EXCLUDED.count_50,
+                count_geki = EXCLUDED.count_geki,
+                count_katu = EXCLUDED.count_katu,
+                count_miss = EXCLUDED.count_miss,
+                played_at = EXCLUDED.played_at
+            WHERE EXCLUDED.total_score > osu_country_scores.total_score",
+            &score_ids,
+            &osu_ids,
+            &usernames,
+            &countries,
+            &beatmap_ids,
+            &modes,
+            &mods,
+            &speeds as &[Option<f32>],
+            &total_scores,
+            &legacy_total_scores,
+            &accuracies,
+            &max_combos,
+            &pps as &[Option<f32>],
+            &grades,
+            &counts_300,
+            &counts_100,
+            &counts_50,
+            &counts_geki,
+            &counts_katu,
+            &counts_miss,
+            &played_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Marks country leaderboard as completely stored,
+    /// `mods` is the filter leaderboard was fetched with
+    pub async fn set_osu_country_leaderboard_fetched(
+        &self,
+        beatmap_id: i64,
+        country: &str,
+        mode: i16,
+        mods: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO osu_country_leaderboards
+            (beatmap_id, country, mode, mods, fetched_at)
+            VALUES ($1, $2, $3, COALESCE($4, '*'), now() AT TIME ZONE 'utc')
+            ON CONFLICT (beatmap_id, country, mode, mods) DO UPDATE
+            SET fetched_at = EXCLUDED.fetched_at",
+            beatmap_id,
+            country,
+            mode,
+            mods
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Whether country leaderboard with the `mods` filter
+    /// was completely fetched in the last `ttl_secs` seconds
+    pub async fn is_osu_country_leaderboard_fresh(
+        &self,
+        beatmap_id: i64,
+        country: &str,
+        mode: i16,
+        mods: Option<&str>,
+        ttl_secs: i64,
+    ) -> Result<bool> {
+        Ok(sqlx::query_scalar!(
+            r#"SELECT EXISTS (
+                SELECT 1 FROM osu_country_leaderboards
+                WHERE beatmap_id = $1 AND country = $2 AND mode = $3
+                AND mods = COALESCE($4, '*')
+                AND fetched_at > now() AT TIME ZONE 'utc'
+                    - make_interval(secs => $5)
+            ) as "exists!""#,
+            beatmap_id,
+            country,
+            mode,
+            mods,
+            ttl_secs as f64
+        )
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    /// Selects country leaderboard of the beatmap in
+    /// the `mode` ruleset sorted by score.
+    ///
+    /// With `mods` only scores with exactly these mods are returned.
+    /// With `best_per_player` only the best score of every player
+    /// is returned, otherwise best score of every mods combination
+    pub async fn select_osu_country_scores(
+        &self,
+        beatmap_id: i64,
+        country: &str,
+        mode: i16,
+        mods: Option<&str>,
+        best_per_player: bool,
+    ) -> Result<Vec<OsuCountryScore>> {
+        Ok(sqlx::query_as!(
+            OsuCountryScore,
+            r#"
+            SELECT * FROM (
+                SELECT DISTINCT ON (
+                    osu_id, CASE WHEN $5 THEN '' ELSE mods END
+                )
+                    score_id, osu_id, osu_username, country, beatmap_id,
+                    mode, mods, speed, total_score, legacy_total_score,
+                    accuracy, max_combo, pp, grade, count_300, count_100,
+                    count_50, count_geki, count_katu, count_miss, played_at
+                FROM osu_country_scores
+                WHERE beatmap_id = $1 AND country = $2 AND mode = $3
+                AND ($4::TEXT IS NULL OR mods = $4)
+                ORDER BY
+                    osu_id,
+                    CASE WHEN $5 THEN '' ELSE mods END,
+                    total_score DESC
+            ) AS t
+            ORDER BY total_score DESC
+            "#,
+            beatmap_id,
+            country,
+            mode,
+            mods,
+            best_per_player
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+}
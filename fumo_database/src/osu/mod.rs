@@ -1,3 +1,11 @@
+pub mod career;
+pub mod digest;
+pub mod follows;
+pub mod leaderboard;
+pub mod snipes;
+pub mod tournament;
+pub mod tracking;
+
 use sqlx::Row;
 use std::collections::HashMap;
 
@@ -19,6 +27,8 @@ pub struct OsuLinkedTrackedUser {
     pub osu_id: i64,
     pub channel_id: i64,
     pub osu_username: String,
+    pub restricted: bool,
+    pub deleted: bool,
 }
 
 #[derive(sqlx::FromRow, Debug)]
@@ -39,6 +49,9 @@ pub struct OsuDbMatch {
     pub name: String,
     pub start_time: NaiveDateTime,
     pub end_time: NaiveDateTime,
+    /// Team names parsed from the tournament match name
+    pub red_team: Option<String>,
+    pub blue_team: Option<String>,
 }
 
 #[derive(sqlx::FromRow, Debug)]
@@ -94,7 +107,7 @@ impl Database {
         Ok(sqlx::query_as!(
             OsuDbMatch,
             "select 
-                id, name, start_time, end_time
+                id, name, start_time, end_time, red_team, blue_team
             from osu_match_game_scores 
             JOIN osu_matches ON osu_match_game_scores.match_id = osu_matches.id 
             WHERE user_id = $1 
@@ -106,23 +119,27 @@ impl Database {
         .await?)
     }
 
+    /// Matches classified as tournament ones,
+    /// optionally only of the provided tournament
     pub async fn get_user_matches_tourney(
         &self,
         user_id: i64,
+        tournament: Option<&str>,
     ) -> Result<Vec<OsuDbMatch>> {
-        // TODO move regex to const?
         Ok(sqlx::query_as!(
             OsuDbMatch,
             r#"select 
-                id, name, start_time, end_time
+                id, name, start_time, end_time, red_team, blue_team
             from osu_match_game_scores 
             JOIN osu_matches ON osu_match_game_scores.match_id = osu_matches.id 
             WHERE user_id = $1 
-			AND osu_matches.name ~ '(.+):\s*(\(*.+\)*)\s*vs\s*(\(*.+\)*)'
+			AND osu_matches.tournament_acronym IS NOT NULL
+			AND ($2::text IS NULL OR osu_matches.tournament_acronym = $2)
             group by osu_matches.id
             ORDER BY start_time DESC
             "#,
-            user_id
+            user_id,
+            tournament
         )
         .fetch_all(&self.pool)
         .await?)
@@ -164,7 +181,8 @@ impl Database {
     ) -> Result<Vec<OsuLinkedTrackedUser>> {
         Ok(sqlx::query_as!(
             OsuLinkedTrackedUser,
-            "select ot.osu_id, ot.channel_id, op.osu_username
+            "select ot.osu_id, ot.channel_id, op.osu_username,
+            op.restricted, op.deleted
             from osu_tracking ot 
             inner join osu_players op 
             on ot.osu_id = op.osu_id where channel_id = $1",
@@ -185,7 +203,8 @@ impl Database {
     ) -> Result<Option<OsuLinkedTrackedUser>> {
         Ok(sqlx::query_as!(
             OsuLinkedTrackedUser,
-            "select ot.osu_id, ot.channel_id, op.osu_username
+            "select ot.osu_id, ot.channel_id, op.osu_username,
+            op.restricted, op.deleted
             from osu_tracking ot 
             inner join osu_players op 
             on ot.osu_id = op.osu_id 
@@ -204,7 +223,8 @@ impl Database {
     ) -> Result<Vec<OsuLinkedTrackedUser>> {
         Ok(sqlx::query_as!(
             OsuLinkedTrackedUser,
-            "select ot.osu_id, ot.channel_id, op.osu_username
+            "select ot.osu_id, ot.channel_id, op.osu_username,
+            op.restricted, op.deleted
             from osu_tracking ot 
             inner join osu_players op 
             on ot.osu_id = op.osu_id where ot.osu_id = $1",
@@ -228,7 +248,11 @@ impl Database {
         select ot.osu_id as osu_id, ot.channel_id as channel_id, op.osu_username as osu_username
         from osu_tracking ot 
         inner join osu_players op 
-        on ot.osu_id = op.osu_id where ot.osu_id = ANY($1::INT8[]) 
+        on ot.osu_id = op.osu_id
+        left join osu_tracking_settings ots
+        on ot.channel_id = ots.channel_id
+        where ot.osu_id = ANY($1::INT8[])
+        and ots.disabled is not true
         ) as t group by osu_id, osu_username
             ",
             users
@@ -533,6 +557,82 @@ impl Database {
         Ok(res)
     }
 
+    pub async fn select_osu_match(
+        &self,
+        match_id: i64,
+    ) -> Result<Option<OsuDbMatch>> {
+        let res = sqlx::query_as!(
+            OsuDbMatch,
+            "SELECT id, name, start_time, end_time, red_team, blue_team
+            FROM osu_matches WHERE id = $1",
+            match_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    /// Games of the matches in the order they were played
+    pub async fn select_match_games(
+        &self,
+        match_ids: &[i64],
+    ) -> Result<Vec<OsuDbMatchGame>> {
+        let res = sqlx::query_as!(
+            OsuDbMatchGame,
+            r#"
+                SELECT 
+                    id,
+                    match_id as "match_id!", 
+                    beatmap_id,
+                    mods,
+                    mode,
+                    scoring_kind,
+                    team_kind,
+                    start_time, end_time
+                FROM osu_match_games WHERE match_id = ANY($1::INT8[])
+                ORDER BY start_time
+            "#,
+            match_ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    pub async fn select_match_scores(
+        &self,
+        match_ids: &[i64],
+    ) -> Result<Vec<OsuDbMatchScore>> {
+        let res = sqlx::query_as!(
+            OsuDbMatchScore,
+            r#"
+            select 
+                game_id as "game_id!", osu_matches.id as "match_id!", 
+                osu_match_game_scores.beatmap_id, user_id, accuracy, 
+                osu_match_game_scores.mods, 
+                score, count50, count100, count300, countgeki, countkatu, countmiss, 
+                max_combo, slot, pass, pp, team,
+                osu_match_games.start_time as "start_time!",
+                osu_match_games.end_time as "end_time!",
+                osu_matches."name" as "match_name!",
+                osu_username as "osu_username?"
+            from osu_match_game_scores 
+            left join osu_match_games 
+                on osu_match_game_scores.game_id = osu_match_games.id
+            left join osu_matches
+                on osu_match_game_scores.match_id = osu_matches.id
+            left join osu_username_kv
+            	on osu_match_game_scores.user_id = osu_username_kv.osu_id
+            where osu_match_game_scores.match_id = ANY($1::INT8[])
+            "#,
+            match_ids
+        ).fetch_all(&self.pool).await?;
+
+        Ok(res)
+    }
+
     pub async fn insert_username(
         &self,
         user_id: i64,
@@ -574,13 +674,8 @@ impl Database {
         &self,
         beatmap_id: i64,
         is_tournament: bool,
+        tournament: Option<&str>,
     ) -> Result<Vec<OsuDbMatchScore>> {
-        let name_filter = if is_tournament {
-            r#"(.+):\s*(\(*.+\)*)\s*vs\s*(\(*.+\)*)"#
-        } else {
-            ".*"
-        };
-
         let res = sqlx::query_as!(
             OsuDbMatchScore,
             r#"
@@ -600,10 +695,12 @@ impl Database {
                 on osu_match_game_scores.match_id = osu_matches.id
             left join osu_username_kv
             	on osu_match_game_scores.user_id = osu_username_kv.osu_id
-            where osu_match_game_scores.beatmap_id = $1) as t1
-            WHERE match_name ~ $2 
+            where osu_match_game_scores.beatmap_id = $1
+                and (not $2 or osu_matches.tournament_acronym is not null)
+                and ($3::text is null or osu_matches.tournament_acronym = $3)
+            ) as t1
             "#,
-            beatmap_id, &name_filter
+            beatmap_id, is_tournament, tournament
         ).fetch_all(&self.pool).await?;
 
         Ok(res)
@@ -614,13 +711,8 @@ impl Database {
         beatmap_id: i64,
         user_id: i64,
         is_tournament: bool,
+        tournament: Option<&str>,
     ) -> Result<Vec<OsuDbMatchScore>> {
-        let name_filter = if is_tournament {
-            r#"(.+):\s*(\(*.+\)*)\s*vs\s*(\(*.+\)*)"#
-        } else {
-            ".*"
-        };
-
         let res = sqlx::query_as!(
             OsuDbMatchScore,
             r#"
@@ -638,9 +730,11 @@ impl Database {
                 on osu_match_game_scores.game_id = osu_match_games.id
             left join osu_matches
                 on osu_match_game_scores.match_id = osu_matches.id
-            where osu_match_game_scores.beatmap_id = $1 and osu_match_game_scores.user_id = $2) as t1
-            WHERE match_name ~ $3"#,
-            beatmap_id, user_id, name_filter
+            where osu_match_game_scores.beatmap_id = $1 and osu_match_game_scores.user_id = $2
+                and (not $3 or osu_matches.tournament_acronym is not null)
+                and ($4::text is null or osu_matches.tournament_acronym = $4)
+            ) as t1"#,
+            beatmap_id, user_id, is_tournament, tournament
         ).fetch_all(&self.pool).await?;
 
         Ok(res)
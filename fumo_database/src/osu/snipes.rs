@@ -0,0 +1,165 @@
+use std::collections::{HashMap, HashSet};
+
+use eyre::Result;
+
+use crate::Database;
+
+/// Holder of the country #1 on the beatmap
+#[derive(Debug, Clone)]
+pub struct CountryFirst {
+    pub beatmap_id: i64,
+    pub country: String,
+    pub mode: i16,
+    pub osu_id: i64,
+    pub osu_username: String,
+    pub score_id: i64,
+    pub total_score: i64,
+}
+
+impl Database {
+    /// Returns stored country #1s of provided beatmaps,
+    /// key is `(beatmap_id, country, mode)`
+    pub async fn select_country_firsts(
+        &self,
+        beatmap_ids: &[i64],
+    ) -> Result<HashMap<(i64, String, i16), CountryFirst>> {
+        let firsts = sqlx::query_as!(
+            CountryFirst,
+            "SELECT
+                beatmap_id, country, mode, osu_id, osu_username,
+                score_id, total_score
+            FROM country_firsts
+            WHERE beatmap_id = ANY($1::INT8[])",
+            beatmap_ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(firsts
+            .into_iter()
+            .map(|x| ((x.beatmap_id, x.country.clone(), x.mode), x))
+            .collect())
+    }
+
+    /// Selects `limit` country #1s held by linked or tracked players
+    /// that weren't checked for the longest time
+    pub async fn select_outdated_country_firsts(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<CountryFirst>> {
+        Ok(sqlx::query_as!(
+            CountryFirst,
+            "SELECT
+                cf.beatmap_id, cf.country, cf.mode, cf.osu_id, cf.osu_username,
+                cf.score_id, cf.total_score
+            FROM country_firsts cf
+            WHERE EXISTS (SELECT 1 FROM osu_users ou WHERE ou.osu_id = cf.osu_id)
+            OR EXISTS (SELECT 1 FROM osu_tracking ot WHERE ot.osu_id = cf.osu_id)
+            ORDER BY cf.checked_at ASC
+            LIMIT $1",
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Starts watching country #1s of the linked or tracked players
+    /// from the completely fetched leaderboards. Seeded #1s keep the
+    /// time leaderboard was fetched, so they are re-checked in order
+    pub async fn seed_country_firsts(&self) -> Result<u64> {
+        let res = sqlx::query!(
+            "INSERT INTO country_firsts
+            (beatmap_id, country, mode, osu_id, osu_username,
+            score_id, total_score, checked_at)
+            SELECT
+                t.beatmap_id, t.country, t.mode, t.osu_id, t.osu_username,
+                t.score_id, t.total_score, l.fetched_at
+            FROM osu_country_leaderboards l
+            JOIN LATERAL (
+                SELECT * FROM osu_country_scores s
+                WHERE s.beatmap_id = l.beatmap_id
+                AND s.country = l.country AND s.mode = l.mode
+                ORDER BY s.total_score DESC
+                LIMIT 1
+            ) t ON true
+            WHERE l.mods = '*'
+            AND NOT EXISTS (
+                SELECT 1 FROM country_firsts cf
+                WHERE cf.beatmap_id = l.beatmap_id
+                AND cf.country = l.country AND cf.mode = l.mode
+            )
+            AND (
+                EXISTS (SELECT 1 FROM osu_users ou WHERE ou.osu_id = t.osu_id)
+                OR EXISTS (SELECT 1 FROM osu_tracking ot WHERE ot.osu_id = t.osu_id)
+            )
+            ON CONFLICT (beatmap_id, country, mode) DO NOTHING"
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected())
+    }
+
+    /// Inserts or replaces country #1 and marks it as checked
+    pub async fn set_country_first(&self, first: &CountryFirst) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO country_firsts
+            (beatmap_id, country, mode, osu_id, osu_username,
+            score_id, total_score)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (beatmap_id, country, mode) DO UPDATE
+            SET osu_id = EXCLUDED.osu_id,
+                osu_username = EXCLUDED.osu_username,
+                score_id = EXCLUDED.score_id,
+                total_score = EXCLUDED.total_score,
+                checked_at = EXCLUDED.checked_at",
+            first.beatmap_id,
+            first.country,
+            first.mode,
+            first.osu_id,
+            first.osu_username,
+            first.score_id,
+            first.total_score
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn touch_country_first(
+        &self,
+        first: &CountryFirst,
+    ) -> Result<()> {
+        sqlx::query!(
+            "UPDATE country_firsts SET checked_at = now() AT TIME ZONE 'utc'
+            WHERE beatmap_id = $1 AND country = $2 AND mode = $3",
+            first.beatmap_id,
+            first.country,
+            first.mode
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Filters players that are linked or tracked by anyone
+    pub async fn select_snipe_players(
+        &self,
+        osu_ids: &[i64],
+    ) -> Result<HashSet<i64>> {
+        let players = sqlx::query_scalar!(
+            r#"SELECT osu_id as "osu_id!" FROM osu_users
+            WHERE osu_id = ANY($1::INT8[])
+            UNION
+            SELECT osu_id as "osu_id!" FROM osu_tracking
+            WHERE osu_id = ANY($1::INT8[])"#,
+            osu_ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(players.into_iter().collect())
+    }
+}
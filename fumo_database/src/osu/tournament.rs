@@ -0,0 +1,348 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use eyre::Result;
+
+use crate::Database;
+
+/// Tournament match parsed from the match name,
+/// e.g. `OWC2023: (United States) vs (Germany)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct OsuTournamentMatch {
+    pub acronym: String,
+    /// Team or player name of the red side
+    pub red_team: String,
+    /// Team or player name of the blue side
+    pub blue_team: String,
+}
+
+#[derive(Debug)]
+pub struct OsuMatchName {
+    pub id: i64,
+    pub name: String,
+}
+
+/// Beatmap of the tournament mappool derived from the played games
+#[derive(Debug)]
+pub struct OsuTournamentBeatmap {
+    pub beatmap_id: i64,
+    /// Amount of games the beatmap was played in
+    pub picks: i64,
+    /// Most common game mods
+    pub mods: i64,
+    pub first_played: NaiveDateTime,
+    pub average_score: f64,
+    pub top_score: Option<i64>,
+    pub top_user_id: Option<i64>,
+    pub top_username: Option<String>,
+}
+
+/// Tournament games grouped by the week they were played in
+#[derive(Debug)]
+pub struct OsuTournamentStage {
+    pub week: NaiveDate,
+    pub matches: i64,
+    pub games: i64,
+    pub beatmaps: i64,
+}
+
+/// Acronyms are stored uppercase without whitespaces,
+/// so `owc 2023` and `OWC2023` are the same tournament
+pub fn normalize_acronym(acronym: &str) -> String {
+    acronym
+        .chars()
+        .filter(|x| !x.is_whitespace())
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+/// Removes whitespaces and wrapping parentheses around the team name
+fn team_name(name: &str) -> Option<String> {
+    let name = name.trim();
+
+    let name = name
+        .strip_prefix('(')
+        .and_then(|x| x.strip_suffix(')'))
+        .unwrap_or(name)
+        .trim();
+
+    (!name.is_empty()).then(|| name.to_owned())
+}
+
+impl OsuTournamentMatch {
+    /// Parses names like `ACRONYM: (Red) vs (Blue)` or `ACRONYM: Red vs. Blue`
+    pub fn parse(name: &str) -> Option<Self> {
+        let (acronym, teams) = name.split_once(':')?;
+
+        let acronym = normalize_acronym(acronym);
+
+        if acronym.is_empty() {
+            return None;
+        }
+
+        // Lowercase ascii keeps byte positions the same
+        let lowercase = teams.to_ascii_lowercase();
+
+        // Every `vs` separated from the team names, the one between
+        // parentheses is preferred since team names can contain `vs`
+        let separators: Vec<(usize, usize, bool)> = lowercase
+            .match_indices("vs")
+            .filter_map(|(idx, _)| {
+                let before = lowercase[..idx].chars().next_back()?;
+
+                let mut end = idx + 2;
+                if lowercase[end..].starts_with('.') {
+                    end += 1;
+                }
+
+                let after = lowercase[end..].chars().next()?;
+
+                let is_separated = (before.is_whitespace() || before == ')')
+                    && (after.is_whitespace() || after == '(');
+
+                let is_parenthesized =
+                    lowercase[..idx].trim_end().ends_with(')')
+                        && lowercase[end..].trim_start().starts_with('(');
+
+                is_separated.then_some((idx, end, is_parenthesized))
+            })
+            .collect();
+
+        let (red_end, blue_start, _) = separators
+            .iter()
+            .find(|x| x.2)
+            .or(separators.first())
+            .copied()?;
+
+        Some(Self {
+            acronym,
+            red_team: team_name(&teams[..red_end])?,
+            blue_team: team_name(&teams[blue_start..])?,
+        })
+    }
+}
+
+impl Database {
+    /// Stores tournament classification of the matches
+    /// and creates tournaments that weren't seen before
+    pub async fn update_osu_match_tournaments(
+        &self,
+        matches: &[(i64, OsuTournamentMatch)],
+    ) -> Result<()> {
+        if matches.is_empty() {
+            return Ok(());
+        }
+
+        let mut ids = Vec::with_capacity(matches.len());
+        let mut acronyms = Vec::with_capacity(matches.len());
+        let mut red_teams = Vec::with_capacity(matches.len());
+        let mut blue_teams = Vec::with_capacity(matches.len());
+
+        for (id, tournament) in matches {
+            ids.push(*id);
+            acronyms.push(tournament.acronym.clone());
+            red_teams.push(tournament.red_team.clone());
+            blue_teams.push(tournament.blue_team.clone());
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "insert into osu_tournaments(acronym)
+            select distinct unnest($1::text[])
+            on conflict do nothing",
+            &acronyms
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "update osu_matches m set
+                tournament_acronym = t.acronym,
+                red_team = t.red_team,
+                blue_team = t.blue_team
+            from unnest($1::int8[], $2::text[], $3::text[], $4::text[])
+                as t(id, acronym, red_team, blue_team)
+            where m.id = t.id",
+            &ids,
+            &acronyms,
+            &red_teams,
+            &blue_teams
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Beatmaps in the order they were played for the first time
+    pub async fn select_tournament_beatmaps(
+        &self,
+        acronym: &str,
+    ) -> Result<Vec<OsuTournamentBeatmap>> {
+        let res = sqlx::query_as!(
+            OsuTournamentBeatmap,
+            r#"
+            with tournament_games as (
+                select g.id, g.beatmap_id, g.mods, g.start_time
+                from osu_match_games g
+                join osu_matches m on m.id = g.match_id
+                where m.tournament_acronym = $1
+            ),
+            average_scores as (
+                select s.beatmap_id, avg(s.score)::float8 as average_score
+                from osu_match_game_scores s
+                join tournament_games g on g.id = s.game_id
+                group by s.beatmap_id
+            ),
+            top_scores as (
+                select distinct on (s.beatmap_id) s.beatmap_id, s.user_id, s.score
+                from osu_match_game_scores s
+                join tournament_games g on g.id = s.game_id
+                order by s.beatmap_id, s.score desc
+            )
+            select
+                g.beatmap_id,
+                count(*) as "picks!",
+                mode() within group (order by g.mods) as "mods!",
+                min(g.start_time) as "first_played!",
+                coalesce(a.average_score, 0) as "average_score!",
+                t.score as "top_score?",
+                t.user_id as "top_user_id?",
+                kv.osu_username as "top_username?"
+            from tournament_games g
+            left join average_scores a on a.beatmap_id = g.beatmap_id
+            left join top_scores t on t.beatmap_id = g.beatmap_id
+            left join osu_username_kv kv on kv.osu_id = t.user_id
+            group by g.beatmap_id, a.average_score, t.score, t.user_id, kv.osu_username
+            order by 4, 2 desc
+            "#,
+            acronym
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    pub async fn select_tournament_stages(
+        &self,
+        acronym: &str,
+    ) -> Result<Vec<OsuTournamentStage>> {
+        let res = sqlx::query_as!(
+            OsuTournamentStage,
+            r#"
+            select
+                date_trunc('week', g.start_time)::date as "week!",
+                count(distinct g.match_id) as "matches!",
+                count(*) as "games!",
+                count(distinct g.beatmap_id) as "beatmaps!"
+            from osu_match_games g
+            join osu_matches m on m.id = g.match_id
+            where m.tournament_acronym = $1
+            group by 1
+            order by 1
+            "#,
+            acronym
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    /// Matches with id greater than `after_id`, ordered by id
+    pub async fn select_osu_match_names(
+        &self,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<OsuMatchName>> {
+        let res = sqlx::query_as!(
+            OsuMatchName,
+            "select id, name from osu_matches
+            where id > $1
+            order by id
+            limit $2",
+            after_id,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tournament_match(
+        acronym: &str,
+        red_team: &str,
+        blue_team: &str,
+    ) -> Option<OsuTournamentMatch> {
+        Some(OsuTournamentMatch {
+            acronym: acronym.to_owned(),
+            red_team: red_team.to_owned(),
+            blue_team: blue_team.to_owned(),
+        })
+    }
+
+    #[test]
+    fn test_parse_match_name() {
+        let cases = [
+            (
+                "OWC2023: (Canada) VS (Germany)",
+                tournament_match("OWC2023", "Canada", "Germany"),
+            ),
+            (
+                "OWC2023: (United States) vs (Germany)",
+                tournament_match("OWC2023", "United States", "Germany"),
+            ),
+            (
+                "owc 2023: (Canada) vs (Germany)",
+                tournament_match("OWC2023", "Canada", "Germany"),
+            ),
+            (
+                "CWC: Canada vs. Germany",
+                tournament_match("CWC", "Canada", "Germany"),
+            ),
+            (
+                "CWC: (Canada) vs.(Germany)",
+                tournament_match("CWC", "Canada", "Germany"),
+            ),
+            (
+                "OWC2023: (Canada)vs(Germany)",
+                tournament_match("OWC2023", "Canada", "Germany"),
+            ),
+            (
+                "5WC: (Team vs Everyone) vs (Versus)",
+                tournament_match("5WC", "Team vs Everyone", "Versus"),
+            ),
+            (
+                "5WC: (Canvas) vs (Elvs Club)",
+                tournament_match("5WC", "Canvas", "Elvs Club"),
+            ),
+            (
+                "MWC: (日本) vs (대한민국)",
+                tournament_match("MWC", "日本", "대한민국"),
+            ),
+            (
+                "MWC: Ünïcödé vs Ελλάδα",
+                tournament_match("MWC", "Ünïcödé", "Ελλάδα"),
+            ),
+            ("(Canada) vs (Germany)", None),
+            (": (Canada) vs (Germany)", None),
+            ("OWC2023: () vs (Germany)", None),
+            ("OWC2023: (Canada) vs ", None),
+            ("OWC2023: (Canada) vs", None),
+            ("OWC2023: Canada versus Germany", None),
+            ("OWC2023: Canadavs Germany", None),
+        ];
+
+        for (name, expected) in cases {
+            assert_eq!(OsuTournamentMatch::parse(name), expected, "{name}");
+        }
+    }
+}
@@ -0,0 +1,546 @@
+use std::collections::HashMap;
+
+use eyre::Result;
+
+use crate::Database;
+
+/// Pending tracking message, `embed` is a serialized discord embed
+#[derive(Debug)]
+pub struct OsuTrackingDelivery {
+    pub id: i64,
+    pub channel_id: i64,
+    pub osu_id: i64,
+    pub osu_username: String,
+    pub avatar_url: String,
+    pub embed: String,
+    pub attempts: i16,
+    /// Whether delivery can be attempted right now
+    pub due: bool,
+    pub webhook_id: Option<i64>,
+    pub webhook_token: Option<String>,
+}
+
+/// Which scores are announced on the channel, `firsts` is
+/// `0` - disabled, `1` - global leaderboard, `2` - country leaderboard
+#[derive(Debug, Clone, Copy)]
+pub struct OsuTrackingChannelSettings {
+    pub style: i16,
+    pub top_plays: bool,
+    pub firsts: i16,
+}
+
+impl Default for OsuTrackingChannelSettings {
+    fn default() -> Self {
+        Self {
+            style: 0,
+            top_plays: true,
+            firsts: 0,
+        }
+    }
+}
+
+/// Last known holder of the beatmap #1
+#[derive(Debug)]
+pub struct OsuTrackingFirst {
+    pub osu_id: i64,
+    pub osu_username: String,
+    pub score_id: i64,
+}
+
+impl Database {
+    /// Sets notification style for the provided tracking channel
+    pub async fn set_osu_tracking_style(
+        &self,
+        channel_id: i64,
+        style: i16,
+    ) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO osu_tracking_settings (channel_id, style)
+            VALUES ($1, $2)
+            ON CONFLICT (channel_id) DO UPDATE SET style = EXCLUDED.style",
+            channel_id,
+            style
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_osu_tracking_style(
+        &self,
+        channel_id: i64,
+    ) -> Result<Option<i16>> {
+        Ok(sqlx::query_scalar!(
+            "SELECT style FROM osu_tracking_settings WHERE channel_id = $1",
+            channel_id
+        )
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    /// Batched way to select notification settings
+    ///
+    /// Channels without any settings are not present in the result
+    pub async fn select_osu_tracking_channel_settings(
+        &self,
+        channel_ids: &[i64],
+    ) -> Result<HashMap<i64, OsuTrackingChannelSettings>> {
+        let rows = sqlx::query!(
+            "SELECT channel_id, style, top_plays, firsts
+            FROM osu_tracking_settings
+            WHERE channel_id = ANY($1::INT8[])",
+            channel_ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|x| {
+                let settings = OsuTrackingChannelSettings {
+                    style: x.style,
+                    top_plays: x.top_plays,
+                    firsts: x.firsts,
+                };
+
+                (x.channel_id, settings)
+            })
+            .collect())
+    }
+
+    /// Sets which kinds of scores are announced on the channel
+    pub async fn set_osu_tracking_mode(
+        &self,
+        channel_id: i64,
+        top_plays: Option<bool>,
+        firsts: Option<i16>,
+    ) -> Result<OsuTrackingChannelSettings> {
+        Ok(sqlx::query_as!(
+            OsuTrackingChannelSettings,
+            "INSERT INTO osu_tracking_settings (channel_id, top_plays, firsts)
+            VALUES ($1, COALESCE($2::BOOL, true), COALESCE($3::INT2, 0))
+            ON CONFLICT (channel_id) DO UPDATE
+            SET top_plays = COALESCE($2::BOOL, osu_tracking_settings.top_plays),
+                firsts = COALESCE($3::INT2, osu_tracking_settings.firsts)
+            RETURNING style, top_plays, firsts",
+            channel_id,
+            top_plays,
+            firsts
+        )
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    /// Stores new holder of the beatmap #1 and returns previous one.
+    /// Global leaderboard is stored with an empty `country_code`
+    pub async fn replace_osu_tracking_first(
+        &self,
+        beatmap_id: i64,
+        mode: i16,
+        country_code: &str,
+        osu_id: i64,
+        osu_username: &str,
+        score_id: i64,
+    ) -> Result<Option<OsuTrackingFirst>> {
+        Ok(sqlx::query_as!(
+            OsuTrackingFirst,
+            r#"
+            WITH previous AS (
+                SELECT osu_id, osu_username, score_id
+                FROM osu_tracking_firsts
+                WHERE beatmap_id = $1 AND mode = $2 AND country_code = $3
+                FOR UPDATE
+            ), updated AS (
+                INSERT INTO osu_tracking_firsts
+                (beatmap_id, mode, country_code, osu_id, osu_username, score_id)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (beatmap_id, mode, country_code) DO UPDATE
+                SET osu_id = EXCLUDED.osu_id,
+                    osu_username = EXCLUDED.osu_username,
+                    score_id = EXCLUDED.score_id,
+                    updated_at = now()
+            )
+            SELECT
+                osu_id as "osu_id!",
+                osu_username as "osu_username!",
+                score_id as "score_id!"
+            FROM previous
+            "#,
+            beatmap_id,
+            mode,
+            country_code,
+            osu_id,
+            osu_username,
+            score_id
+        )
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    /// Enqueues same score for multiple channels,
+    /// `channel_ids` and `embeds` should have equal length
+    pub async fn add_osu_tracking_deliveries(
+        &self,
+        osu_id: i64,
+        osu_username: &str,
+        avatar_url: &str,
+        channel_ids: &[i64],
+        embeds: &[String],
+    ) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO osu_tracking_deliveries
+            (channel_id, osu_id, osu_username, avatar_url, embed)
+            SELECT channel_id, $1, $2, $3, embed
+            FROM UNNEST($4::INT8[], $5::TEXT[]) AS t(channel_id, embed)",
+            osu_id,
+            osu_username,
+            avatar_url,
+            channel_ids,
+            embeds
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn count_osu_tracking_deliveries(&self) -> Result<i64> {
+        Ok(sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!" FROM osu_tracking_deliveries"#
+        )
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    /// Selects oldest pending deliveries in the order they were enqueued.
+    /// Only first `per_channel` deliveries of the channels with due
+    /// head of the queue are taken, so postponed channels don't
+    /// take the place of the others
+    pub async fn select_osu_tracking_deliveries(
+        &self,
+        limit: i64,
+        per_channel: i64,
+    ) -> Result<Vec<OsuTrackingDelivery>> {
+        Ok(sqlx::query_as!(
+            OsuTrackingDelivery,
+            r#"
+            SELECT
+                d.id, d.channel_id, d.osu_id, d.osu_username,
+                d.avatar_url, d.embed, d.attempts,
+                d.next_attempt_at <= now() as "due!",
+                s.webhook_id as "webhook_id?",
+                s.webhook_token as "webhook_token?"
+            FROM (
+                SELECT
+                    *,
+                    row_number() OVER w as position,
+                    first_value(next_attempt_at) OVER w as head_attempt_at
+                FROM osu_tracking_deliveries
+                WINDOW w AS (PARTITION BY channel_id ORDER BY id)
+            ) d
+            LEFT JOIN osu_tracking_settings s
+                ON s.channel_id = d.channel_id
+            WHERE d.position <= $2 AND d.head_attempt_at <= now()
+            ORDER BY d.id
+            LIMIT $1
+            "#,
+            limit,
+            per_channel
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn remove_osu_tracking_deliveries(
+        &self,
+        ids: &[i64],
+    ) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM osu_tracking_deliveries WHERE id = ANY($1::INT8[])",
+            ids
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Increments attempts counter and delays next attempt
+    pub async fn postpone_osu_tracking_deliveries(
+        &self,
+        ids: &[i64],
+        delay_secs: f64,
+    ) -> Result<()> {
+        sqlx::query!(
+            "UPDATE osu_tracking_deliveries
+            SET attempts = attempts + 1,
+                next_attempt_at = now() + make_interval(secs => $2)
+            WHERE id = ANY($1::INT8[])",
+            ids,
+            delay_secs
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Passing `None` disables webhook delivery for the channel
+    pub async fn set_osu_tracking_webhook(
+        &self,
+        channel_id: i64,
+        webhook: Option<(i64, &str)>,
+    ) -> Result<()> {
+        let (webhook_id, webhook_token) = webhook.unzip();
+
+        sqlx::query!(
+            "INSERT INTO osu_tracking_settings
+            (channel_id, webhook_id, webhook_token)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (channel_id) DO UPDATE
+            SET webhook_id = EXCLUDED.webhook_id,
+                webhook_token = EXCLUDED.webhook_token",
+            channel_id,
+            webhook_id,
+            webhook_token
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_osu_tracking_webhook(
+        &self,
+        channel_id: i64,
+    ) -> Result<Option<i64>> {
+        Ok(sqlx::query_scalar!(
+            "SELECT webhook_id FROM osu_tracking_settings
+            WHERE channel_id = $1",
+            channel_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .flatten())
+    }
+
+    /// Resets failures of the channel and enables it back
+    /// if it was disabled by maintenance
+    pub async fn enable_osu_tracking_channel(
+        &self,
+        channel_id: i64,
+        guild_id: Option<i64>,
+    ) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO osu_tracking_settings (channel_id, guild_id)
+            VALUES ($1, $2)
+            ON CONFLICT (channel_id) DO UPDATE
+            SET guild_id = COALESCE(
+                    EXCLUDED.guild_id, osu_tracking_settings.guild_id
+                ),
+                failures = 0,
+                disabled = false",
+            channel_id,
+            guild_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Remembers the guild of the channel, channels that were set up
+    /// before the guild was stored don't have it
+    pub async fn set_osu_tracking_guild(
+        &self,
+        channel_id: i64,
+        guild_id: i64,
+    ) -> Result<()> {
+        sqlx::query!(
+            "UPDATE osu_tracking_settings SET guild_id = $2
+            WHERE channel_id = $1 AND guild_id IS NULL",
+            channel_id,
+            guild_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn add_osu_tracking_failure(
+        &self,
+        channel_id: i64,
+    ) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO osu_tracking_settings (channel_id, failures)
+            VALUES ($1, 1)
+            ON CONFLICT (channel_id) DO UPDATE
+            SET failures = osu_tracking_settings.failures + 1",
+            channel_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn reset_osu_tracking_failures(
+        &self,
+        channel_id: i64,
+    ) -> Result<()> {
+        sqlx::query!(
+            "UPDATE osu_tracking_settings SET failures = 0
+            WHERE channel_id = $1 AND failures > 0",
+            channel_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Returns enabled channels with at least `failures` failed deliveries
+    /// along with their guild id
+    pub async fn select_failing_osu_tracking_channels(
+        &self,
+        failures: i16,
+    ) -> Result<Vec<(i64, Option<i64>)>> {
+        let rows = sqlx::query!(
+            "SELECT channel_id, guild_id FROM osu_tracking_settings
+            WHERE failures >= $1 AND NOT disabled",
+            failures
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|x| (x.channel_id, x.guild_id))
+            .collect())
+    }
+
+    /// Disables channel and drops everything that is waiting for delivery
+    pub async fn disable_osu_tracking_channel(
+        &self,
+        channel_id: i64,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "UPDATE osu_tracking_settings SET disabled = true
+            WHERE channel_id = $1",
+            channel_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "DELETE FROM osu_tracking_deliveries WHERE channel_id = $1",
+            channel_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Selects tracked players that were refreshed the longest time ago
+    pub async fn select_outdated_osu_players(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<i64>> {
+        Ok(sqlx::query_scalar!(
+            "SELECT op.osu_id FROM osu_players op
+            WHERE EXISTS (
+                SELECT 1 FROM osu_tracking ot WHERE ot.osu_id = op.osu_id
+            )
+            ORDER BY op.updated_at ASC NULLS FIRST
+            LIMIT $1",
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Bulk update of players fetched from the api,
+    /// all slices should have equal length
+    pub async fn update_osu_players(
+        &self,
+        osu_ids: &[i64],
+        usernames: &[String],
+        deleted: &[bool],
+    ) -> Result<()> {
+        sqlx::query!(
+            "UPDATE osu_players op
+            SET osu_username = t.osu_username,
+                deleted = t.deleted,
+                restricted = false,
+                updated_at = now()
+            FROM UNNEST($1::INT8[], $2::TEXT[], $3::BOOL[])
+                AS t(osu_id, osu_username, deleted)
+            WHERE op.osu_id = t.osu_id",
+            osu_ids,
+            usernames,
+            deleted
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Players missing from the api responses are considered restricted
+    pub async fn set_osu_players_restricted(
+        &self,
+        osu_ids: &[i64],
+    ) -> Result<()> {
+        sqlx::query!(
+            "UPDATE osu_players
+            SET restricted = true, updated_at = now()
+            WHERE osu_id = ANY($1::INT8[])",
+            osu_ids
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Adds multiple players to the tracking of the channel at once,
+    /// already tracked players are skipped.
+    /// `osu_ids` and `usernames` should have equal length
+    ///
+    /// Returns ids of the newly tracked players
+    pub async fn add_osu_tracking_bulk(
+        &self,
+        channel_id: i64,
+        osu_ids: &[i64],
+        usernames: &[String],
+    ) -> Result<Vec<i64>> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            WITH players AS (
+                INSERT INTO osu_players (osu_id, osu_username)
+                SELECT DISTINCT ON (osu_id) osu_id, osu_username
+                FROM UNNEST($2::INT8[], $3::TEXT[]) AS t(osu_id, osu_username)
+                ON CONFLICT (osu_id) DO UPDATE
+                SET osu_username = EXCLUDED.osu_username
+                RETURNING osu_id
+            )
+            INSERT INTO osu_tracking (osu_id, channel_id)
+            SELECT osu_id, $1 FROM players
+            ON CONFLICT (osu_id, channel_id) DO NOTHING
+            RETURNING osu_id
+            "#,
+            channel_id,
+            osu_ids,
+            usernames
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+}
@@ -107,6 +107,7 @@ pub fn listing(args: TokenStream, input: TokenStream) -> TokenStream {
     // Page navigation methods
     let methods = quote! {
         impl #struct_name {
+            #[allow(clippy::too_many_arguments)]
             pub fn new(#(#field_names: #field_types),*) -> Self {
                 Self {
                     current_page: 1,
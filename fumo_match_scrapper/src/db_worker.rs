@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use fumo_database::Database;
+use fumo_database::{osu::tournament::OsuTournamentMatch, Database};
 use osu_api::models::osu_matches::OsuMatchGet;
 use tokio::sync::mpsc::UnboundedReceiver;
 use tokio_util::sync::CancellationToken;
@@ -38,6 +38,23 @@ pub async fn worker(
                     continue 'main_loop;
                 };
 
+                if let Some(tournament) =
+                    OsuTournamentMatch::parse(&osu_match.osu_match.name)
+                {
+                    let _ = db
+                        .update_osu_match_tournaments(&[(
+                            osu_match.osu_match.id,
+                            tournament,
+                        )])
+                        .await
+                        .inspect_err(|e| {
+                            println!(
+                                "[{}] Failed to classify tournament match: {e}",
+                                osu_match.osu_match.id
+                            )
+                        });
+                }
+
                 for event in osu_match.events {
                     if event.game.is_none() {
                         continue;
@@ -2,6 +2,7 @@ mod db_worker;
 mod live_scrapper;
 mod match_not_found;
 mod scrap_worker;
+mod tournament_backfill;
 
 use clap::{command, Parser, Subcommand};
 use fumo_database::Database;
@@ -43,6 +44,9 @@ pub enum ScrapperKind {
         file: PathBuf,
     },
     Live {},
+    /// Classify already scrapped matches into tournaments
+    /// (uses batch size for the amount of matches per query)
+    Backfill {},
 }
 
 #[derive(Parser, Debug)]
@@ -175,6 +179,14 @@ async fn main() {
                 args.batch_size,
             ));
         }
+        ScrapperKind::Backfill {} => {
+            tokio::select! {
+                _ = tournament_backfill::run(db.clone(), args.batch_size) => {},
+                _ = signal::ctrl_c() => {},
+            };
+
+            return;
+        }
         ScrapperKind::Live {} => {
             live_scrapper::run(
                 osu_api.clone(),
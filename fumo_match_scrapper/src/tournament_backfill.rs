@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use fumo_database::{osu::tournament::OsuTournamentMatch, Database};
+
+/// Goes through all stored matches from the oldest id
+/// and classifies the tournament ones
+pub async fn run(db: Arc<Database>, batch_size: usize) {
+    let mut last_id = 0;
+    let mut classified = 0;
+
+    loop {
+        let matches =
+            match db.select_osu_match_names(last_id, batch_size as i64).await {
+                Ok(matches) => matches,
+                Err(e) => {
+                    println!("[BACKFILL] Failed to select matches: {e}");
+                    return;
+                }
+            };
+
+        let Some(last) = matches.last() else {
+            break;
+        };
+
+        last_id = last.id;
+
+        let tournaments: Vec<(i64, OsuTournamentMatch)> = matches
+            .iter()
+            .filter_map(|x| {
+                OsuTournamentMatch::parse(&x.name)
+                    .map(|tourney| (x.id, tourney))
+            })
+            .collect();
+
+        if let Err(e) = db.update_osu_match_tournaments(&tournaments).await {
+            println!(
+                "[BACKFILL] Failed to update matches up to {last_id}: {e}"
+            );
+            return;
+        }
+
+        classified += tournaments.len();
+
+        println!(
+            "[BACKFILL] Processed matches up to {last_id}: {classified} tournament matches"
+        );
+    }
+
+    println!("[BACKFILL] Done: {classified} tournament matches");
+}
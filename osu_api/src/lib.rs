@@ -10,8 +10,8 @@ use fallback_models::FallbackBeatmapScores;
 use models::{
     osu_matches::{OsuMatchContainer, OsuMatchGet},
     osu_mods::OsuModsLazer,
-    BeatmapUserScore, GetUsersResponse, OsuBeatmapAttributes,
-    ScoresBatch,
+    BeatmapUserScore, GetBeatmapsResponse, GetUsersResponse,
+    OsuBeatmapAttributes, ScoresBatch,
 };
 use reqwest::{
     header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, COOKIE, USER_AGENT},
@@ -20,11 +20,11 @@ use reqwest::{
 
 use self::models::{
     osu_leaderboard::OsuLeaderboardLazer, ApiError, GetRanking, GetUserScores,
-    OauthResponse, OsuBeatmap, OsuGameMode, OsuScore, OsuUserExtended,
-    RankingKind, Rankings, UserId,
+    OauthResponse, OsuBeatmap, OsuBeatmapset, OsuGameMode, OsuScore,
+    OsuUserExtended, RankingKind, Rankings, UserId,
 };
 
-use std::{fmt::Write, time::Duration};
+use std::{fmt::Write, ops::RangeInclusive, time::Duration};
 
 use self::metrics::Metrics;
 
@@ -42,6 +42,9 @@ static OSU_BASE: &str = "https://osu.ppy.sh";
 static CATBOY_BASE: &str = "https://catboy.best";
 static OSU_API_BASE: &str = "https://osu.ppy.sh/api/v2";
 
+/// Amount of players returned by the rankings endpoint per page
+const RANKINGS_PAGE_SIZE: usize = 50;
+
 type ApiResult<T> = Result<T, OsuApiError>;
 
 pub enum ApiKind {
@@ -106,6 +109,20 @@ impl OsuToken {
     }
 }
 
+/// Appends `mods[]` query parameters, acronyms are
+/// separated by `,` and empty string stands for nomod
+fn push_mods_query(link: &mut String, mods: Option<&str>) {
+    match mods {
+        Some("") => link.push_str("&mods[]=NM"),
+        Some(mods) => {
+            for acronym in mods.split(',').filter(|x| !x.is_empty()) {
+                let _ = write!(link, "&mods[]={acronym}");
+            }
+        }
+        None => {}
+    }
+}
+
 impl OsuApi {
     async fn make_request<T: DeserializeOwned>(
         &self,
@@ -330,16 +347,27 @@ impl OsuApi {
         }
     }
 
+    /// Best score of the user on the beatmap, `mods` are
+    /// concatenated acronyms, empty string stands for nomod.
+    /// `mode` other than the beatmap ruleset stands for converts
     pub async fn get_user_beatmap_scores(
         &self,
         beatmap_id: i64,
         user_id: UserId,
+        mods: Option<&str>,
+        mode: Option<OsuGameMode>,
     ) -> ApiResult<BeatmapUserScore> {
-        let link = format!(
-            "{OSU_API_BASE}/beatmaps/{}/scores/users/{}",
+        let mut link = format!(
+            "{OSU_API_BASE}/beatmaps/{}/scores/users/{}?",
             beatmap_id, user_id
         );
 
+        push_mods_query(&mut link, mods);
+
+        if let Some(mode) = mode {
+            let _ = write!(link, "&mode={mode}");
+        }
+
         let r = self
             .make_request(&link, Method::GET, ApiKind::General, None)
             .await?;
@@ -367,6 +395,99 @@ impl OsuApi {
         Ok(r)
     }
 
+    /// Beatmaps that weren't found are absent in the response
+    pub async fn get_beatmaps(
+        &self,
+        beatmap_ids: &[i64],
+    ) -> ApiResult<Vec<OsuBeatmap>> {
+        let link = format!("{OSU_API_BASE}/beatmaps");
+        let mut result = Vec::with_capacity(beatmap_ids.len());
+
+        for chunk in beatmap_ids.chunks(50) {
+            let mut url = reqwest::Url::parse(&link)
+                .map_err(|_| OsuApiError::FromStrError)?;
+
+            {
+                let mut query = url.query_pairs_mut();
+
+                for id in chunk {
+                    query.append_pair("ids[]", &id.to_string());
+                }
+            }
+
+            self.stats
+                .counters
+                .with_label_values(&["get_beatmaps"])
+                .inc();
+
+            let response: GetBeatmapsResponse = self
+                .make_request(url.as_str(), Method::GET, ApiKind::General, None)
+                .await?;
+
+            result.extend(response.beatmaps);
+        }
+
+        Ok(result)
+    }
+
+    pub async fn get_beatmapset(
+        &self,
+        beatmapset_id: i64,
+    ) -> ApiResult<OsuBeatmapset> {
+        let link = format!("{OSU_API_BASE}/beatmapsets/{beatmapset_id}");
+
+        let r = self
+            .make_request(&link, Method::GET, ApiKind::General, None)
+            .await?;
+
+        self.stats
+            .counters
+            .with_label_values(&["get_beatmapset"])
+            .inc();
+
+        Ok(r)
+    }
+
+    /// Legacy score links contain ruleset along with the score id
+    pub async fn get_score(
+        &self,
+        score_id: i64,
+        mode: Option<OsuGameMode>,
+    ) -> ApiResult<OsuScore> {
+        let link = match mode {
+            Some(mode) => format!("{OSU_API_BASE}/scores/{mode}/{score_id}"),
+            None => format!("{OSU_API_BASE}/scores/{score_id}"),
+        };
+
+        let r = self
+            .make_request(&link, Method::GET, ApiKind::General, None)
+            .await?;
+
+        self.stats.counters.with_label_values(&["get_score"]).inc();
+
+        Ok(r)
+    }
+
+    /// Looks beatmap up by MD5 checksum of the `.osu` file
+    pub async fn lookup_beatmap(
+        &self,
+        checksum: &str,
+    ) -> ApiResult<OsuBeatmap> {
+        let link =
+            format!("{OSU_API_BASE}/beatmaps/lookup?checksum={checksum}");
+
+        let r = self
+            .make_request(&link, Method::GET, ApiKind::General, None)
+            .await?;
+
+        self.stats
+            .counters
+            .with_label_values(&["lookup_beatmap"])
+            .inc();
+
+        Ok(r)
+    }
+
     pub async fn get_beatmap_attributes(
         &self,
         bid: i64,
@@ -397,25 +518,46 @@ impl OsuApi {
         Ok(r)
     }
 
+    /// Fetches `amount` of players starting from the `ranking.page`
     pub async fn get_rankings(
         &self,
         ranking: &GetRanking,
         amount: usize,
     ) -> ApiResult<Rankings> {
-        let mut link = String::with_capacity(50);
-        let mut buffer: Vec<OsuUserStatistics> = Vec::with_capacity(amount);
+        // Pages are starting from 1
+        let page = ranking.page.unwrap_or(1).max(1) as usize;
+        let first_rank = (page - 1) * RANKINGS_PAGE_SIZE + 1;
+
+        self.get_rankings_range(
+            ranking,
+            first_rank..=first_rank + amount.max(1) - 1,
+        )
+        .await
+    }
+
+    /// Fetches players placed inside of the provided ranks range,
+    /// `ranking.page` is ignored
+    pub async fn get_rankings_range(
+        &self,
+        ranking: &GetRanking,
+        ranks: RangeInclusive<usize>,
+    ) -> ApiResult<Rankings> {
+        let (first_rank, last_rank) = (*ranks.start(), *ranks.end());
 
-        let pages: usize = ((amount as f32 / 50.0).ceil() as usize).max(1);
+        if first_rank == 0 || last_rank < first_rank {
+            return Ok(Rankings {
+                ranking: Vec::new(),
+            });
+        }
 
-        let pages_offset = ranking.page.unwrap_or(0) as usize;
+        let mut link = String::with_capacity(50);
+        let mut buffer: Vec<OsuUserStatistics> =
+            Vec::with_capacity(last_rank - first_rank + 1);
 
-        let pages_range = if pages_offset != 0 {
-            pages_offset..=pages_offset + pages
-        } else {
-            1..=pages
-        };
+        let first_page = (first_rank - 1) / RANKINGS_PAGE_SIZE + 1;
+        let last_page = (last_rank - 1) / RANKINGS_PAGE_SIZE + 1;
 
-        for page in pages_range {
+        for page in first_page..=last_page {
             link.clear();
             let _ = write!(
                 link,
@@ -438,10 +580,21 @@ impl OsuApi {
                 .with_label_values(&["get_rankings"])
                 .inc();
 
-            let amount_to_append =
-                (amount - buffer.len()).min(res.ranking.len());
+            let page_len = res.ranking.len();
+
+            // Rank of the first player on the current page
+            let page_rank = (page - 1) * RANKINGS_PAGE_SIZE + 1;
 
-            buffer.extend_from_slice(&res.ranking[0..amount_to_append])
+            res.ranking
+                .into_iter()
+                .enumerate()
+                .filter(|(i, _)| ranks.contains(&(page_rank + i)))
+                .for_each(|(_, x)| buffer.push(x));
+
+            // Reached the end of the rankings
+            if page_len < RANKINGS_PAGE_SIZE {
+                break;
+            }
         }
 
         Ok(Rankings { ranking: buffer })
@@ -478,18 +631,20 @@ impl OsuApi {
         after: Option<i64>,
         limit: Option<u8>,
     ) -> ApiResult<OsuMatchGet> {
-        let mut link = format!("{OSU_API_BASE}/matches/{}", match_id);
-
-        if let Some(before) = before {
-            let _ = write!(link, "&before={}", before);
-        }
-
-        if let Some(after) = after {
-            let _ = write!(link, "?after={}", after);
-        }
-
-        if let Some(limit) = limit {
-            let _ = write!(link, "&limit={}", limit);
+        let mut link = format!("{OSU_API_BASE}/matches/{match_id}");
+        let mut separator = '?';
+
+        let params = [
+            ("before", before),
+            ("after", after),
+            ("limit", limit.map(i64::from)),
+        ];
+
+        for (key, value) in params {
+            if let Some(value) = value {
+                let _ = write!(link, "{separator}{key}={value}");
+                separator = '&';
+            }
         }
 
         self.stats.counters.with_label_values(&["get_match"]).inc();
@@ -498,27 +653,37 @@ impl OsuApi {
             .await
     }
 
+    /// Looks up users by ids or usernames,
+    /// users that weren't found are absent in the response
     pub async fn lookup_users(
         &self,
-        user_ids: &[i64],
+        users: &[UserId],
     ) -> ApiResult<GetUsersResponse> {
         let link = format!("{OSU_API_BASE}/users/lookup");
-        let mut result = Vec::with_capacity(user_ids.len());
+        let mut result = Vec::with_capacity(users.len());
 
-        for chunk in user_ids.chunks(50) {
-            let mut link = link.clone();
+        for chunk in users.chunks(50) {
+            let mut url = reqwest::Url::parse(&link)
+                .map_err(|_| OsuApiError::FromStrError)?;
 
-            for (i, id) in chunk.iter().enumerate() {
-                if i == 0 {
-                    let _ = write!(link, "?ids[]={}", id);
-                } else {
-                    let _ = write!(link, "&ids[]={}", id);
+            {
+                let mut query = url.query_pairs_mut();
+
+                for user in chunk {
+                    match user {
+                        UserId::Id(id) => {
+                            query.append_pair("ids[]", &id.to_string())
+                        }
+                        UserId::Username(name) => {
+                            query.append_pair("ids[]", &format!("@{name}"))
+                        }
+                    };
                 }
             }
 
             self.stats.counters.with_label_values(&["get_users"]).inc();
             let users_response: GetUsersResponse = self
-                .make_request(&link, Method::GET, ApiKind::General, None)
+                .make_request(url.as_str(), Method::GET, ApiKind::General, None)
                 .await?;
 
             // TODO
@@ -618,10 +783,15 @@ impl OsuApi {
             .await
     }
 
+    /// Country scope is the country of the session owner.
+    /// `mods` are concatenated acronyms, empty string stands for nomod.
+    /// `mode` other than the beatmap ruleset stands for converts
     pub async fn get_leaderboard_hidden(
         &self,
         bid: i32,
         country: bool,
+        mods: Option<&str>,
+        mode: Option<OsuGameMode>,
     ) -> ApiResult<OsuLeaderboardLazer> {
         let mut link = format!("{OSU_BASE}/beatmaps/{bid}/scores?");
 
@@ -629,6 +799,12 @@ impl OsuApi {
             link.push_str("type=country")
         }
 
+        push_mods_query(&mut link, mods);
+
+        if let Some(mode) = mode {
+            let _ = write!(link, "&mode={mode}");
+        }
+
         self.stats
             .counters
             .with_label_values(&["get_leaderboard_hidden"])
@@ -637,16 +813,23 @@ impl OsuApi {
             .await
     }
 
+    /// Whether fallback api was configured
+    pub fn has_fallback(&self) -> bool {
+        !self.fallback_url.is_empty()
+    }
+
     // This method works only if FALLBACK_API variable
     // is set.
     pub async fn get_countryleaderboard_fallback(
         &self,
         bid: i32,
+        country: &str,
         mods: Option<String>,
+        mode: OsuGameMode,
     ) -> ApiResult<FallbackBeatmapScores> {
         let mut link = format!(
-            "{}/osu/beatmaps/v2/{}/scores?country=BY&type=country",
-            self.fallback_url, bid
+            "{}/{}/beatmaps/v2/{}/scores?country={}&type=country",
+            self.fallback_url, mode, bid, country
         );
 
         if let Some(mods) = mods {
@@ -827,13 +1010,17 @@ mod tests {
     async fn test_get_leaderboard_hidden() {
         let api = API_INSTANCE.get().await.unwrap();
 
-        let leaderboard =
-            api.get_leaderboard_hidden(1804553, false).await.unwrap();
+        let leaderboard = api
+            .get_leaderboard_hidden(1804553, false, None, None)
+            .await
+            .unwrap();
 
         assert!(leaderboard.scores.len() == 50);
 
-        let leaderboard =
-            api.get_leaderboard_hidden(1804553, true).await.unwrap();
+        let leaderboard = api
+            .get_leaderboard_hidden(1804553, true, None, None)
+            .await
+            .unwrap();
 
         assert!(leaderboard.scores.len() > 2);
     }
@@ -947,7 +1134,12 @@ mod tests {
         let api = API_INSTANCE.get().await.unwrap();
 
         let res = api
-            .get_countryleaderboard_fallback(1627148, None)
+            .get_countryleaderboard_fallback(
+                1627148,
+                "BY",
+                None,
+                OsuGameMode::Osu,
+            )
             .await
             .unwrap();
 
@@ -1070,17 +1262,28 @@ mod tests {
     async fn get_lookup_users_batch() {
         let api = API_INSTANCE.get().await.unwrap();
 
-        let res = api.lookup_users(&[6892711]).await.unwrap();
+        let res = api.lookup_users(&[UserId::Id(6892711)]).await.unwrap();
 
         assert_eq!(res.users.len(), 1);
         assert_eq!(&res.users[0].username, "LoPij");
 
         let res = api
-            .lookup_users(&[6892711, 17851835, 7979597])
+            .lookup_users(&[
+                UserId::Id(6892711),
+                UserId::Id(17851835),
+                UserId::Id(7979597),
+            ])
             .await
             .unwrap();
         assert_eq!(res.users.len(), 3);
 
+        let res = api
+            .lookup_users(&[UserId::Username("LoPij".to_owned())])
+            .await
+            .unwrap();
+        assert_eq!(res.users.len(), 1);
+        assert_eq!(res.users[0].id, 6892711);
+
         dbg!(res);
     }
 
@@ -1133,6 +1336,11 @@ mod tests {
         let res = api.get_rankings(&req, 111).await.unwrap();
 
         assert_eq!(111, res.ranking.len());
+
+        let res = api.get_rankings_range(&req, 51..=150).await.unwrap();
+
+        assert_eq!(100, res.ranking.len());
+        assert_eq!(51, res.ranking[0].global_rank);
     }
 
     #[test]
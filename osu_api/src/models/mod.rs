@@ -170,7 +170,20 @@ impl Visitor<'_> for OsuRankVisitor {
     }
 
     fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
-        let rank = match v {
+        v.parse().map_err(|_| {
+            Error::invalid_value(
+                Unexpected::Str(v),
+                &r#""XH", "SH", "X", "S", "A", "B", "C", "D" or "F""#,
+            )
+        })
+    }
+}
+
+impl FromStr for OsuGrade {
+    type Err = OsuApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rank = match s {
             "XH" => OsuGrade::GradeXH,
             "SH" => OsuGrade::GradeSH,
             "X" => OsuGrade::GradeX,
@@ -180,12 +193,7 @@ impl Visitor<'_> for OsuRankVisitor {
             "C" => OsuGrade::GradeC,
             "D" => OsuGrade::GradeD,
             "F" => OsuGrade::GradeF,
-            _ => {
-                return Err(Error::invalid_value(
-                    Unexpected::Str(v),
-                    &r#""XH", "SH", "X", "S", "A", "B", "C", "D" or "F""#,
-                ))
-            }
+            _ => return Err(OsuApiError::FromStrError),
         };
 
         Ok(rank)
@@ -540,6 +548,20 @@ impl TryFrom<&str> for OsuGameMode {
     }
 }
 
+impl TryFrom<u8> for OsuGameMode {
+    type Error = OsuApiError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(OsuGameMode::Osu),
+            1 => Ok(OsuGameMode::Taiko),
+            2 => Ok(OsuGameMode::Fruits),
+            3 => Ok(OsuGameMode::Mania),
+            _ => Err(OsuApiError::Casting),
+        }
+    }
+}
+
 #[derive(Deserialize, Debug)]
 pub struct OauthResponse {
     pub token_type: String,
@@ -640,6 +662,37 @@ impl OsuBeatmap {
     }
 }
 
+/// Difficulty of the beatmapset
+#[derive(Deserialize, Clone, Debug)]
+pub struct OsuBeatmapsetDifficulty {
+    pub id: i32,
+    pub mode: OsuGameMode,
+    pub difficulty_rating: f32,
+    pub version: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct OsuBeatmapset {
+    pub id: i32,
+    pub title: String,
+    pub artist: String,
+    pub creator: String,
+    pub beatmaps: Vec<OsuBeatmapsetDifficulty>,
+}
+
+impl OsuBeatmapset {
+    /// Hardest difficulty, only of the `mode` if it's provided
+    pub fn top_difficulty(
+        &self,
+        mode: Option<OsuGameMode>,
+    ) -> Option<&OsuBeatmapsetDifficulty> {
+        self.beatmaps
+            .iter()
+            .filter(|x| mode.is_none_or(|mode| x.mode == mode))
+            .max_by(|a, b| a.difficulty_rating.total_cmp(&b.difficulty_rating))
+    }
+}
+
 #[derive(Deserialize, Clone, Debug)]
 pub struct OsuBeatmapScore {
     pub beatmapset_id: i32,
@@ -863,6 +916,11 @@ pub struct GetUsersResponse {
     pub users: Vec<OsuUser>,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+pub struct GetBeatmapsResponse {
+    pub beatmaps: Vec<OsuBeatmap>,
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct OsuUser {
     pub id: i64,
@@ -880,6 +938,37 @@ pub struct OsuUser {
     pub is_online: bool,
     #[serde(deserialize_with = "datetime::deserialize_bool::deserialize")]
     pub is_supporter: bool,
+    #[serde(default)]
+    pub statistics_rulesets: OsuUserStatisticsRulesets,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct OsuUserRulesetStatistics {
+    pub pp: f32,
+    pub hit_accuracy: f32,
+    pub play_count: u32,
+    pub global_rank: Option<u32>,
+}
+
+/// Statistics of the user in every ruleset,
+/// rulesets that user never played are absent
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct OsuUserStatisticsRulesets {
+    pub osu: Option<OsuUserRulesetStatistics>,
+    pub taiko: Option<OsuUserRulesetStatistics>,
+    pub fruits: Option<OsuUserRulesetStatistics>,
+    pub mania: Option<OsuUserRulesetStatistics>,
+}
+
+impl OsuUserStatisticsRulesets {
+    pub fn get(&self, mode: OsuGameMode) -> Option<&OsuUserRulesetStatistics> {
+        match mode {
+            OsuGameMode::Osu => self.osu.as_ref(),
+            OsuGameMode::Taiko => self.taiko.as_ref(),
+            OsuGameMode::Fruits => self.fruits.as_ref(),
+            OsuGameMode::Mania => self.mania.as_ref(),
+        }
+    }
 }
 
 mod utils {
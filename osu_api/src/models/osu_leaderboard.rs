@@ -15,6 +15,8 @@ pub struct StatisticsLazer {
     pub perfect: Option<u32>,
     pub large_tick_hit: Option<u32>,
     pub small_tick_hit: Option<u32>,
+    pub large_tick_miss: Option<u32>,
+    pub small_tick_miss: Option<u32>,
     pub slider_tail_hit: Option<u32>, // slider_end_hits
 }
 
@@ -3,7 +3,7 @@ use std::fmt;
 use chrono::{DateTime, Utc};
 use serde::{de, Deserialize, Deserializer};
 
-use crate::datetime;
+use crate::{datetime, error::OsuApiError};
 
 use super::{OsuBeatmap, OsuGameMode, OsuMods, OsuScore};
 
@@ -55,6 +55,7 @@ pub enum OsuMatchEventKind {
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct OsuMatchEvent {
+    pub id: i64,
     pub detail: OsuMatchEventDetails,
     pub game: Option<OsuMatchGame>,
 }
@@ -125,6 +126,20 @@ impl ScoringKind {
     }
 }
 
+impl TryFrom<u8> for ScoringKind {
+    type Error = OsuApiError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ScoringKind::Accuracy),
+            1 => Ok(ScoringKind::Combo),
+            2 => Ok(ScoringKind::Score),
+            3 => Ok(ScoringKind::ScoreV2),
+            _ => Err(OsuApiError::Casting),
+        }
+    }
+}
+
 struct ScoringKindVisitor;
 
 impl de::Visitor<'_> for ScoringKindVisitor {
@@ -173,6 +188,25 @@ impl TeamKind {
     pub fn as_u8(&self) -> u8 {
         *self as u8
     }
+
+    /// Whether scores are summed up by red and blue teams
+    pub fn is_team(&self) -> bool {
+        matches!(self, TeamKind::TeamVs | TeamKind::TagTeamVs)
+    }
+}
+
+impl TryFrom<u8> for TeamKind {
+    type Error = OsuApiError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(TeamKind::HeadToHead),
+            1 => Ok(TeamKind::TagCoop),
+            2 => Ok(TeamKind::TagTeamVs),
+            3 => Ok(TeamKind::TeamVs),
+            _ => Err(OsuApiError::Casting),
+        }
+    }
 }
 
 struct TeamKindVisitor;
@@ -1,17 +1,28 @@
 use crate::{
+    commands::GameModeOption,
     components::listing::ListingTrait,
     fumo_context::FumoContext,
+    leaderboard_source::{
+        feed_country_score, fetch_country_leaderboard,
+        fetch_players_leaderboard, mods_acronyms, mods_label, normalize_mods,
+        split_acronyms, LeaderboardQuery, LeaderboardSourceKind,
+    },
+    render::scoreboard::{render_scoreboard, Scoreboard, ScoreboardRow},
     utils::{
+        countries::{country_flag, country_name},
         interaction::{InteractionCommand, InteractionComponent},
-        searching::{find_beatmap_link, parse_beatmap_link},
+        searching::{
+            find_message_beatmap, find_recent_beatmap, parse_beatmap_link,
+        },
         static_components::pages_components,
     },
 };
+use fumo_database::osu::{leaderboard::OsuCountryScore, OsuDbUser};
 use fumo_macro::listing;
 use fumo_twilight::message::MessageBuilder;
-use osu_api::{
-    fallback_models::FallbackBeatmapScores,
-    models::{OsuBeatmap, RankStatus},
+use osu_api::models::{
+    osu_leaderboard::OsuScoreLazer, OsuBeatmap, OsuGameMode, OsuGrade,
+    RankStatus, UserId,
 };
 
 use twilight_interactions::command::{
@@ -20,6 +31,7 @@ use twilight_interactions::command::{
 use twilight_model::{
     application::interaction::{Interaction, InteractionData},
     channel::message::embed::EmbedFooter,
+    http::attachment::Attachment,
 };
 use twilight_util::builder::embed::{
     image_source::ImageSource, EmbedAuthorBuilder, EmbedBuilder,
@@ -29,8 +41,14 @@ use num_format::{Locale, ToFormattedString};
 
 use tokio_stream::StreamExt;
 
-use std::{cmp::Ordering, fmt::Write, time::Duration};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    time::Duration,
+};
 
+use chrono::NaiveDate;
 use eyre::Result;
 
 #[derive(Debug, CommandOption, CreateOption, Copy, Clone)]
@@ -41,8 +59,206 @@ pub enum LeaderboardSortingKind {
     Pp = 1,
 }
 
+#[derive(Debug, CommandOption, CreateOption, Copy, Clone, PartialEq, Eq)]
+pub enum LeaderboardScope {
+    #[option(name = "Country", value = "country")]
+    Country,
+    #[option(name = "Server", value = "server")]
+    Server,
+}
+
+/// Name of the attached scoreboard image
+const SCOREBOARD_FILENAME: &str = "leaderboard.png";
+
+/// Text embed or in-game like scoreboard image
+#[derive(Debug, CommandOption, CreateOption, Copy, Clone, PartialEq, Eq)]
+pub enum LeaderboardStyle {
+    #[option(name = "Embed", value = "embed")]
+    Embed,
+    #[option(name = "Image", value = "image")]
+    Image,
+}
+
+/// How `mods` option is matched against the score mods
+#[derive(Debug, CommandOption, CreateOption, Copy, Clone, PartialEq, Eq)]
+pub enum ModsFilterKind {
+    #[option(name = "Exact", value = "exact")]
+    Exact,
+    #[option(name = "Include", value = "include")]
+    Include,
+    #[option(name = "Exclude", value = "exclude")]
+    Exclude,
+}
+
+#[derive(Debug, CommandOption, CreateOption, Copy, Clone, PartialEq, Eq)]
+pub enum GradeOption {
+    #[option(name = "SS", value = "ss")]
+    SS = 5,
+    #[option(name = "S", value = "s")]
+    S = 4,
+    #[option(name = "A", value = "a")]
+    A = 3,
+    #[option(name = "B", value = "b")]
+    B = 2,
+    #[option(name = "C", value = "c")]
+    C = 1,
+    #[option(name = "D", value = "d")]
+    D = 0,
+}
+
+impl GradeOption {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::SS => "SS",
+            Self::S => "S",
+            Self::A => "A",
+            Self::B => "B",
+            Self::C => "C",
+            Self::D => "D",
+        }
+    }
+}
+
+/// Silver grades are the same tier as the regular ones
+fn grade_tier(grade: &OsuGrade) -> i8 {
+    match grade {
+        OsuGrade::GradeXH | OsuGrade::GradeX => 5,
+        OsuGrade::GradeSH | OsuGrade::GradeS => 4,
+        OsuGrade::GradeA => 3,
+        OsuGrade::GradeB => 2,
+        OsuGrade::GradeC => 1,
+        OsuGrade::GradeD => 0,
+        OsuGrade::GradeF => -1,
+    }
+}
+
+/// Filters that are applied to the scores after fetching,
+/// since leaderboard sources support only exact mods
+#[derive(Debug, Default)]
+struct LeaderboardFilters {
+    /// Normalized mods
+    mods: Option<String>,
+    mods_kind: Option<ModsFilterKind>,
+    min_grade: Option<GradeOption>,
+    after: Option<NaiveDate>,
+    before: Option<NaiveDate>,
+    all_mods: bool,
+}
+
+impl LeaderboardFilters {
+    /// Mods that can be requested from the source directly
+    fn source_mods(&self) -> Option<&str> {
+        match self.mods_kind {
+            Some(ModsFilterKind::Exact) | None => self.mods.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Whether source could return only the best score of every player.
+    /// Otherwise every mods combination is needed, since the best
+    /// score might not pass filters while another one does
+    fn source_best_per_player(&self) -> bool {
+        let client_side = (self.source_mods().is_none() && self.mods.is_some())
+            || self.min_grade.is_some()
+            || self.after.is_some()
+            || self.before.is_some();
+
+        !self.all_mods && !client_side
+    }
+
+    fn matches(&self, score: &OsuCountryScore) -> bool {
+        if let Some(mods) = &self.mods {
+            let mut acronyms = mods_acronyms(mods);
+            let has_mod = |x: &str| mods_acronyms(&score.mods).any(|y| x == y);
+
+            let matches = match self.mods_kind {
+                Some(ModsFilterKind::Exact) | None => &score.mods == mods,
+                Some(ModsFilterKind::Include) => acronyms.all(has_mod),
+                Some(ModsFilterKind::Exclude) => !acronyms.any(has_mod),
+            };
+
+            if !matches {
+                return false;
+            }
+        }
+
+        if let Some(min_grade) = self.min_grade {
+            let grade = score.grade.parse::<OsuGrade>().ok();
+
+            if grade.is_none_or(|x| grade_tier(&x) < min_grade as i8) {
+                return false;
+            }
+        }
+
+        let played_at = score.played_at.date();
+
+        if self.after.is_some_and(|x| played_at < x) {
+            return false;
+        }
+
+        if self.before.is_some_and(|x| played_at > x) {
+            return false;
+        }
+
+        true
+    }
+
+    /// Keeps scores that pass filters, scores should be sorted already
+    /// so only the first score of every player is kept
+    fn apply(&self, scores: &mut Vec<OsuCountryScore>) {
+        let mut players = HashSet::with_capacity(scores.len());
+
+        scores.retain(|score| {
+            self.matches(score)
+                && (self.all_mods || players.insert(score.osu_id))
+        });
+    }
+
+    /// Short description of the active filters
+    fn describe(&self) -> Option<String> {
+        let mut filters = Vec::new();
+
+        if let Some(mods) = &self.mods {
+            let mods = mods_label(mods);
+
+            filters.push(match self.mods_kind {
+                Some(ModsFilterKind::Exact) | None => format!("+{mods}"),
+                Some(ModsFilterKind::Include) => format!("+{mods}+"),
+                Some(ModsFilterKind::Exclude) => format!("-{mods}"),
+            });
+        }
+
+        if let Some(grade) = self.min_grade {
+            filters.push(format!("{}+", grade.name()));
+        }
+
+        if let Some(after) = self.after {
+            filters.push(format!("after {after}"));
+        }
+
+        if let Some(before) = self.before {
+            filters.push(format!("before {before}"));
+        }
+
+        if self.all_mods {
+            filters.push(String::from("all mods"));
+        }
+
+        if filters.is_empty() {
+            None
+        } else {
+            Some(filters.join(", "))
+        }
+    }
+}
+
+/// Parses `YYYY-MM-DD` date
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok()
+}
+
 /// Country leaderboard
-#[derive(CommandModel, CreateCommand, Debug)]
+#[derive(CommandModel, CreateCommand, Debug, Default)]
 #[command(name = "leaderboard")]
 pub struct LeaderboardCommand {
     /// Direct link to the beatmap
@@ -58,6 +274,36 @@ pub struct LeaderboardCommand {
 
     /// Enable legacy scoring
     legacy: Option<bool>,
+
+    /// Ruleset, osu! beatmaps are converted to other rulesets
+    mode: Option<GameModeOption>,
+
+    /// Country code, server default or your osu! country otherwise
+    #[command(autocomplete = true, min_length = 2, max_length = 2)]
+    country: Option<String>,
+
+    /// Show scores of the linked server members instead of the country
+    scope: Option<LeaderboardScope>,
+
+    /// How mods are matched, exact by default
+    mods_filter: Option<ModsFilterKind>,
+
+    /// Minimum grade of the score
+    min_grade: Option<GradeOption>,
+
+    /// Only scores set after this date (YYYY-MM-DD)
+    #[command(min_length = 10, max_length = 10)]
+    after: Option<String>,
+
+    /// Only scores set before this date (YYYY-MM-DD)
+    #[command(min_length = 10, max_length = 10)]
+    before: Option<String>,
+
+    /// Show best score of every mods combination instead of every player
+    all_mods: Option<bool>,
+
+    /// Output style, embed by default
+    style: Option<LeaderboardStyle>,
 }
 
 impl LeaderboardCommand {
@@ -81,16 +327,10 @@ impl LeaderboardCommand {
 
         // If link already provided go straight to parsing
         if let Some(link) = &self.link {
-            if let Some(beatmap_id) = parse_beatmap_link(link) {
-                return country_leaderboard(
-                    ctx,
-                    beatmap_id,
-                    self.mods.clone(),
-                    self.sorting,
-                    self.legacy,
-                    &cmd,
-                )
-                .await; // TODO another cloning....................
+            if let Some(beatmap_id) =
+                parse_beatmap_link(ctx, link, self.mode.map(Into::into)).await?
+            {
+                return country_leaderboard(ctx, beatmap_id, self, &cmd).await;
             } else {
                 let builder =
                     MessageBuilder::new().content("Please provide valid link");
@@ -101,28 +341,11 @@ impl LeaderboardCommand {
         }
 
         // If not try to search through recent messages
-        let msgs = ctx
-            .http
-            .channel_messages(cmd.channel_id)
-            .limit(50)?
-            .await?
-            .models()
-            .await?;
-
-        for m in msgs {
-            if let Some(link) = find_beatmap_link(&m) {
-                if let Some(bid) = parse_beatmap_link(link.as_ref()) {
-                    return country_leaderboard(
-                        ctx,
-                        bid,
-                        self.mods.clone(),
-                        self.sorting,
-                        self.legacy,
-                        &cmd,
-                    )
-                    .await;
-                }
-            }
+        if let Some(bid) =
+            find_recent_beatmap(ctx, cmd.channel_id, self.mode.map(Into::into))
+                .await?
+        {
+            return country_leaderboard(ctx, bid, self, &cmd).await;
         }
 
         let builder =
@@ -134,10 +357,16 @@ impl LeaderboardCommand {
 
 #[listing]
 struct LeaderboardListing {
-    scores: FallbackBeatmapScores,
+    scores: Vec<OsuCountryScore>,
+    /// Country flag and code or server
+    scope: String,
+    source: LeaderboardSourceKind,
+    filters: LeaderboardFilters,
     beatmap: OsuBeatmap,
+    mode: OsuGameMode,
     user_position: Option<usize>,
     is_legacy: bool,
+    style: LeaderboardStyle,
 }
 
 impl ListingTrait for LeaderboardListing {
@@ -158,46 +387,41 @@ impl ListingTrait for LeaderboardListing {
     }
 
     fn update(&mut self) {
-        let mut text = format!("Page {}/{}", self.current_page, self.max_pages);
-
-        if let Some(pos) = self.user_position {
-            text.push_str(&format!(
-                " • Your position: {}/{}",
-                pos,
-                self.scores.items.len()
-            ));
-        }
-
         let footer = EmbedFooter {
-            text,
+            text: self.footer_text(),
             icon_url: None,
             proxy_icon_url: None,
         };
 
+        let author = EmbedAuthorBuilder::new(self.beatmap.metadata())
+            .url(format!("https://osu.ppy.sh/b/{}", self.beatmap.id))
+            .build();
+
+        let embed = EmbedBuilder::new()
+            .color(865846)
+            .author(author)
+            .footer(footer);
+
+        // Scores are drawn on the attached image instead
+        if self.style == LeaderboardStyle::Image {
+            let image = ImageSource::attachment(SCOREBOARD_FILENAME).unwrap();
+
+            self.embed = Some(embed.image(image).build());
+            return;
+        }
+
         let mut description = String::with_capacity(1500);
 
         let start_at = (self.current_page - 1) * self.entries_per_page;
 
         let scores_iter = self
             .scores
-            .items
             .iter()
             .skip(start_at)
             .take(self.entries_per_page);
 
         for (index, score) in scores_iter.enumerate() {
-            let mut mods_string = String::new();
-
-            if score.stats.mods.difficulty.is_empty() {
-                mods_string.push_str("NM");
-            } else {
-                score
-                    .stats
-                    .mods
-                    .difficulty
-                    .iter()
-                    .for_each(|x| mods_string.push_str(&x.acronym))
-            };
+            let mods_string = mods_label(&score.mods);
 
             let mut score_row = String::with_capacity(100);
 
@@ -205,74 +429,55 @@ impl ListingTrait for LeaderboardListing {
                 score_row,
                 "{}. [{}](https://osu.ppy.sh/u/{}) +**{}",
                 index + 1 + start_at,
-                score.player.username,
-                score.player.id,
+                score.osu_username,
+                score.osu_id,
                 mods_string
             );
 
-            let mode_with_speed_change = score
-                .stats
-                .mods
-                .difficulty
-                .iter()
-                .find(|x| x.speed.is_some());
-
-            if let Some(mode) = mode_with_speed_change {
-                match mode.speed {
-                    Some(speed) => {
-                        let _ = write!(score_row, " (x{})**", speed);
-                    }
-                    None => {
-                        let _ = write!(score_row, "**");
-                    }
+            match score.speed {
+                Some(speed) => {
+                    let _ = write!(score_row, " (x{})**", speed);
+                }
+                None => {
+                    let _ = write!(score_row, "**");
                 }
-            } else {
-                let _ = write!(score_row, "**");
             };
 
             let _ = writeln!(description, "{}", score_row);
 
             let pp = match self.beatmap.status {
                 RankStatus::Loved => "\\❤️".to_owned(),
-                _ => format!("{:.2}pp", score.stats.performance),
+                _ => format!("{:.2}pp", score.pp.unwrap_or(0.0)),
             };
 
             let osu_score = if self.is_legacy {
-                score.stats.score.legacy.to_formatted_string(&Locale::en)
+                score.legacy_total_score.to_formatted_string(&Locale::en)
             } else {
-                score.stats.score.lazer.to_formatted_string(&Locale::en)
+                score.total_score.to_formatted_string(&Locale::en)
             };
 
+            let grade =
+                score.grade.parse::<OsuGrade>().unwrap_or(OsuGrade::GradeD);
+
             let _ = writeln!(
                 description,
                 "{} • {:.2}% • {} • {}",
-                score.stats.rank.to_emoji(),
-                score.stats.accuracy,
+                grade.to_emoji(),
+                score.accuracy * 100.0,
                 pp,
                 osu_score
             );
 
+            self.write_hits(&mut description, score);
+
             let _ = writeln!(
                 description,
-                "[{}x/{}x] [{}/{}/{}/{}]",
-                score.stats.combo,
-                self.beatmap.max_combo.unwrap_or(0),
-                score.counts.x300,
-                score.counts.x100,
-                score.counts.x50,
-                score.counts.xmiss,
+                "<t:{}:R>",
+                score.played_at.and_utc().timestamp()
             );
-
-            let _ = writeln!(description, "<t:{}:R>", score.date.timestamp());
         }
 
-        let author = EmbedAuthorBuilder::new(self.beatmap.metadata())
-            .url(format!("https://osu.ppy.sh/b/{}", self.beatmap.id))
-            .build();
-
-        let embed = EmbedBuilder::new()
-            .color(865846)
-            .author(author)
+        let embed = embed
             .thumbnail(
                 ImageSource::url(format!(
                     "https://assets.ppy.sh/beatmaps/{}/covers/list.jpg",
@@ -281,41 +486,352 @@ impl ListingTrait for LeaderboardListing {
                 .unwrap(),
             )
             .description(description)
-            .footer(footer)
             .build();
 
         self.embed = Some(embed);
     }
 }
 
+impl LeaderboardListing {
+    fn footer_text(&self) -> String {
+        let mut text = self.scope.clone();
+
+        if self.is_convert() {
+            let _ = write!(text, " • {} convert", self.mode);
+        }
+
+        let _ = write!(
+            text,
+            " • Page {}/{} • Source: {}",
+            self.current_page, self.max_pages, self.source
+        );
+
+        if let Some(filters) = self.filters.describe() {
+            text.push_str(&format!(" • Filters: {filters}"));
+        }
+
+        if let Some(pos) = self.user_position {
+            text.push_str(&format!(
+                " • Your position: {}/{}",
+                pos,
+                self.scores.len()
+            ));
+        }
+
+        text
+    }
+
+    /// Renders current page as the scoreboard image, falls back
+    /// to the embed style if rendering fails. Empty list removes
+    /// image of the previous page from the message
+    async fn attachments(&mut self) -> Vec<Attachment> {
+        if self.style != LeaderboardStyle::Image {
+            return Vec::new();
+        }
+
+        match render_scoreboard(self.scoreboard()).await {
+            Ok(png) => vec![Attachment::from_bytes(
+                SCOREBOARD_FILENAME.to_owned(),
+                png,
+                1,
+            )],
+            Err(e) => {
+                tracing::warn!("Failed to render scoreboard: {e}");
+
+                self.style = LeaderboardStyle::Embed;
+                self.update();
+
+                Vec::new()
+            }
+        }
+    }
+
+    fn scoreboard(&self) -> Scoreboard {
+        let start_at = (self.current_page - 1) * self.entries_per_page;
+
+        let rows = self
+            .scores
+            .iter()
+            .enumerate()
+            .skip(start_at)
+            .take(self.entries_per_page)
+            .map(|(index, score)| {
+                let pp = match self.beatmap.status {
+                    RankStatus::Loved => "♥".to_owned(),
+                    _ => format!("{:.2}pp", score.pp.unwrap_or(0.0)),
+                };
+
+                let total_score = if self.is_legacy {
+                    score.legacy_total_score
+                } else {
+                    score.total_score
+                };
+
+                ScoreboardRow {
+                    position: index + 1,
+                    osu_id: score.osu_id,
+                    username: score.osu_username.clone(),
+                    country: score.country.clone(),
+                    grade: score
+                        .grade
+                        .parse::<OsuGrade>()
+                        .unwrap_or(OsuGrade::GradeD),
+                    mods: score.mods.clone(),
+                    speed: score.speed,
+                    accuracy: score.accuracy,
+                    max_combo: score.max_combo,
+                    score: total_score.to_formatted_string(&Locale::en),
+                    pp,
+                    played_at: score.played_at,
+                    highlighted: self.user_position == Some(index + 1),
+                }
+            })
+            .collect();
+
+        Scoreboard {
+            title: self.beatmap.metadata(),
+            subtitle: self.footer_text(),
+            rows,
+        }
+    }
+
+    /// Max combo of the beatmap is known only for its own ruleset
+    fn is_convert(&self) -> bool {
+        OsuGameMode::try_from(self.beatmap.mode.as_str())
+            .is_ok_and(|mode| mode != self.mode)
+    }
+
+    /// Hits in the layout of the ruleset
+    fn write_hits(&self, s: &mut String, score: &OsuCountryScore) {
+        let _ = write!(s, "[{}x", score.max_combo);
+
+        if !self.is_convert() {
+            let _ = write!(s, "/{}x", self.beatmap.max_combo.unwrap_or(0));
+        }
+
+        let _ = match self.mode {
+            OsuGameMode::Osu => writeln!(
+                s,
+                "] [{}/{}/{}/{}]",
+                score.count_300,
+                score.count_100,
+                score.count_50,
+                score.count_miss
+            ),
+            OsuGameMode::Taiko => writeln!(
+                s,
+                "] [{}/{}/{}]",
+                score.count_300, score.count_100, score.count_miss
+            ),
+            OsuGameMode::Fruits => writeln!(
+                s,
+                "] [{}/{}/{}/{}] • {} droplets missed",
+                score.count_300,
+                score.count_100,
+                score.count_50,
+                score.count_miss,
+                score.count_katu
+            ),
+            OsuGameMode::Mania => {
+                let ma_ratio =
+                    score.count_geki as f32 / score.count_300.max(1) as f32;
+                let pa_ratio =
+                    score.count_300 as f32 / score.count_katu.max(1) as f32;
+
+                writeln!(
+                    s,
+                    "] [{}/{}/{}/{}/{}/{}] • MA: {:.2} PA: {:.2}",
+                    score.count_geki,
+                    score.count_300,
+                    score.count_katu,
+                    score.count_100,
+                    score.count_50,
+                    score.count_miss,
+                    ma_ratio,
+                    pa_ratio
+                )
+            }
+        };
+    }
+
+    /// Applies filters that the source couldn't apply
+    /// and finds position of the user afterwards
+    fn apply_filters(mut self, osu_id: Option<i64>) -> Self {
+        self.filters.apply(&mut self.scores);
+
+        self.user_position = osu_id.and_then(|osu_id| {
+            self.scores
+                .iter()
+                .position(|score| score.osu_id == osu_id)
+                .map(|index| index + 1)
+        });
+
+        self
+    }
+}
+
+/// Persists feed scores of the players from the countries
+/// that have local leaderboards, returns stored scores
+pub async fn store_country_scores(
+    ctx: &FumoContext,
+    scores: &[OsuScoreLazer],
+) -> Result<Vec<OsuCountryScore>> {
+    let countries = ctx.db.select_leaderboard_countries().await?;
+
+    if countries.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Failed scores are not preserved and never show up on leaderboards
+    let scores: Vec<&OsuScoreLazer> =
+        scores.iter().filter(|x| x.preserve).collect();
+
+    let mut players: HashMap<i64, (String, String)> = scores
+        .iter()
+        .filter_map(|x| {
+            x.user.as_ref().map(|user| {
+                (
+                    x.user_id,
+                    (user.username.clone(), user.country_code.clone()),
+                )
+            })
+        })
+        .collect();
+
+    let mut unknown: Vec<i64> = scores
+        .iter()
+        .map(|x| x.user_id)
+        .filter(|x| !players.contains_key(x))
+        .collect();
+
+    unknown.sort_unstable();
+    unknown.dedup();
+
+    if !unknown.is_empty() {
+        players.extend(ctx.db.select_osu_player_countries(&unknown).await?);
+
+        let missing: Vec<UserId> = unknown
+            .into_iter()
+            .filter(|x| !players.contains_key(x))
+            .map(UserId::Id)
+            .collect();
+
+        if !missing.is_empty() {
+            let users = ctx.osu_api.lookup_users(&missing).await?.users;
+
+            let mut osu_ids = Vec::with_capacity(users.len());
+            let mut usernames = Vec::with_capacity(users.len());
+            let mut user_countries = Vec::with_capacity(users.len());
+
+            for user in users {
+                players.insert(
+                    user.id,
+                    (user.username.clone(), user.country_code.clone()),
+                );
+
+                osu_ids.push(user.id);
+                usernames.push(user.username);
+                user_countries.push(user.country_code);
+            }
+
+            ctx.db
+                .add_osu_player_countries(&osu_ids, &usernames, &user_countries)
+                .await?;
+        }
+    }
+
+    let mut stored = Vec::with_capacity(scores.len());
+
+    for score in scores {
+        let Some((username, country)) = players.get(&score.user_id) else {
+            continue;
+        };
+
+        if !countries.contains(country) {
+            continue;
+        }
+
+        stored.push(feed_country_score(score, username, country));
+    }
+
+    // Feed scores only improve stored leaderboards, they
+    // never make the leaderboard complete on their own
+    ctx.db.add_osu_country_scores(&stored).await?;
+
+    Ok(stored)
+}
+
+/// Country is taken from the command option, then from the server
+/// default and then from the linked osu! account of the user
+async fn resolve_country(
+    ctx: &FumoContext,
+    cmd: &InteractionCommand,
+    country: Option<String>,
+    osu_user: Option<&OsuDbUser>,
+) -> Result<Option<String>> {
+    if let Some(country) = country {
+        return Ok(Some(country.to_uppercase()));
+    }
+
+    if let Some(guild_id) = cmd.guild_id {
+        let country = ctx.db.get_guild_country(guild_id.get() as i64).await?;
+
+        if country.is_some() {
+            return Ok(country);
+        }
+    }
+
+    if let Some(osu_user) = osu_user {
+        let user = ctx
+            .osu_api
+            .get_user(UserId::Id(osu_user.osu_id), None)
+            .await?;
+
+        return Ok(user.map(|x| x.country_code));
+    }
+
+    Ok(None)
+}
+
 pub async fn country_leaderboard(
     ctx: &FumoContext,
     bid: i32,
-    mods: Option<String>,
-    sorting: Option<LeaderboardSortingKind>,
-    legacy: Option<bool>,
+    options: &LeaderboardCommand,
     cmd: &InteractionCommand,
 ) -> Result<()> {
     let mut builder = MessageBuilder::new();
 
     let osu_user = osu_user!(ctx, cmd);
 
-    let (clb_res, b_res) = tokio::join!(
-        ctx.osu_api.get_countryleaderboard_fallback(bid, mods),
-        ctx.osu_api.get_beatmap(bid as i64),
-    );
+    let mut filters = LeaderboardFilters {
+        mods_kind: options.mods_filter,
+        min_grade: options.min_grade,
+        all_mods: options.all_mods.unwrap_or(false),
+        ..Default::default()
+    };
 
-    let mut clb = match clb_res {
-        Ok(lb) => lb,
-        Err(e) => {
-            builder =
-                builder.content("Issues with leaderboard api. blame seneal");
-            cmd.update(ctx, &builder).await?;
-            return Err(e.into());
+    for (date, filter) in [
+        (&options.after, &mut filters.after),
+        (&options.before, &mut filters.before),
+    ] {
+        if let Some(date) = date {
+            let Some(date) = parse_date(date) else {
+                builder = builder
+                    .content("Please provide dates in YYYY-MM-DD format");
+                cmd.update(ctx, &builder).await?;
+                return Ok(());
+            };
+
+            *filter = Some(date);
         }
-    };
+    }
 
-    let b = match b_res {
+    filters.mods = options
+        .mods
+        .as_deref()
+        .map(|x| normalize_mods(split_acronyms(x).iter().map(String::as_str)));
+
+    let b = match ctx.osu_api.get_beatmap(bid as i64).await {
         Ok(b) => b,
         Err(e) => {
             builder = builder.content("Issues with osu!api. blame peppy");
@@ -324,60 +840,147 @@ pub async fn country_leaderboard(
         }
     };
 
-    let total_scores = clb.items.len();
+    let beatmap_mode =
+        OsuGameMode::try_from(b.mode.as_str()).unwrap_or(OsuGameMode::Osu);
+    let mode = options.mode.map(Into::into).unwrap_or(beatmap_mode);
 
-    match sorting {
-        Some(LeaderboardSortingKind::Pp) => {
-            if b.status != RankStatus::Loved {
-                clb.items.sort_by(|a, b| {
-                    b.stats
-                        .performance
-                        .partial_cmp(&a.stats.performance)
-                        .unwrap_or(Ordering::Equal)
-                });
-            }
+    if mode != beatmap_mode && beatmap_mode != OsuGameMode::Osu {
+        builder = builder
+            .content("Only osu! beatmaps can be converted to other rulesets");
+        cmd.update(ctx, &builder).await?;
+        return Ok(());
+    }
+
+    let leaderboard = match options.scope {
+        Some(LeaderboardScope::Server) => {
+            let Some(guild_id) = cmd.guild_id else {
+                builder = builder.content(
+                    "Server leaderboard is available only on servers!",
+                );
+                cmd.update(ctx, &builder).await?;
+                return Ok(());
+            };
+
+            let osu_ids =
+                ctx.db.select_guild_osu_users(guild_id.get() as i64).await?;
+
+            let scores = fetch_players_leaderboard(
+                ctx,
+                bid,
+                mode,
+                &osu_ids,
+                filters.source_mods(),
+            )
+            .await?;
+
+            Some((
+                scores,
+                String::from("Server"),
+                LeaderboardSourceKind::UserScores,
+            ))
         }
-        Some(LeaderboardSortingKind::Score) => {
-            clb.items.sort_by(|a, b| match legacy {
-                Some(true) => b.stats.score.legacy.cmp(&a.stats.score.legacy),
-                None | Some(false) => {
-                    b.stats.score.lazer.cmp(&a.stats.score.lazer)
+        Some(LeaderboardScope::Country) | None => {
+            let country = match resolve_country(
+                ctx,
+                cmd,
+                options.country.clone(),
+                osu_user.as_ref(),
+            )
+            .await?
+            {
+                Some(country) if country_name(&country).is_some() => country,
+                Some(_) => {
+                    builder = builder.content("Unknown country code!");
+                    cmd.update(ctx, &builder).await?;
+                    return Ok(());
                 }
-            });
-        }
-        None => {
-            clb.items.sort_by(|a, b| match legacy {
-                Some(true) => b.stats.score.legacy.cmp(&a.stats.score.legacy),
-                None | Some(false) => {
-                    b.stats.score.lazer.cmp(&a.stats.score.lazer)
+                None => {
+                    builder = builder.content(
+                        "Please specify a country or link your osu! account",
+                    );
+                    cmd.update(ctx, &builder).await?;
+                    return Ok(());
                 }
-            });
+            };
+
+            let query = LeaderboardQuery {
+                beatmap_id: bid,
+                country: &country,
+                mode,
+                mods: filters.source_mods(),
+                best_per_player: filters.source_best_per_player(),
+            };
+
+            fetch_country_leaderboard(ctx, &query).await?.map(
+                |(scores, source)| {
+                    let scope =
+                        format!("{} {}", country_flag(&country), country);
+
+                    (scores, scope, source)
+                },
+            )
         }
     };
 
-    let user_position: Option<usize> = match osu_user {
-        Some(osu_user) => {
-            let pos = clb
-                .items
-                .iter()
-                .enumerate()
-                .find(|(_index, score)| score.player.id == osu_user.osu_id);
+    let Some((mut scores, scope, source)) = leaderboard else {
+        builder = builder.content("No scores found on this beatmap!");
+        cmd.update(ctx, &builder).await?;
+        return Ok(());
+    };
 
-            if let Some((index, _score)) = pos {
-                Some(index + 1)
-            } else {
-                None
+    if scores.is_empty() {
+        builder = builder.content("No scores found on this beatmap!");
+        cmd.update(ctx, &builder).await?;
+        return Ok(());
+    }
+
+    let by_score =
+        |a: &OsuCountryScore, b: &OsuCountryScore| match options.legacy {
+            Some(true) => b.legacy_total_score.cmp(&a.legacy_total_score),
+            None | Some(false) => b.total_score.cmp(&a.total_score),
+        };
+
+    match options.sorting {
+        Some(LeaderboardSortingKind::Pp) => {
+            if b.status != RankStatus::Loved {
+                scores.sort_by(|a, b| {
+                    b.pp.unwrap_or(0.0)
+                        .partial_cmp(&a.pp.unwrap_or(0.0))
+                        .unwrap_or(Ordering::Equal)
+                });
             }
         }
-        None => None,
+        Some(LeaderboardSortingKind::Score) | None => {
+            scores.sort_by(by_score);
+        }
     };
 
-    let mut lb_list =
-        LeaderboardListing::new(clb, b, user_position, legacy.unwrap_or(false))
-            .calculate_pages(total_scores, 10);
+    let lb_list = LeaderboardListing::new(
+        scores,
+        scope,
+        source,
+        filters,
+        b,
+        mode,
+        None,
+        options.legacy.unwrap_or(false),
+        options.style.unwrap_or(LeaderboardStyle::Embed),
+    )
+    .apply_filters(osu_user.map(|x| x.osu_id));
+
+    if lb_list.scores.is_empty() {
+        builder = builder.content("No scores found matching the filters!");
+        cmd.update(ctx, &builder).await?;
+        return Ok(());
+    }
+
+    let total_scores = lb_list.scores.len();
+    let mut lb_list = lb_list.calculate_pages(total_scores, 10);
 
     lb_list.update();
 
+    let attachments = lb_list.attachments().await;
+
     let mut msg_builder = MessageBuilder::new()
         .embed(
             lb_list
@@ -386,6 +989,7 @@ pub async fn country_leaderboard(
                 .expect("embed should be present")
                 .clone(),
         )
+        .attachments(attachments)
         .components(pages_components());
 
     let msg = cmd.update(ctx, &msg_builder).await?.model().await?;
@@ -397,13 +1001,17 @@ pub async fn country_leaderboard(
         lb_list.handle_interaction_component(ctx, &component).await;
         lb_list.update();
 
-        msg_builder = msg_builder.embed(
-            lb_list
-                .embed
-                .as_ref()
-                .expect("embed should be present")
-                .clone(),
-        );
+        let attachments = lb_list.attachments().await;
+
+        msg_builder = msg_builder
+            .embed(
+                lb_list
+                    .embed
+                    .as_ref()
+                    .expect("embed should be present")
+                    .clone(),
+            )
+            .attachments(attachments);
 
         cmd.update(ctx, &msg_builder).await?;
     }
@@ -435,13 +1043,14 @@ pub async fn run(ctx: &FumoContext, command: InteractionCommand) -> Result<()> {
             .model()
             .await?;
 
-        if let Some(link) = find_beatmap_link(&msg) {
-            if let Some(bid) = parse_beatmap_link(link.as_ref()) {
-                return country_leaderboard(
-                    ctx, bid, None, None, None, &command,
-                )
-                .await;
-            }
+        if let Some(bid) = find_message_beatmap(ctx, &msg, None).await {
+            return country_leaderboard(
+                ctx,
+                bid,
+                &LeaderboardCommand::default(),
+                &command,
+            )
+            .await;
         }
     }
 
@@ -80,4 +80,33 @@ pub mod country_leaderboard;
 pub mod multiplayer;
 pub mod osu;
 pub mod osu_tracking;
+pub mod server_rankings;
+pub mod snipes;
 pub mod twitch;
+
+use osu_api::models::OsuGameMode;
+use twilight_interactions::command::{CommandOption, CreateOption};
+
+/// osu! ruleset command option
+#[derive(Debug, CommandOption, CreateOption, Clone, Copy)]
+pub enum GameModeOption {
+    #[option(name = "osu!", value = "osu")]
+    Osu,
+    #[option(name = "osu!taiko", value = "taiko")]
+    Taiko,
+    #[option(name = "osu!catch", value = "fruits")]
+    Fruits,
+    #[option(name = "osu!mania", value = "mania")]
+    Mania,
+}
+
+impl From<GameModeOption> for OsuGameMode {
+    fn from(value: GameModeOption) -> Self {
+        match value {
+            GameModeOption::Osu => OsuGameMode::Osu,
+            GameModeOption::Taiko => OsuGameMode::Taiko,
+            GameModeOption::Fruits => OsuGameMode::Fruits,
+            GameModeOption::Mania => OsuGameMode::Mania,
+        }
+    }
+}
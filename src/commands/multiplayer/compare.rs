@@ -2,7 +2,7 @@ use crate::{
     fumo_context::FumoContext,
     utils::{
         interaction::InteractionCommand,
-        searching::{find_beatmap_link, parse_beatmap_link},
+        searching::{find_recent_beatmap, parse_beatmap_link},
     },
 };
 use eyre::Result;
@@ -19,7 +19,7 @@ use twilight_util::builder::embed::{
     EmbedAuthorBuilder, EmbedBuilder, ImageSource,
 };
 
-use super::ListKind;
+use super::{tournament_acronym, ListKind};
 
 /// List all multiplayer scores
 #[derive(CommandModel, CreateCommand, Debug)]
@@ -33,6 +33,9 @@ pub struct MultiplayerCompare {
 
     /// User ID or username
     pub user: Option<String>,
+
+    /// Tournament acronym e.g. OWC2023, implies tournament matches
+    pub tournament: Option<String>,
 }
 
 impl MultiplayerCompare {
@@ -68,7 +71,8 @@ impl MultiplayerCompare {
         let beatmap_id = match &self.beatmap {
             Some(v) => {
                 // Try to parse from regex
-                let Some(beatmap_id) = parse_beatmap_link(v.as_ref()) else {
+                let Some(beatmap_id) = parse_beatmap_link(ctx, v, None).await?
+                else {
                     let builder = MessageBuilder::new().content(
                         "Failed to parse beatmap id from provided link",
                     );
@@ -79,28 +83,18 @@ impl MultiplayerCompare {
                 // TODO avoid converting but whatever for now
                 beatmap_id as i64
             }
-            None => 'blk: {
-                // Try to fetch from recent messages
-                let msgs = ctx
-                    .http
-                    .channel_messages(cmd.channel_id)
-                    .limit(50)?
-                    .await?
-                    .models()
-                    .await?;
-
-                for msg in msgs {
-                    if let Some(link) = find_beatmap_link(&msg) {
-                        if let Some(bid) = parse_beatmap_link(link.as_ref()) {
-                            break 'blk bid as i64;
-                        }
-                    }
-                }
+            None => {
+                let Some(bid) =
+                    find_recent_beatmap(ctx, cmd.channel_id, None).await?
+                else {
+                    let builder = MessageBuilder::new().content(
+                        "Failed to find beatmap from recent 50 messages",
+                    );
+                    cmd.update(ctx, &builder).await?;
+                    return Ok(());
+                };
 
-                let builder = MessageBuilder::new()
-                    .content("Failed to find beatmap from recent 50 messages");
-                cmd.update(ctx, &builder).await?;
-                return Ok(());
+                bid as i64
             }
         };
 
@@ -116,11 +110,14 @@ impl MultiplayerCompare {
             (UserId::Id(_), Some(api)) => api.id,
         };
 
+        let tournament = tournament_acronym(&self.tournament);
+
         let (scores, beatmap) = tokio::join!(
             ctx.db.select_beatmap_scores_by_user(
                 beatmap_id,
                 user_id,
-                self.kind.is_tournament()
+                self.kind.is_tournament() || tournament.is_some(),
+                tournament.as_deref()
             ),
             ctx.osu_api.get_beatmap(beatmap_id)
         );
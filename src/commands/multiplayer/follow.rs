@@ -0,0 +1,493 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt::Write,
+    sync::Arc,
+    time::Duration,
+};
+
+use eyre::Result;
+use fumo_database::osu::follows::OsuMatchFollow;
+use fumo_twilight::message::MessageBuilder;
+use num_format::{Locale, ToFormattedString};
+use osu_api::{
+    error::OsuApiError,
+    models::{
+        osu_matches::{OsuMatchEvent, OsuMatchGame, OsuMatchGet},
+        OsuScoreMatchTeam,
+    },
+};
+use tokio::time::MissedTickBehavior;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+    channel::message::{Embed, MessageFlags},
+    guild::Permissions,
+    id::Id,
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+use crate::{
+    components::listing::ListingTrait, fumo_context::FumoContext,
+    utils::interaction::InteractionCommand,
+};
+
+use super::summary::{
+    parse_match_id, parse_teams, team_emoji, GameWinner, MatchGame,
+    MatchListing, MatchSummary,
+};
+
+/// How often followed matches are checked for finished games
+const FOLLOW_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Max amount of matches followed in one channel at the same time
+const MAX_CHANNEL_FOLLOWS: i64 = 5;
+
+/// Scores shown in the game embed, embed field is limited to 1024 characters
+const MAX_SCORES_SHOWN: usize = 12;
+
+/// Games shown in the final summary of the match
+const MAX_SUMMARY_GAMES: usize = 16;
+
+/// Finished games after the already processed events and id of the last
+/// processed event. Cursor doesn't move past the game that is still in
+/// progress, so the game is picked up again once it finishes. Games of
+/// the closed match won't ever finish, so they are skipped instead
+fn completed_games(
+    events: &[OsuMatchEvent],
+    mut cursor: i64,
+    is_closed: bool,
+) -> (Vec<&OsuMatchGame>, i64) {
+    let mut games = Vec::new();
+    let start = cursor;
+
+    for event in events.iter().filter(|x| x.id > start) {
+        if let Some(game) = &event.game {
+            if game.end_time.is_none() {
+                if !is_closed {
+                    break;
+                }
+
+                cursor = event.id;
+                continue;
+            }
+
+            // Aborted games don't have any scores
+            if !game.scores.is_empty() {
+                games.push(game);
+            }
+        }
+
+        cursor = event.id;
+    }
+
+    (games, cursor)
+}
+
+fn add_win(follow: &mut OsuMatchFollow, game: &MatchGame) {
+    match game.winner() {
+        Some(GameWinner::Team(OsuScoreMatchTeam::Red)) => follow.red_wins += 1,
+        Some(GameWinner::Team(OsuScoreMatchTeam::Blue)) => {
+            follow.blue_wins += 1
+        }
+        _ => {}
+    }
+}
+
+/// Scores of the finished game with the running match score
+fn game_embed(
+    summary: &MatchSummary,
+    game: &MatchGame,
+    follow: &OsuMatchFollow,
+) -> Embed {
+    let is_team = game.team_kind.is_team();
+    let (red_name, blue_name) = summary.team_names();
+
+    let beatmap = summary
+        .beatmaps
+        .get(&game.beatmap_id)
+        .map_or("Unknown beatmap", |x| x.as_str());
+
+    let mut description = String::with_capacity(500);
+
+    let _ = writeln!(
+        description,
+        "**[{}](https://osu.ppy.sh/b/{})** +{}",
+        beatmap, game.beatmap_id, game.mods
+    );
+
+    if is_team {
+        let _ = writeln!(
+            description,
+            "## {} {red_name} {} – {} {blue_name} {}",
+            team_emoji(OsuScoreMatchTeam::Red),
+            follow.red_wins,
+            follow.blue_wins,
+            team_emoji(OsuScoreMatchTeam::Blue),
+        );
+    }
+
+    match game.winner() {
+        Some(GameWinner::Team(team)) => {
+            let (red, blue) = game.team_totals();
+
+            let winner = match team {
+                OsuScoreMatchTeam::Blue => &blue_name,
+                _ => &red_name,
+            };
+
+            let _ = writeln!(
+                description,
+                "{} {winner} wins • {} – {}",
+                team_emoji(team),
+                game.format_value(red),
+                game.format_value(blue)
+            );
+        }
+        Some(GameWinner::Player(user_id)) => {
+            let _ =
+                writeln!(description, "🏆 {} wins", summary.username(user_id));
+        }
+        None => {
+            let _ = writeln!(description, "Draw");
+        }
+    }
+
+    let mut scores: Vec<_> = game.scores.iter().collect();
+    scores.sort_by(|a, b| game.score_value(b).total_cmp(&game.score_value(a)));
+
+    let mut scores_field = String::with_capacity(1000);
+
+    for score in scores.iter().take(MAX_SCORES_SHOWN) {
+        if is_team {
+            let _ = write!(scores_field, "{} ", team_emoji(score.team));
+        }
+
+        let _ = write!(
+            scores_field,
+            "**{}** • {} • {:.2}% • x{}",
+            summary.username(score.user_id),
+            score.score.to_formatted_string(&Locale::en),
+            score.accuracy * 100.0,
+            score.max_combo
+        );
+
+        if !score.pass {
+            scores_field.push_str(" • failed");
+        }
+
+        scores_field.push('\n');
+    }
+
+    if scores.len() > MAX_SCORES_SHOWN {
+        let _ = writeln!(
+            scores_field,
+            "and {} more",
+            scores.len() - MAX_SCORES_SHOWN
+        );
+    }
+
+    EmbedBuilder::new()
+        .color(123432)
+        .title(&summary.name)
+        .url(format!(
+            "https://osu.ppy.sh/community/matches/{}",
+            summary.id
+        ))
+        .description(description)
+        .field(EmbedFieldBuilder::new("Scores", scores_field))
+        .build()
+}
+
+async fn send_embed(
+    ctx: &FumoContext,
+    channel_id: i64,
+    embed: &Embed,
+) -> Result<()> {
+    ctx.http
+        .create_message(Id::new(channel_id as u64))
+        .embeds(std::slice::from_ref(embed))?
+        .await?;
+
+    Ok(())
+}
+
+/// Posts the final summary of the match
+async fn send_summary(
+    ctx: &FumoContext,
+    follow: &OsuMatchFollow,
+) -> Result<()> {
+    let Some(summary) = MatchSummary::load(ctx, follow.match_id).await? else {
+        return Ok(());
+    };
+
+    let players = summary.players();
+    let games_len = summary.games.len();
+
+    let mut match_list = MatchListing::new(summary, players)
+        .calculate_pages(games_len, games_len.clamp(1, MAX_SUMMARY_GAMES));
+
+    match_list.update();
+
+    if let Some(embed) = &match_list.embed {
+        send_embed(ctx, follow.channel_id, embed).await?;
+    }
+
+    Ok(())
+}
+
+/// Posts games finished since the last check, matches followed in
+/// multiple channels share the response if their progress is the same
+async fn check_follow(
+    ctx: &FumoContext,
+    follow: &mut OsuMatchFollow,
+    cache: &mut HashMap<(i64, i64), OsuMatchGet>,
+) -> Result<()> {
+    let osu_match = match cache.entry((follow.match_id, follow.last_event_id)) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => {
+            let res = ctx
+                .osu_api
+                .get_match(
+                    follow.match_id,
+                    None,
+                    Some(follow.last_event_id),
+                    None,
+                )
+                .await;
+
+            match res {
+                Ok(osu_match) => entry.insert(osu_match),
+                Err(OsuApiError::NotFound { .. }) => {
+                    ctx.db
+                        .remove_osu_match_follow(
+                            follow.match_id,
+                            follow.channel_id,
+                        )
+                        .await?;
+
+                    return Ok(());
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    };
+
+    let is_closed = osu_match.osu_match.end_time.is_some();
+
+    let (games, cursor) =
+        completed_games(&osu_match.events, follow.last_event_id, is_closed);
+
+    if !games.is_empty() {
+        let beatmaps = games
+            .iter()
+            .filter_map(|x| {
+                Some((x.beatmap_id, x.beatmap.as_ref()?.metadata()))
+            })
+            .collect();
+
+        let summary = MatchSummary {
+            id: osu_match.osu_match.id,
+            name: osu_match.osu_match.name.clone(),
+            start_time: osu_match.osu_match.start_time.naive_utc(),
+            end_time: None,
+            games: games.into_iter().map(MatchGame::from_api).collect(),
+            teams: parse_teams(&osu_match.osu_match.name),
+            beatmaps,
+            usernames: HashMap::new(),
+        }
+        .fill_missing(ctx)
+        .await?;
+
+        for game in &summary.games {
+            add_win(follow, game);
+
+            let embed = game_embed(&summary, game, follow);
+
+            if let Err(e) = send_embed(ctx, follow.channel_id, &embed).await {
+                tracing::warn!(
+                    channel_id = follow.channel_id,
+                    match_id = follow.match_id,
+                    "Failed to send followed match game: {e}"
+                );
+            }
+        }
+    }
+
+    let is_finished = is_closed && cursor >= osu_match.latest_event_id;
+
+    if cursor != follow.last_event_id {
+        follow.last_event_id = cursor;
+        ctx.db.update_osu_match_follow(follow).await?;
+    }
+
+    if is_finished {
+        if let Err(e) = send_summary(ctx, follow).await {
+            tracing::warn!(
+                channel_id = follow.channel_id,
+                match_id = follow.match_id,
+                "Failed to send followed match summary: {e}"
+            );
+        }
+
+        ctx.db
+            .remove_osu_match_follow(follow.match_id, follow.channel_id)
+            .await?;
+    }
+
+    Ok(())
+}
+
+async fn check_follows(ctx: &FumoContext) -> Result<()> {
+    let follows = ctx.db.select_osu_match_follows().await?;
+    let mut cache = HashMap::new();
+
+    for mut follow in follows {
+        if let Err(e) = check_follow(ctx, &mut follow, &mut cache).await {
+            tracing::error!(
+                match_id = follow.match_id,
+                "Failed to check followed match: {e}"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn match_follow_worker(ctx: Arc<FumoContext>) {
+    tracing::info!("Starting multiplayer follow worker!");
+
+    let mut interval = tokio::time::interval(FOLLOW_INTERVAL);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        if let Err(e) = check_follows(&ctx).await {
+            tracing::error!("Failed to check followed matches: {e}");
+        }
+    }
+}
+
+/// Post games of the live match to this channel as they finish
+#[derive(CommandModel, CreateCommand, Debug)]
+#[command(name = "follow")]
+pub struct MultiplayerFollow {
+    /// Match ID or link to the match
+    #[command(min_length = 1, max_length = 256)]
+    osu_match: String,
+
+    /// Stop following the match
+    stop: Option<bool>,
+}
+
+impl MultiplayerFollow {
+    pub async fn run(
+        &self,
+        ctx: &FumoContext,
+        cmd: InteractionCommand,
+    ) -> Result<()> {
+        let mut msg = MessageBuilder::new().flags(MessageFlags::EPHEMERAL);
+
+        if cmd.guild_id.is_none() {
+            msg = msg.content("This command is available only on servers!");
+            cmd.response(ctx, &msg).await?;
+            return Ok(());
+        }
+
+        let can_manage = cmd
+            .member
+            .as_ref()
+            .and_then(|x| x.permissions)
+            .is_some_and(|x| x.contains(Permissions::MANAGE_GUILD));
+
+        if !can_manage {
+            msg =
+                msg.content("You need `Manage Server` permission to do that!");
+            cmd.response(ctx, &msg).await?;
+            return Ok(());
+        }
+
+        let Some(match_id) = parse_match_id(&self.osu_match) else {
+            msg = msg.content("Please provide valid match ID or link");
+            cmd.response(ctx, &msg).await?;
+            return Ok(());
+        };
+
+        let channel_id = cmd.channel_id.get() as i64;
+
+        if self.stop.unwrap_or(false) {
+            let removed =
+                ctx.db.remove_osu_match_follow(match_id, channel_id).await?;
+
+            msg = msg.content(if removed {
+                "Stopped following the match"
+            } else {
+                "This match is not followed in this channel"
+            });
+            cmd.response(ctx, &msg).await?;
+            return Ok(());
+        }
+
+        if ctx.db.count_osu_match_follows(channel_id).await?
+            >= MAX_CHANNEL_FOLLOWS
+        {
+            msg = msg.content(format!(
+                "Only {MAX_CHANNEL_FOLLOWS} matches can be followed in one channel"
+            ));
+            cmd.response(ctx, &msg).await?;
+            return Ok(());
+        }
+
+        cmd.defer(ctx).await?;
+
+        let mut msg = MessageBuilder::new();
+
+        let osu_match = match ctx.osu_api.get_match_all_events(match_id).await {
+            Ok(osu_match) => osu_match,
+            Err(OsuApiError::NotFound { .. }) => {
+                msg = msg.content("Match is not found on osu!");
+                cmd.update(ctx, &msg).await?;
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        if osu_match.osu_match.end_time.is_some() {
+            msg = msg.content(
+                "Match has already ended, use `/multiplayer match` to see the summary",
+            );
+            cmd.update(ctx, &msg).await?;
+            return Ok(());
+        }
+
+        // Games that already finished are counted
+        // to the match score but not posted
+        let (games, last_event_id) =
+            completed_games(&osu_match.events, 0, false);
+
+        let mut follow = OsuMatchFollow {
+            match_id,
+            channel_id,
+            last_event_id,
+            red_wins: 0,
+            blue_wins: 0,
+        };
+
+        for game in games {
+            add_win(&mut follow, &MatchGame::from_api(game));
+        }
+
+        if !ctx.db.add_osu_match_follow(&follow).await? {
+            msg = msg.content("This match is already followed in this channel");
+            cmd.update(ctx, &msg).await?;
+            return Ok(());
+        }
+
+        msg = msg.content(format!(
+            "Following **{}**, games will be posted here as they finish",
+            osu_match.osu_match.name
+        ));
+        cmd.update(ctx, &msg).await?;
+
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@ use fumo_database::osu::OsuDbMatchScore;
 use fumo_macro::listing;
 use fumo_twilight::message::MessageBuilder;
 use num_format::{Locale, ToFormattedString};
-use osu_api::models::{OsuBeatmap, OsuMods};
+use osu_api::models::{OsuBeatmap, OsuMods, UserId};
 use std::{collections::HashSet, fmt::Write};
 use twilight_interactions::command::{CommandModel, CreateCommand};
 use twilight_model::channel::message::Embed;
@@ -15,13 +15,13 @@ use twilight_model::application::interaction::{Interaction, InteractionData};
 
 use std::time::Duration;
 
-use super::ListKind;
+use super::{tournament_acronym, ListKind};
 use crate::{
     components::listing::ListingTrait,
     fumo_context::FumoContext,
     utils::{
         interaction::{InteractionCommand, InteractionComponent},
-        searching::{find_beatmap_link, parse_beatmap_link},
+        searching::{find_recent_beatmap, parse_beatmap_link},
         static_components::pages_components,
     },
 };
@@ -36,6 +36,9 @@ pub struct MultiplayerLeaderboard {
 
     /// Beatmap ID or beatmap link
     pub beatmap: Option<String>,
+
+    /// Tournament acronym e.g. OWC2023, implies tournament matches
+    pub tournament: Option<String>,
 }
 
 #[listing]
@@ -133,7 +136,9 @@ impl MultiplayerLeaderboard {
 
         let beatmap = match &self.beatmap {
             Some(link) => {
-                let Some(beatmap_id) = parse_beatmap_link(link.as_ref()) else {
+                let Some(beatmap_id) =
+                    parse_beatmap_link(ctx, link, None).await?
+                else {
                     let builder = MessageBuilder::new().content(
                         "Failed to parse beatmap id from provided link",
                     );
@@ -143,44 +148,42 @@ impl MultiplayerLeaderboard {
 
                 beatmap_id as i64
             }
-            None => 'blk: {
-                // Try to fetch from recent messages
-                let msgs = ctx
-                    .http
-                    .channel_messages(cmd.channel_id)
-                    .limit(50)?
-                    .await?
-                    .models()
-                    .await?;
-
-                for msg in msgs {
-                    if let Some(link) = find_beatmap_link(&msg) {
-                        if let Some(bid) = parse_beatmap_link(link.as_ref()) {
-                            break 'blk bid as i64;
-                        }
-                    }
-                }
-
-                let builder = MessageBuilder::new()
-                    .content("Failed to find beatmap from recent 50 messages");
-                cmd.update(ctx, &builder).await?;
-                return Ok(());
+            None => {
+                let Some(bid) =
+                    find_recent_beatmap(ctx, cmd.channel_id, None).await?
+                else {
+                    let builder = MessageBuilder::new().content(
+                        "Failed to find beatmap from recent 50 messages",
+                    );
+                    cmd.update(ctx, &builder).await?;
+                    return Ok(());
+                };
+
+                bid as i64
             }
         };
 
+        let tournament = tournament_acronym(&self.tournament);
+        let is_tournament = self.kind.is_tournament() || tournament.is_some();
+
         let mut scores = ctx
             .db
-            .select_beatmap_scores(beatmap, self.kind.is_tournament())
+            .select_beatmap_scores(
+                beatmap,
+                is_tournament,
+                tournament.as_deref(),
+            )
             .await?;
 
         // Finding a users that doesn't have a cached username
-        let fetch_usernames: Vec<i64> = scores
+        let fetch_usernames: Vec<UserId> = scores
             .iter()
             .filter(|score| score.osu_username.is_none())
             .map(|score| score.user_id)
             .collect::<HashSet<i64>>()
             .into_iter()
-            .collect::<Vec<i64>>();
+            .map(UserId::Id)
+            .collect();
 
         if !fetch_usernames.is_empty() {
             let temp_msg = MessageBuilder::new().embed(
@@ -200,7 +203,11 @@ impl MultiplayerLeaderboard {
             // Fetching second time, TODO yep thats bad
             scores = ctx
                 .db
-                .select_beatmap_scores(beatmap, self.kind.is_tournament())
+                .select_beatmap_scores(
+                    beatmap,
+                    is_tournament,
+                    tournament.as_deref(),
+                )
                 .await?;
         }
 
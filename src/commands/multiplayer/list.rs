@@ -21,7 +21,7 @@ use crate::{
     },
 };
 
-use super::ListKind;
+use super::{tournament_acronym, ListKind};
 
 #[listing]
 pub struct MatchesListing {
@@ -96,6 +96,9 @@ pub struct MultiplayerList {
 
     /// osu! user id or username
     user: Option<String>,
+
+    /// Tournament acronym e.g. OWC2023, implies tournament matches
+    tournament: Option<String>,
 }
 
 impl MultiplayerList {
@@ -142,13 +145,17 @@ impl MultiplayerList {
 
         let osu_api_user = osu_api_user.unwrap();
 
-        let matches = match self.kind {
-            ListKind::All => {
-                ctx.db.get_user_matches_all(osu_api_user.id).await?
-            }
-            ListKind::Tournament => {
-                ctx.db.get_user_matches_tourney(osu_api_user.id).await?
-            }
+        let tournament = tournament_acronym(&self.tournament);
+
+        let matches = if self.kind.is_tournament() || tournament.is_some() {
+            ctx.db
+                .get_user_matches_tourney(
+                    osu_api_user.id,
+                    tournament.as_deref(),
+                )
+                .await?
+        } else {
+            ctx.db.get_user_matches_all(osu_api_user.id).await?
         };
 
         let matches_len = matches.len();
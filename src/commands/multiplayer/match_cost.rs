@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use super::summary::MatchGame;
+
+/// Max multiplier for the players that played every map
+const PARTICIPATION_BONUS: f64 = 1.4;
+
+fn median(values: &mut [f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+
+    values.sort_by(|a, b| a.total_cmp(b));
+
+    let middle = values.len() / 2;
+
+    if values.len().is_multiple_of(2) {
+        (values[middle - 1] + values[middle]) / 2.0
+    } else {
+        values[middle]
+    }
+}
+
+/// Match costs of the players over the games of a single match.
+///
+/// Every score is normalized by the median of the game and
+/// `MC = 2 / (n + 2) * Σ(S / M) * 1.4 ^ (((n - 1) / (N - 1)) ^ 0.6)`
+/// where `n` is amount of games played by the player
+/// and `N` is amount of games in the match.
+/// Games where median is zero are not counted
+pub fn match_costs(games: &[MatchGame]) -> HashMap<i64, f64> {
+    let mut ratios: HashMap<i64, (f64, usize)> = HashMap::new();
+    let mut games_counted = 0;
+
+    for game in games {
+        let mut values: Vec<f64> =
+            game.scores.iter().map(|x| game.score_value(x)).collect();
+
+        let median = median(&mut values);
+
+        if median <= 0.0 {
+            continue;
+        }
+
+        games_counted += 1;
+
+        for score in &game.scores {
+            let entry = ratios.entry(score.user_id).or_insert((0.0, 0));
+
+            entry.0 += game.score_value(score) / median;
+            entry.1 += 1;
+        }
+    }
+
+    ratios
+        .into_iter()
+        .map(|(user_id, (sum, played))| {
+            let played = played as f64;
+
+            let participation = if games_counted > 1 {
+                (played - 1.0) / (games_counted as f64 - 1.0)
+            } else {
+                1.0
+            };
+
+            let cost = 2.0 / (played + 2.0)
+                * sum
+                * PARTICIPATION_BONUS.powf(participation.powf(0.6));
+
+            (user_id, cost)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use osu_api::models::{
+        osu_matches::{ScoringKind, TeamKind},
+        OsuMods, OsuScoreMatchTeam,
+    };
+
+    use super::super::summary::MatchScore;
+
+    use super::*;
+
+    fn score(user_id: i64, score: i64, accuracy: f32) -> MatchScore {
+        MatchScore {
+            user_id,
+            score,
+            accuracy,
+            max_combo: 100,
+            team: OsuScoreMatchTeam::None,
+            pass: true,
+        }
+    }
+
+    fn game(scoring_kind: ScoringKind, scores: Vec<MatchScore>) -> MatchGame {
+        MatchGame {
+            beatmap_id: 1,
+            mods: OsuMods::NOMOD,
+            scoring_kind,
+            team_kind: TeamKind::HeadToHead,
+            scores,
+        }
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&mut []), 0.0);
+        assert_eq!(median(&mut [3.0, 1.0, 2.0]), 2.0);
+        assert_eq!(median(&mut [4.0, 1.0, 3.0, 2.0]), 2.5);
+    }
+
+    #[test]
+    fn test_equal_players() {
+        let games: Vec<MatchGame> = (0..4)
+            .map(|_| {
+                game(
+                    ScoringKind::ScoreV2,
+                    vec![score(1, 500_000, 0.9), score(2, 500_000, 0.9)],
+                )
+            })
+            .collect();
+
+        let costs = match_costs(&games);
+
+        // 2 / 6 * 4 * 1.4
+        assert!((costs[&1] - 1.4 * 4.0 / 3.0).abs() < 1e-9);
+        assert!((costs[&1] - costs[&2]).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_participation() {
+        let mut games: Vec<MatchGame> = (0..4)
+            .map(|_| {
+                game(
+                    ScoringKind::ScoreV2,
+                    vec![
+                        score(1, 500_000, 0.9),
+                        score(2, 500_000, 0.9),
+                        score(3, 500_000, 0.9),
+                    ],
+                )
+            })
+            .collect();
+
+        // Third player played only the first game
+        for game in games.iter_mut().skip(1) {
+            game.scores.pop();
+        }
+
+        let costs = match_costs(&games);
+
+        assert!(costs[&3] < costs[&1]);
+        assert!((costs[&3] - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_scoring_kind() {
+        let games = [game(
+            ScoringKind::Accuracy,
+            vec![score(1, 100_000, 1.0), score(2, 900_000, 0.5)],
+        )];
+
+        let costs = match_costs(&games);
+
+        assert!(costs[&1] > costs[&2]);
+    }
+}
@@ -1,17 +1,33 @@
 use compare::MultiplayerCompare;
 use eyre::Result;
+use follow::MultiplayerFollow;
+use fumo_database::osu::tournament::normalize_acronym;
 use leaderboard::MultiplayerLeaderboard;
+use seeding::MultiplayerSeeding;
+use stats::MultiplayerStats;
+use summary::MultiplayerMatch;
+use tournament::MultiplayerTournament;
 use twilight_interactions::command::{
     CommandModel, CommandOption, CreateCommand, CreateOption,
 };
+use versus::MultiplayerVersus;
 
 use crate::{
     fumo_context::FumoContext, utils::interaction::InteractionCommand,
 };
 
 mod compare;
+mod follow;
 mod leaderboard;
 mod list;
+mod match_cost;
+mod seeding;
+mod stats;
+mod summary;
+mod tournament;
+mod versus;
+
+pub use follow::match_follow_worker;
 
 use list::MultiplayerList;
 
@@ -32,6 +48,11 @@ impl ListKind {
     }
 }
 
+/// Tournament acronym from the command option, `owc 2023` => `OWC2023`
+fn tournament_acronym(tournament: &Option<String>) -> Option<String> {
+    tournament.as_deref().map(normalize_acronym)
+}
+
 /// All osu! multiplayer related commands
 #[derive(CommandModel, CreateCommand, Debug)]
 #[command(name = "multiplayer")]
@@ -42,6 +63,18 @@ pub enum MultiplayerCommands {
     Compare(MultiplayerCompare),
     #[command(name = "leaderboard")]
     Leaderboard(MultiplayerLeaderboard),
+    #[command(name = "match")]
+    Match(MultiplayerMatch),
+    #[command(name = "stats")]
+    Stats(MultiplayerStats),
+    #[command(name = "versus")]
+    Versus(MultiplayerVersus),
+    #[command(name = "tournament")]
+    Tournament(MultiplayerTournament),
+    #[command(name = "seeding")]
+    Seeding(MultiplayerSeeding),
+    #[command(name = "follow")]
+    Follow(MultiplayerFollow),
 }
 
 impl MultiplayerCommands {
@@ -76,6 +109,54 @@ impl MultiplayerCommands {
                     .inc();
                 command.run(ctx, cmd).await
             }
+            MultiplayerCommands::Match(command) => {
+                ctx.stats
+                    .bot
+                    .cmd
+                    .with_label_values(&["multiplayer_match"])
+                    .inc();
+                command.run(ctx, cmd).await
+            }
+            MultiplayerCommands::Stats(command) => {
+                ctx.stats
+                    .bot
+                    .cmd
+                    .with_label_values(&["multiplayer_stats"])
+                    .inc();
+                command.run(ctx, cmd).await
+            }
+            MultiplayerCommands::Versus(command) => {
+                ctx.stats
+                    .bot
+                    .cmd
+                    .with_label_values(&["multiplayer_versus"])
+                    .inc();
+                command.run(ctx, cmd).await
+            }
+            MultiplayerCommands::Tournament(command) => {
+                ctx.stats
+                    .bot
+                    .cmd
+                    .with_label_values(&["multiplayer_tournament"])
+                    .inc();
+                command.run(ctx, cmd).await
+            }
+            MultiplayerCommands::Seeding(command) => {
+                ctx.stats
+                    .bot
+                    .cmd
+                    .with_label_values(&["multiplayer_seeding"])
+                    .inc();
+                command.run(ctx, cmd).await
+            }
+            MultiplayerCommands::Follow(command) => {
+                ctx.stats
+                    .bot
+                    .cmd
+                    .with_label_values(&["multiplayer_follow"])
+                    .inc();
+                command.run(ctx, cmd).await
+            }
         }
     }
 }
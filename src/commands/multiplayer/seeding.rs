@@ -0,0 +1,617 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    time::Duration,
+};
+
+use eyre::Result;
+use fumo_macro::listing;
+use fumo_twilight::message::MessageBuilder;
+use num_format::{Locale, ToFormattedString};
+use tokio_stream::StreamExt;
+use twilight_interactions::command::{
+    CommandModel, CommandOption, CreateCommand, CreateOption,
+};
+use twilight_model::{
+    application::interaction::{Interaction, InteractionData},
+    channel::{message::MessageFlags, Attachment},
+    http::attachment::Attachment as HttpAttachment,
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFooterBuilder};
+
+use crate::{
+    components::listing::ListingTrait,
+    fumo_context::FumoContext,
+    utils::{
+        interaction::{InteractionCommand, InteractionComponent},
+        static_components::pages_components,
+    },
+};
+
+use super::summary::{fill_usernames, parse_match_id, MatchGame, MatchSummary};
+
+/// Max amount of matches seeding is calculated from
+const MAX_SEEDING_MATCHES: usize = 50;
+
+const MAX_SEEDING_FILE_SIZE: u64 = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, CommandOption, CreateOption)]
+pub enum SeedingMethod {
+    #[option(name = "Sum of scores", value = "sum")]
+    Sum,
+    #[option(name = "Average rank", value = "average_rank")]
+    AverageRank,
+    #[option(name = "Z-sum", value = "z_sum")]
+    ZSum,
+    #[option(name = "Percentile", value = "percentile")]
+    Percentile,
+}
+
+impl SeedingMethod {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SeedingMethod::Sum => "Sum of scores",
+            SeedingMethod::AverageRank => "Average rank",
+            SeedingMethod::ZSum => "Z-sum",
+            SeedingMethod::Percentile => "Percentile",
+        }
+    }
+
+    /// Average rank is the only method where lower value is better
+    fn is_ascending(&self) -> bool {
+        matches!(self, SeedingMethod::AverageRank)
+    }
+
+    pub fn format_value(&self, value: f64) -> String {
+        match self {
+            SeedingMethod::Sum => {
+                (value as i64).to_formatted_string(&Locale::en)
+            }
+            SeedingMethod::AverageRank => format!("{value:.2}"),
+            SeedingMethod::ZSum => format!("{value:.3}"),
+            SeedingMethod::Percentile => format!("{value:.2}%"),
+        }
+    }
+}
+
+/// Best scores of the players on every qualifier map
+#[derive(Default)]
+pub struct QualifierScores {
+    /// Maps in the order they were played for the first time
+    pub beatmaps: Vec<i64>,
+    /// User id to the beatmap id and the best score on it
+    pub scores: HashMap<i64, HashMap<i64, i64>>,
+}
+
+impl QualifierScores {
+    pub fn add_games(&mut self, games: &[MatchGame]) {
+        for game in games {
+            if !self.beatmaps.contains(&game.beatmap_id) {
+                self.beatmaps.push(game.beatmap_id);
+            }
+
+            for score in &game.scores {
+                let best = self
+                    .scores
+                    .entry(score.user_id)
+                    .or_default()
+                    .entry(game.beatmap_id)
+                    .or_default();
+
+                *best = (*best).max(score.score);
+            }
+        }
+    }
+
+    pub fn score(&self, user_id: i64, beatmap_id: i64) -> Option<i64> {
+        self.scores.get(&user_id)?.get(&beatmap_id).copied()
+    }
+
+    /// Rank of the player on the map, players with the same score
+    /// share the rank
+    pub fn rank(&self, user_id: i64, beatmap_id: i64) -> Option<usize> {
+        let score = self.score(user_id, beatmap_id)?;
+
+        let better = self
+            .scores
+            .values()
+            .filter_map(|x| x.get(&beatmap_id))
+            .filter(|&&x| x > score)
+            .count();
+
+        Some(better + 1)
+    }
+
+    fn map_scores(&self, beatmap_id: i64) -> Vec<i64> {
+        self.scores
+            .values()
+            .filter_map(|x| x.get(&beatmap_id).copied())
+            .collect()
+    }
+
+    fn value(&self, user_id: i64, method: SeedingMethod) -> f64 {
+        let maps = self.beatmaps.len().max(1) as f64;
+
+        match method {
+            SeedingMethod::Sum => self
+                .beatmaps
+                .iter()
+                .filter_map(|&x| self.score(user_id, x))
+                .sum::<i64>() as f64,
+            // Missed maps count as the rank after the last player
+            SeedingMethod::AverageRank => {
+                self.beatmaps
+                    .iter()
+                    .map(|&x| {
+                        self.rank(user_id, x)
+                            .unwrap_or_else(|| self.map_scores(x).len() + 1)
+                            as f64
+                    })
+                    .sum::<f64>()
+                    / maps
+            }
+            // Missed maps count as zero score
+            SeedingMethod::ZSum => self
+                .beatmaps
+                .iter()
+                .map(|&x| {
+                    let scores = self.map_scores(x);
+                    let len = scores.len() as f64;
+
+                    let mean = scores.iter().sum::<i64>() as f64 / len;
+                    let deviation = (scores
+                        .iter()
+                        .map(|&x| (x as f64 - mean).powi(2))
+                        .sum::<f64>()
+                        / len)
+                        .sqrt();
+
+                    if deviation == 0.0 {
+                        return 0.0;
+                    }
+
+                    let score = self.score(user_id, x).unwrap_or(0);
+
+                    (score as f64 - mean) / deviation
+                })
+                .sum(),
+            // Percent of players that are not better on the map,
+            // missed maps count as zero
+            SeedingMethod::Percentile => {
+                self.beatmaps
+                    .iter()
+                    .filter_map(|&x| {
+                        let rank = self.rank(user_id, x)?;
+                        let players = self.map_scores(x).len();
+
+                        Some(1.0 - (rank - 1) as f64 / players as f64)
+                    })
+                    .sum::<f64>()
+                    / maps
+                    * 100.0
+            }
+        }
+    }
+
+    /// Players sorted by their seed, ties are broken by user id
+    pub fn seeds(&self, method: SeedingMethod) -> Vec<(i64, f64)> {
+        let mut seeds: Vec<(i64, f64)> = self
+            .scores
+            .keys()
+            .map(|&user_id| (user_id, self.value(user_id, method)))
+            .collect();
+
+        seeds.sort_by(|a, b| {
+            let ordering = if method.is_ascending() {
+                a.1.total_cmp(&b.1)
+            } else {
+                b.1.total_cmp(&a.1)
+            };
+
+            ordering.then(a.0.cmp(&b.0))
+        });
+
+        seeds
+    }
+}
+
+#[listing]
+pub struct SeedingListing {
+    method: SeedingMethod,
+    scores: QualifierScores,
+    seeds: Vec<(i64, f64)>,
+    usernames: HashMap<i64, String>,
+    matches: usize,
+    /// Provided match ids that weren't found on osu!
+    not_found: Vec<i64>,
+}
+
+impl SeedingListing {
+    fn username(&self, user_id: i64) -> String {
+        self.usernames
+            .get(&user_id)
+            .cloned()
+            .unwrap_or_else(|| user_id.to_string())
+    }
+
+    fn maps_played(&self, user_id: i64) -> usize {
+        self.scores.scores.get(&user_id).map_or(0, |x| x.len())
+    }
+
+    /// Seeds with the score and rank on every map as a csv file
+    fn csv(&self) -> String {
+        let mut csv = String::from("seed,user_id,username,value,maps_played");
+
+        for beatmap_id in &self.scores.beatmaps {
+            let _ = write!(csv, ",{beatmap_id}_score,{beatmap_id}_rank");
+        }
+
+        csv.push('\n');
+
+        for (index, (user_id, value)) in self.seeds.iter().enumerate() {
+            let username = self.username(*user_id);
+
+            let _ = write!(
+                csv,
+                "{},{user_id},{},{value},{}",
+                index + 1,
+                if username.contains([',', '"']) {
+                    format!("\"{}\"", username.replace('"', "\"\""))
+                } else {
+                    username
+                },
+                self.maps_played(*user_id)
+            );
+
+            for &beatmap_id in &self.scores.beatmaps {
+                let score = self.scores.score(*user_id, beatmap_id);
+                let rank = self.scores.rank(*user_id, beatmap_id);
+
+                let _ = write!(
+                    csv,
+                    ",{},{}",
+                    score.map_or(String::new(), |x| x.to_string()),
+                    rank.map_or(String::new(), |x| x.to_string())
+                );
+            }
+
+            csv.push('\n');
+        }
+
+        csv
+    }
+}
+
+impl ListingTrait for SeedingListing {
+    async fn handle_interaction_component(
+        &mut self,
+        ctx: &FumoContext,
+        component: &InteractionComponent,
+    ) {
+        let _ = component.defer(ctx).await;
+
+        if let Some(data) = &component.data {
+            match data.custom_id.as_ref() {
+                "B1" => self.previous_page(),
+                "B2" => self.next_page(),
+                _ => {}
+            }
+        }
+    }
+
+    fn update(&mut self) {
+        let footer = EmbedFooterBuilder::new(format!(
+            "Page {}/{}",
+            self.current_page, self.max_pages
+        ));
+
+        let mut description = String::with_capacity(2000);
+
+        let _ = writeln!(
+            description,
+            "Method: **{}** • Matches: **{}** • Maps: **{}** • Players: **{}**",
+            self.method.name(),
+            self.matches,
+            self.scores.beatmaps.len(),
+            self.seeds.len()
+        );
+
+        if !self.not_found.is_empty() {
+            let not_found: Vec<String> =
+                self.not_found.iter().map(|x| x.to_string()).collect();
+
+            let _ =
+                writeln!(description, "Not found: {}", not_found.join(", "));
+        }
+
+        description.push('\n');
+
+        let start_at = (self.current_page - 1) * self.entries_per_page;
+
+        let seeds = self
+            .seeds
+            .iter()
+            .enumerate()
+            .skip(start_at)
+            .take(self.entries_per_page);
+
+        for (index, (user_id, value)) in seeds {
+            let _ = writeln!(
+                description,
+                "`#{}` **{}** • **{}** • Maps: **{}/{}**",
+                index + 1,
+                self.username(*user_id),
+                self.method.format_value(*value),
+                self.maps_played(*user_id),
+                self.scores.beatmaps.len()
+            );
+        }
+
+        let embed = EmbedBuilder::new()
+            .color(123432)
+            .title("Qualifier seeding")
+            .description(description)
+            .footer(footer)
+            .build();
+
+        self.embed = Some(embed);
+    }
+}
+
+/// Qualifier seeding calculated from the scores of the matches
+#[derive(CommandModel, CreateCommand, Debug)]
+#[command(name = "seeding")]
+pub struct MultiplayerSeeding {
+    /// Match ids or links separated by spaces
+    matches: Option<String>,
+
+    /// Text file with match ids or links
+    file: Option<Attachment>,
+
+    /// Seeding method, sum of scores by default
+    method: Option<SeedingMethod>,
+
+    /// Attach the seeding as a csv file
+    export: Option<bool>,
+}
+
+impl MultiplayerSeeding {
+    pub async fn run(
+        &self,
+        ctx: &FumoContext,
+        cmd: InteractionCommand,
+    ) -> Result<()> {
+        if self.matches.is_none() && self.file.is_none() {
+            let msg = MessageBuilder::new()
+                .flags(MessageFlags::EPHEMERAL)
+                .content("Please provide match ids or a file with them");
+            cmd.response(ctx, &msg).await?;
+            return Ok(());
+        }
+
+        if self
+            .file
+            .as_ref()
+            .is_some_and(|x| x.size > MAX_SEEDING_FILE_SIZE)
+        {
+            let msg = MessageBuilder::new()
+                .flags(MessageFlags::EPHEMERAL)
+                .content("File is too big!");
+            cmd.response(ctx, &msg).await?;
+            return Ok(());
+        }
+
+        cmd.defer(ctx).await?;
+
+        let mut text = self.matches.clone().unwrap_or_default();
+
+        if let Some(file) = &self.file {
+            let bytes = reqwest::get(&file.url).await?.bytes().await?;
+
+            text.push('\n');
+            text.push_str(&String::from_utf8_lossy(&bytes));
+        }
+
+        // Removing duplicates while keeping order
+        let mut seen = HashSet::new();
+        let match_ids: Vec<i64> = text
+            .split(|x: char| x.is_whitespace() || x == ',')
+            .filter_map(parse_match_id)
+            .filter(|x| seen.insert(*x))
+            .collect();
+
+        if match_ids.is_empty() || match_ids.len() > MAX_SEEDING_MATCHES {
+            let msg = MessageBuilder::new().content(format!(
+                "Please provide from 1 to {MAX_SEEDING_MATCHES} matches"
+            ));
+            cmd.update(ctx, &msg).await?;
+            return Ok(());
+        }
+
+        let mut scores = QualifierScores::default();
+        let mut usernames = HashMap::new();
+        let mut not_found = Vec::new();
+
+        let mut user_ids = HashSet::new();
+
+        // Beatmaps are not needed for the seeding and
+        // usernames are fetched once for all of the matches
+        for &match_id in &match_ids {
+            let Some(summary) = MatchSummary::load_games(ctx, match_id).await?
+            else {
+                not_found.push(match_id);
+                continue;
+            };
+
+            user_ids.extend(
+                summary
+                    .games
+                    .iter()
+                    .flat_map(|x| x.scores.iter().map(|x| x.user_id)),
+            );
+
+            scores.add_games(&summary.games);
+            usernames.extend(summary.usernames);
+        }
+
+        fill_usernames(ctx, &mut usernames, user_ids).await?;
+
+        let method = self.method.unwrap_or(SeedingMethod::Sum);
+        let seeds = scores.seeds(method);
+
+        if seeds.is_empty() {
+            let msg = MessageBuilder::new()
+                .content("No scores found in the provided matches!");
+            cmd.update(ctx, &msg).await?;
+            return Ok(());
+        }
+
+        let seeds_len = seeds.len();
+        let matches = match_ids.len() - not_found.len();
+
+        let mut seeding_list = SeedingListing::new(
+            method, scores, seeds, usernames, matches, not_found,
+        )
+        .calculate_pages(seeds_len, 15);
+
+        seeding_list.update();
+
+        let mut msg_builder = MessageBuilder::new()
+            .embed(
+                seeding_list
+                    .embed
+                    .as_ref()
+                    .expect("embed should be present")
+                    .clone(),
+            )
+            .components(pages_components());
+
+        if self.export.unwrap_or(false) {
+            msg_builder =
+                msg_builder.attachments(vec![HttpAttachment::from_bytes(
+                    String::from("seeding.csv"),
+                    seeding_list.csv().into_bytes(),
+                    1,
+                )]);
+        }
+
+        let msg = cmd.update(ctx, &msg_builder).await?.model().await?;
+
+        // Keeping already uploaded csv file on the page updates
+        msg_builder.attachments = None;
+
+        let msg_stream = component_stream!(ctx, msg);
+
+        tokio::pin!(msg_stream);
+
+        while let Some(Ok(component)) = msg_stream.next().await {
+            seeding_list
+                .handle_interaction_component(ctx, &component)
+                .await;
+            seeding_list.update();
+
+            msg_builder = msg_builder.embed(
+                seeding_list
+                    .embed
+                    .as_ref()
+                    .expect("embed should be present")
+                    .clone(),
+            );
+
+            cmd.update(ctx, &msg_builder).await?;
+        }
+
+        // Clearing components
+        msg_builder.clear_components();
+        cmd.update(ctx, &msg_builder).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use osu_api::models::{
+        osu_matches::{ScoringKind, TeamKind},
+        OsuMods, OsuScoreMatchTeam,
+    };
+
+    use super::*;
+    use crate::commands::multiplayer::summary::MatchScore;
+
+    fn game(beatmap_id: i64, scores: &[(i64, i64)]) -> MatchGame {
+        MatchGame {
+            beatmap_id,
+            mods: OsuMods::NOMOD,
+            scoring_kind: ScoringKind::ScoreV2,
+            team_kind: TeamKind::HeadToHead,
+            scores: scores
+                .iter()
+                .map(|&(user_id, score)| MatchScore {
+                    user_id,
+                    score,
+                    accuracy: 1.0,
+                    max_combo: 100,
+                    team: OsuScoreMatchTeam::None,
+                    pass: true,
+                })
+                .collect(),
+        }
+    }
+
+    fn qualifier() -> QualifierScores {
+        let mut scores = QualifierScores::default();
+
+        // Player 3 missed the second map, player 1 replayed the first map
+        scores.add_games(&[
+            game(1, &[(1, 500_000), (2, 700_000), (3, 900_000)]),
+            game(2, &[(1, 800_000), (2, 600_000)]),
+            game(1, &[(1, 750_000)]),
+        ]);
+
+        scores
+    }
+
+    fn seeded(scores: &QualifierScores, method: SeedingMethod) -> Vec<i64> {
+        scores.seeds(method).into_iter().map(|x| x.0).collect()
+    }
+
+    #[test]
+    fn test_best_scores() {
+        let scores = qualifier();
+
+        assert_eq!(scores.beatmaps, vec![1, 2]);
+        assert_eq!(scores.score(1, 1), Some(750_000));
+        assert_eq!(scores.rank(1, 1), Some(2));
+        assert_eq!(scores.rank(3, 2), None);
+    }
+
+    #[test]
+    fn test_sum() {
+        let scores = qualifier();
+
+        assert_eq!(seeded(&scores, SeedingMethod::Sum), vec![1, 2, 3]);
+        assert_eq!(scores.seeds(SeedingMethod::Sum)[0].1, 1_550_000.0);
+    }
+
+    #[test]
+    fn test_average_rank() {
+        let scores = qualifier();
+
+        // 1: (2 + 1) / 2, 2: (3 + 2) / 2, 3: (1 + 3) / 2
+        assert_eq!(seeded(&scores, SeedingMethod::AverageRank), vec![1, 3, 2]);
+    }
+
+    #[test]
+    fn test_z_sum_and_percentile() {
+        let scores = qualifier();
+
+        assert_eq!(seeded(&scores, SeedingMethod::ZSum), vec![1, 2, 3]);
+        assert_eq!(seeded(&scores, SeedingMethod::Percentile), vec![1, 3, 2]);
+
+        let percentile = scores.seeds(SeedingMethod::Percentile);
+        assert!(
+            (percentile[0].1 - (2.0 / 3.0 + 1.0) / 2.0 * 100.0).abs() < 1e-9
+        );
+    }
+}
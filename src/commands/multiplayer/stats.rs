@@ -0,0 +1,561 @@
+use std::{collections::HashMap, fmt::Write, time::Duration};
+
+use num_format::{Locale, ToFormattedString};
+
+use eyre::Result;
+use fumo_database::osu::{
+    career::{OsuCareerMods, OsuCareerPlayer, OsuCareerTotals, OsuCareerYear},
+    OsuDbMatch,
+};
+use fumo_macro::listing;
+use fumo_twilight::message::MessageBuilder;
+use osu_api::models::{OsuMods, OsuUserExtended, UserId};
+use tokio_stream::StreamExt;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+    application::interaction::{Interaction, InteractionData},
+    channel::message::MessageFlags,
+};
+use twilight_util::builder::embed::{
+    EmbedBuilder, EmbedFieldBuilder, EmbedFooterBuilder, ImageSource,
+};
+
+use crate::{
+    components::listing::ListingTrait,
+    fumo_context::FumoContext,
+    utils::{
+        interaction::{InteractionCommand, InteractionComponent},
+        static_components::pages_components,
+    },
+};
+
+use super::{
+    summary::{group_db_games, MatchSummary, PlayerSummary},
+    tournament_acronym, ListKind,
+};
+
+/// Amount of entries in the top maps, mods and players fields
+const CAREER_TOP_LIMIT: i64 = 5;
+
+/// Aggregated stats over all matches of the player
+pub struct CareerStats {
+    totals: OsuCareerTotals,
+    /// Beatmap id, `{Artist} - {Title} [{Version}]` and maps played
+    beatmaps: Vec<(i64, String, i64)>,
+    mods: Vec<OsuCareerMods>,
+    teammates: Vec<OsuCareerPlayer>,
+    opponents: Vec<OsuCareerPlayer>,
+    years: Vec<OsuCareerYear>,
+}
+
+impl CareerStats {
+    async fn load(
+        ctx: &FumoContext,
+        user_id: i64,
+        is_tournament: bool,
+        tournament: Option<&str>,
+    ) -> Result<Self> {
+        let (totals, beatmaps, mods, teammates, opponents, years) = tokio::try_join!(
+            ctx.db
+                .select_career_totals(user_id, is_tournament, tournament),
+            ctx.db.select_career_beatmaps(
+                user_id,
+                is_tournament,
+                tournament,
+                CAREER_TOP_LIMIT
+            ),
+            ctx.db.select_career_mods(
+                user_id,
+                is_tournament,
+                tournament,
+                CAREER_TOP_LIMIT
+            ),
+            ctx.db.select_career_players(
+                user_id,
+                is_tournament,
+                tournament,
+                true,
+                CAREER_TOP_LIMIT
+            ),
+            ctx.db.select_career_players(
+                user_id,
+                is_tournament,
+                tournament,
+                false,
+                CAREER_TOP_LIMIT
+            ),
+            ctx.db
+                .select_career_years(user_id, is_tournament, tournament),
+        )?;
+
+        let beatmap_ids: Vec<i64> =
+            beatmaps.iter().map(|x| x.beatmap_id).collect();
+
+        let names: HashMap<i64, String> = if beatmap_ids.is_empty() {
+            HashMap::new()
+        } else {
+            ctx.osu_api
+                .get_beatmaps(&beatmap_ids)
+                .await?
+                .into_iter()
+                .map(|x| (x.id as i64, x.metadata()))
+                .collect()
+        };
+
+        let beatmaps = beatmaps
+            .into_iter()
+            .map(|x| {
+                let name = names
+                    .get(&x.beatmap_id)
+                    .cloned()
+                    .unwrap_or_else(|| format!("Beatmap {}", x.beatmap_id));
+
+                (x.beatmap_id, name, x.maps_played)
+            })
+            .collect();
+
+        let mut career = Self {
+            totals,
+            beatmaps,
+            mods,
+            teammates,
+            opponents,
+            years,
+        };
+
+        career.fill_usernames(ctx).await?;
+
+        Ok(career)
+    }
+
+    /// Looks up players that don't have a cached username
+    async fn fill_usernames(&mut self, ctx: &FumoContext) -> Result<()> {
+        let missing: Vec<UserId> = self
+            .teammates
+            .iter()
+            .chain(&self.opponents)
+            .filter(|x| x.osu_username.is_none())
+            .map(|x| UserId::Id(x.user_id))
+            .collect();
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let users = ctx.osu_api.lookup_users(&missing).await?.users;
+
+        for user in &users {
+            ctx.db.insert_username(user.id, &user.username).await?;
+        }
+
+        for player in self.teammates.iter_mut().chain(&mut self.opponents) {
+            if player.osu_username.is_none() {
+                player.osu_username = users
+                    .iter()
+                    .find(|x| x.id == player.user_id)
+                    .map(|x| x.username.clone());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn totals_field(&self) -> String {
+        let totals = &self.totals;
+
+        let win_rate = |wins: i64, maps: i64| {
+            if maps == 0 {
+                0.0
+            } else {
+                wins as f64 / maps as f64 * 100.0
+            }
+        };
+
+        format!(
+            "Matches: **{}** • Maps: **{}**\n\
+            Team wins: **{}/{}** ({:.2}%)\n\
+            Head to head wins: **{}/{}** ({:.2}%)\n\
+            Average score: **{}** • Accuracy: **{:.2}%**",
+            totals.matches,
+            totals.maps_played,
+            totals.team_wins,
+            totals.team_maps,
+            win_rate(totals.team_wins, totals.team_maps),
+            totals.head_to_head_wins,
+            totals.head_to_head_maps,
+            win_rate(totals.head_to_head_wins, totals.head_to_head_maps),
+            (totals.average_score.round() as i64)
+                .to_formatted_string(&Locale::en),
+            totals.average_accuracy * 100.0
+        )
+    }
+
+    fn beatmaps_field(&self) -> String {
+        let mut field = String::with_capacity(300);
+
+        for (beatmap_id, name, maps_played) in &self.beatmaps {
+            let _ = writeln!(
+                field,
+                "[{name}](https://osu.ppy.sh/b/{beatmap_id}) • {maps_played}x"
+            );
+        }
+
+        field
+    }
+
+    fn mods_field(&self) -> String {
+        self.mods
+            .iter()
+            .map(|x| {
+                format!(
+                    "+{} • {}x",
+                    OsuMods::from_bits_truncate(x.mods as u32),
+                    x.maps_played
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn players_field(players: &[OsuCareerPlayer]) -> String {
+        players
+            .iter()
+            .map(|x| {
+                format!(
+                    "[{}](https://osu.ppy.sh/users/{}) • {} maps",
+                    x.osu_username.as_deref().unwrap_or("Unknown"),
+                    x.user_id,
+                    x.maps_played
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn years_field(&self) -> String {
+        self.years
+            .iter()
+            .map(|x| {
+                format!(
+                    "{}: {} matches • {} maps",
+                    x.year, x.matches, x.maps_played
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn fields(&self) -> Vec<EmbedFieldBuilder> {
+        let fields = [
+            ("Career", self.totals_field(), false),
+            ("Most played maps", self.beatmaps_field(), false),
+            ("Favourite mods", self.mods_field(), true),
+            ("Activity", self.years_field(), true),
+            ("Teammates", Self::players_field(&self.teammates), true),
+            ("Opponents", Self::players_field(&self.opponents), true),
+        ];
+
+        fields
+            .into_iter()
+            .filter(|(_, value, _)| !value.is_empty())
+            .map(|(name, value, inline)| {
+                let field = EmbedFieldBuilder::new(name, value);
+
+                if inline {
+                    field.inline()
+                } else {
+                    field
+                }
+            })
+            .collect()
+    }
+}
+
+/// Performance of the player in a single match
+pub struct MatchPerformance {
+    osu_match: OsuDbMatch,
+    player: PlayerSummary,
+}
+
+#[listing]
+pub struct MatchStatsListing {
+    performances: Vec<MatchPerformance>,
+    career: CareerStats,
+    osu_user: OsuUserExtended,
+}
+
+impl MatchStatsListing {
+    fn overview(&self) -> String {
+        let matches = self.performances.len() as f64;
+
+        let maps_played: usize =
+            self.performances.iter().map(|x| x.player.maps_played).sum();
+        let maps_won: usize =
+            self.performances.iter().map(|x| x.player.maps_won).sum();
+
+        let average_cost = self
+            .performances
+            .iter()
+            .map(|x| x.player.match_cost)
+            .sum::<f64>()
+            / matches;
+
+        let average_accuracy = self
+            .performances
+            .iter()
+            .map(|x| x.player.average_accuracy * x.player.maps_played as f64)
+            .sum::<f64>()
+            / maps_played.max(1) as f64;
+
+        let mut overview = String::with_capacity(200);
+
+        let _ = writeln!(overview, "**Average match cost: {average_cost:.2}**");
+
+        if let Some(best) = self
+            .performances
+            .iter()
+            .max_by(|a, b| a.player.match_cost.total_cmp(&b.player.match_cost))
+        {
+            let _ = writeln!(
+                overview,
+                "Best: {:.2} in [{}](https://osu.ppy.sh/community/matches/{})",
+                best.player.match_cost, best.osu_match.name, best.osu_match.id
+            );
+        }
+
+        let _ = writeln!(
+            overview,
+            "Maps won: {}/{} ({:.2}%) • Accuracy: {:.2}%",
+            maps_won,
+            maps_played,
+            maps_won as f64 / maps_played.max(1) as f64 * 100.0,
+            average_accuracy * 100.0
+        );
+
+        overview
+    }
+}
+
+impl ListingTrait for MatchStatsListing {
+    async fn handle_interaction_component(
+        &mut self,
+        ctx: &FumoContext,
+        component: &InteractionComponent,
+    ) {
+        let _ = component.defer(ctx).await;
+
+        if let Some(data) = &component.data {
+            match data.custom_id.as_ref() {
+                "B1" => self.previous_page(),
+                "B2" => self.next_page(),
+                _ => {}
+            }
+        }
+    }
+
+    fn update(&mut self) {
+        let footer = EmbedFooterBuilder::new(format!(
+            "Matches: {} • Page {}/{}",
+            self.performances.len(),
+            self.current_page,
+            self.max_pages,
+        ));
+
+        let mut description = self.overview();
+        description.push('\n');
+
+        let start_at = (self.current_page - 1) * self.entries_per_page;
+
+        let performances = self
+            .performances
+            .iter()
+            .skip(start_at)
+            .take(self.entries_per_page);
+
+        for performance in performances {
+            let _ = writeln!(
+                description,
+                "- **[{}](https://osu.ppy.sh/community/matches/{})**",
+                performance.osu_match.name, performance.osu_match.id
+            );
+            let _ = writeln!(
+                description,
+                "MC {:.2} • {}/{} won • {:.2}% • <t:{}:R>",
+                performance.player.match_cost,
+                performance.player.maps_won,
+                performance.player.maps_played,
+                performance.player.average_accuracy * 100.0,
+                performance.osu_match.start_time.and_utc().timestamp()
+            );
+        }
+
+        let mut embed = EmbedBuilder::new()
+            .color(123432)
+            .title(format!("Multiplayer stats for {}", &self.osu_user.username))
+            .thumbnail(
+                ImageSource::url(&self.osu_user.avatar_url)
+                    .expect("avatar url should be valid"),
+            )
+            .description(description)
+            .footer(footer);
+
+        for field in self.career.fields() {
+            embed = embed.field(field);
+        }
+
+        self.embed = Some(embed.build());
+    }
+}
+
+/// Multiplayer career and match costs of the player
+#[derive(CommandModel, CreateCommand, Debug)]
+#[command(name = "stats")]
+pub struct MultiplayerStats {
+    /// osu! user id or username
+    user: Option<String>,
+
+    /// All matches or only tournament related, tournament by default
+    kind: Option<ListKind>,
+
+    /// Tournament acronym e.g. OWC2023, implies tournament matches
+    tournament: Option<String>,
+}
+
+impl MultiplayerStats {
+    pub async fn run(
+        &self,
+        ctx: &FumoContext,
+        cmd: InteractionCommand,
+    ) -> Result<()> {
+        let osu_user_id = match &self.user {
+            Some(value) => UserId::from(value.as_ref()),
+            None => {
+                let Some(osu_user) = osu_user!(ctx, cmd) else {
+                    let msg = MessageBuilder::new()
+                        .flags(MessageFlags::EPHEMERAL)
+                        .content("No linked account found!");
+                    cmd.response(ctx, &msg).await?;
+                    return Ok(());
+                };
+
+                UserId::Id(osu_user.osu_id)
+            }
+        };
+
+        cmd.defer(ctx).await?;
+
+        let Some(osu_user) = ctx.osu_api.get_user(osu_user_id, None).await?
+        else {
+            let msg = MessageBuilder::new()
+                .content("Are you restricted? Can't find user id on osu!");
+            cmd.update(ctx, &msg).await?;
+            return Ok(());
+        };
+
+        let tournament = tournament_acronym(&self.tournament);
+        let is_tournament = tournament.is_some()
+            || self.kind.as_ref().is_none_or(|x| x.is_tournament());
+
+        let matches = if is_tournament {
+            ctx.db
+                .get_user_matches_tourney(osu_user.id, tournament.as_deref())
+                .await?
+        } else {
+            ctx.db.get_user_matches_all(osu_user.id).await?
+        };
+
+        let match_ids: Vec<i64> = matches.iter().map(|x| x.id).collect();
+
+        let db_games = ctx.db.select_match_games(&match_ids).await?;
+        let db_scores = ctx.db.select_match_scores(&match_ids).await?;
+
+        let mut games = group_db_games(&db_games, &db_scores);
+
+        let performances: Vec<MatchPerformance> = matches
+            .into_iter()
+            .filter_map(|osu_match| {
+                let summary = MatchSummary {
+                    id: osu_match.id,
+                    name: osu_match.name.clone(),
+                    start_time: osu_match.start_time,
+                    end_time: Some(osu_match.end_time),
+                    games: games.remove(&osu_match.id)?,
+                    teams: osu_match
+                        .red_team
+                        .clone()
+                        .zip(osu_match.blue_team.clone()),
+                    beatmaps: HashMap::new(),
+                    usernames: HashMap::new(),
+                };
+
+                let player = summary
+                    .players()
+                    .into_iter()
+                    .find(|x| x.user_id == osu_user.id)?;
+
+                Some(MatchPerformance { osu_match, player })
+            })
+            .collect();
+
+        if performances.is_empty() {
+            let msg = MessageBuilder::new()
+                .content("No matches found for this player!");
+            cmd.update(ctx, &msg).await?;
+            return Ok(());
+        }
+
+        let career = CareerStats::load(
+            ctx,
+            osu_user.id,
+            is_tournament,
+            tournament.as_deref(),
+        )
+        .await?;
+
+        let performances_len = performances.len();
+
+        let mut stats_list =
+            MatchStatsListing::new(performances, career, osu_user)
+                .calculate_pages(performances_len, 5);
+
+        stats_list.update();
+
+        let mut msg_builder = MessageBuilder::new()
+            .embed(
+                stats_list
+                    .embed
+                    .as_ref()
+                    .expect("embed should be present")
+                    .clone(),
+            )
+            .components(pages_components());
+
+        let msg = cmd.update(ctx, &msg_builder).await?.model().await?;
+        let msg_stream = component_stream!(ctx, msg);
+
+        tokio::pin!(msg_stream);
+
+        while let Some(Ok(component)) = msg_stream.next().await {
+            stats_list
+                .handle_interaction_component(ctx, &component)
+                .await;
+            stats_list.update();
+
+            msg_builder = msg_builder.embed(
+                stats_list
+                    .embed
+                    .as_ref()
+                    .expect("embed should be present")
+                    .clone(),
+            );
+
+            cmd.update(ctx, &msg_builder).await?;
+        }
+
+        // Clearing components
+        msg_builder.clear_components();
+        cmd.update(ctx, &msg_builder).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,780 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    time::Duration,
+};
+
+use chrono::NaiveDateTime;
+use eyre::Result;
+use fumo_database::osu::{
+    tournament::OsuTournamentMatch, OsuDbMatchGame, OsuDbMatchScore,
+};
+use fumo_macro::listing;
+use fumo_twilight::message::MessageBuilder;
+use num_format::{Locale, ToFormattedString};
+use osu_api::{
+    error::OsuApiError,
+    models::{
+        osu_matches::{OsuMatchGame, ScoringKind, TeamKind},
+        OsuMods, OsuScore, OsuScoreMatchTeam, UserId,
+    },
+};
+use tokio_stream::StreamExt;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::application::interaction::{Interaction, InteractionData};
+use twilight_util::builder::embed::{
+    EmbedBuilder, EmbedFieldBuilder, EmbedFooterBuilder,
+};
+
+use super::match_cost::match_costs;
+use crate::{
+    components::listing::ListingTrait,
+    fumo_context::FumoContext,
+    utils::{
+        interaction::{InteractionCommand, InteractionComponent},
+        searching::{find_osu_links, OsuLink},
+        static_components::pages_components,
+    },
+};
+
+/// Max amount of players shown in the averages field
+const MAX_PLAYERS_SHOWN: usize = 16;
+
+pub struct MatchScore {
+    pub user_id: i64,
+    pub score: i64,
+    pub accuracy: f32,
+    pub max_combo: i32,
+    pub team: OsuScoreMatchTeam,
+    pub pass: bool,
+}
+
+impl From<&OsuDbMatchScore> for MatchScore {
+    fn from(score: &OsuDbMatchScore) -> Self {
+        Self {
+            user_id: score.user_id,
+            score: score.score,
+            accuracy: score.accuracy as f32,
+            max_combo: score.max_combo,
+            team: score.team,
+            pass: score.pass,
+        }
+    }
+}
+
+impl From<&OsuScore> for MatchScore {
+    fn from(score: &OsuScore) -> Self {
+        let (team, pass) = score
+            .osu_match
+            .as_ref()
+            .map_or((OsuScoreMatchTeam::None, score.passed), |x| {
+                (x.team, x.pass)
+            });
+
+        Self {
+            user_id: score.user_id,
+            score: score.score,
+            accuracy: score.accuracy,
+            max_combo: score.max_combo.unwrap_or(0),
+            team,
+            pass,
+        }
+    }
+}
+
+pub struct MatchGame {
+    pub beatmap_id: i64,
+    pub mods: OsuMods,
+    pub scoring_kind: ScoringKind,
+    pub team_kind: TeamKind,
+    pub scores: Vec<MatchScore>,
+}
+
+pub enum GameWinner {
+    Team(OsuScoreMatchTeam),
+    Player(i64),
+}
+
+impl MatchGame {
+    fn from_db(game: &OsuDbMatchGame, scores: Vec<MatchScore>) -> Self {
+        Self {
+            beatmap_id: game.beatmap_id,
+            mods: OsuMods::from_bits_truncate(game.mods as u32),
+            scoring_kind: ScoringKind::try_from(game.scoring_kind as u8)
+                .unwrap_or(ScoringKind::Score),
+            team_kind: TeamKind::try_from(game.team_kind as u8)
+                .unwrap_or(TeamKind::HeadToHead),
+            scores,
+        }
+    }
+
+    pub fn from_api(game: &OsuMatchGame) -> Self {
+        Self {
+            beatmap_id: game.beatmap_id,
+            mods: game.mods,
+            scoring_kind: game.scoring_kind,
+            team_kind: game.team_kind,
+            scores: game.scores.iter().map(MatchScore::from).collect(),
+        }
+    }
+
+    /// Value the game winner is decided by
+    pub fn score_value(&self, score: &MatchScore) -> f64 {
+        match self.scoring_kind {
+            ScoringKind::Score | ScoringKind::ScoreV2 => score.score as f64,
+            ScoringKind::Accuracy => score.accuracy as f64,
+            ScoringKind::Combo => score.max_combo as f64,
+        }
+    }
+
+    /// Formats score value or team total of the game
+    pub fn format_value(&self, value: f64) -> String {
+        match self.scoring_kind {
+            ScoringKind::Score | ScoringKind::ScoreV2 => {
+                (value.round() as i64).to_formatted_string(&Locale::en)
+            }
+            ScoringKind::Accuracy => format!("{:.2}%", value * 100.0),
+            ScoringKind::Combo => {
+                format!(
+                    "x{}",
+                    (value.round() as i64).to_formatted_string(&Locale::en)
+                )
+            }
+        }
+    }
+
+    /// Values of the passed scores of red and blue teams, summed
+    /// up except for accuracy which is averaged over the team
+    pub fn team_totals(&self) -> (f64, f64) {
+        let (red, blue) = self.scores.iter().filter(|x| x.pass).fold(
+            ((0.0, 0), (0.0, 0)),
+            |(red, blue), score| {
+                let value = self.score_value(score);
+
+                match score.team {
+                    OsuScoreMatchTeam::Red => {
+                        ((red.0 + value, red.1 + 1), blue)
+                    }
+                    OsuScoreMatchTeam::Blue => {
+                        (red, (blue.0 + value, blue.1 + 1))
+                    }
+                    OsuScoreMatchTeam::None => (red, blue),
+                }
+            },
+        );
+
+        match self.scoring_kind {
+            ScoringKind::Accuracy => {
+                (red.0 / red.1.max(1) as f64, blue.0 / blue.1.max(1) as f64)
+            }
+            _ => (red.0, blue.0),
+        }
+    }
+
+    /// `None` if the game ended in a draw or nobody passed
+    pub fn winner(&self) -> Option<GameWinner> {
+        if self.team_kind.is_team() {
+            let (red, blue) = self.team_totals();
+
+            return match red.total_cmp(&blue) {
+                std::cmp::Ordering::Greater => {
+                    Some(GameWinner::Team(OsuScoreMatchTeam::Red))
+                }
+                std::cmp::Ordering::Less => {
+                    Some(GameWinner::Team(OsuScoreMatchTeam::Blue))
+                }
+                std::cmp::Ordering::Equal => None,
+            };
+        }
+
+        self.scores
+            .iter()
+            .filter(|x| x.pass)
+            .max_by(|a, b| self.score_value(a).total_cmp(&self.score_value(b)))
+            .map(|x| GameWinner::Player(x.user_id))
+    }
+}
+
+/// Groups stored games by matches, games without scores are skipped
+pub fn group_db_games(
+    games: &[OsuDbMatchGame],
+    scores: &[OsuDbMatchScore],
+) -> HashMap<i64, Vec<MatchGame>> {
+    let mut game_scores: HashMap<i64, Vec<MatchScore>> = HashMap::new();
+
+    for score in scores {
+        game_scores
+            .entry(score.game_id)
+            .or_default()
+            .push(MatchScore::from(score));
+    }
+
+    let mut matches: HashMap<i64, Vec<MatchGame>> = HashMap::new();
+
+    for game in games {
+        let Some(scores) = game_scores.remove(&game.id) else {
+            continue;
+        };
+
+        matches
+            .entry(game.match_id)
+            .or_default()
+            .push(MatchGame::from_db(game, scores));
+    }
+
+    matches
+}
+
+/// Adds usernames of the players that are not known yet,
+/// ones missing in the database are fetched from osu!api
+pub async fn fill_usernames(
+    ctx: &FumoContext,
+    usernames: &mut HashMap<i64, String>,
+    user_ids: HashSet<i64>,
+) -> Result<()> {
+    let user_ids: Vec<i64> = user_ids
+        .into_iter()
+        .filter(|x| !usernames.contains_key(x))
+        .collect();
+
+    if user_ids.is_empty() {
+        return Ok(());
+    }
+
+    usernames.extend(ctx.db.get_usernames_batch(&user_ids).await?);
+
+    let missing: Vec<UserId> = user_ids
+        .into_iter()
+        .filter(|x| !usernames.contains_key(x))
+        .map(UserId::Id)
+        .collect();
+
+    if !missing.is_empty() {
+        let users = ctx.osu_api.lookup_users(&missing).await?.users;
+
+        for user in users {
+            ctx.db.insert_username(user.id, &user.username).await?;
+            usernames.insert(user.id, user.username);
+        }
+    }
+
+    Ok(())
+}
+
+/// Team names from the tournament match name
+/// e.g. `OWC: (Team A) vs (Team B)`
+pub fn parse_teams(name: &str) -> Option<(String, String)> {
+    OsuTournamentMatch::parse(name).map(|x| (x.red_team, x.blue_team))
+}
+
+/// Averages of the player over the whole match
+pub struct PlayerSummary {
+    pub user_id: i64,
+    pub team: OsuScoreMatchTeam,
+    pub maps_played: usize,
+    pub maps_won: usize,
+    pub average_score: f64,
+    pub average_accuracy: f64,
+    pub match_cost: f64,
+}
+
+pub struct MatchSummary {
+    pub id: i64,
+    pub name: String,
+    pub start_time: NaiveDateTime,
+    pub end_time: Option<NaiveDateTime>,
+    pub games: Vec<MatchGame>,
+    /// Red and blue team names of the tournament match
+    pub teams: Option<(String, String)>,
+    /// Beatmap id to `{Artist} - {Title} [{Version}]`
+    pub beatmaps: HashMap<i64, String>,
+    pub usernames: HashMap<i64, String>,
+}
+
+impl MatchSummary {
+    /// Stored match is taken from the database, other
+    /// matches are fetched from osu!api. `None` if match doesn't exist
+    pub async fn load(
+        ctx: &FumoContext,
+        match_id: i64,
+    ) -> Result<Option<Self>> {
+        match Self::load_games(ctx, match_id).await? {
+            Some(summary) => Ok(Some(summary.fill_missing(ctx).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Same as `load` but missing beatmaps and usernames are not fetched
+    pub async fn load_games(
+        ctx: &FumoContext,
+        match_id: i64,
+    ) -> Result<Option<Self>> {
+        let summary = match ctx.db.select_osu_match(match_id).await? {
+            Some(osu_match) => {
+                let db_games = ctx.db.select_match_games(&[match_id]).await?;
+                let db_scores = ctx.db.select_match_scores(&[match_id]).await?;
+
+                let games = group_db_games(&db_games, &db_scores)
+                    .remove(&match_id)
+                    .unwrap_or_default();
+
+                let usernames = db_scores
+                    .into_iter()
+                    .filter_map(|x| Some((x.user_id, x.osu_username?)))
+                    .collect();
+
+                Self {
+                    id: osu_match.id,
+                    name: osu_match.name,
+                    start_time: osu_match.start_time,
+                    end_time: Some(osu_match.end_time),
+                    games,
+                    teams: osu_match.red_team.zip(osu_match.blue_team),
+                    beatmaps: HashMap::new(),
+                    usernames,
+                }
+            }
+            None => {
+                let osu_match =
+                    match ctx.osu_api.get_match_all_events(match_id).await {
+                        Ok(osu_match) => osu_match,
+                        Err(OsuApiError::NotFound { .. }) => return Ok(None),
+                        Err(e) => return Err(e.into()),
+                    };
+
+                let mut beatmaps = HashMap::new();
+                let mut games = Vec::new();
+
+                for game in
+                    osu_match.events.iter().filter_map(|x| x.game.as_ref())
+                {
+                    // Aborted games don't have any scores
+                    if game.scores.is_empty() {
+                        continue;
+                    }
+
+                    if let Some(beatmap) = &game.beatmap {
+                        beatmaps.insert(game.beatmap_id, beatmap.metadata());
+                    }
+
+                    games.push(MatchGame::from_api(game));
+                }
+
+                Self {
+                    id: osu_match.osu_match.id,
+                    teams: parse_teams(&osu_match.osu_match.name),
+                    name: osu_match.osu_match.name,
+                    start_time: osu_match.osu_match.start_time.naive_utc(),
+                    end_time: osu_match
+                        .osu_match
+                        .end_time
+                        .map(|x| x.naive_utc()),
+                    games,
+                    beatmaps,
+                    usernames: HashMap::new(),
+                }
+            }
+        };
+
+        Ok(Some(summary))
+    }
+
+    /// Fetches beatmaps and usernames that are not known yet
+    pub async fn fill_missing(mut self, ctx: &FumoContext) -> Result<Self> {
+        let beatmap_ids: Vec<i64> = self
+            .games
+            .iter()
+            .map(|x| x.beatmap_id)
+            .filter(|x| !self.beatmaps.contains_key(x))
+            .collect::<HashSet<i64>>()
+            .into_iter()
+            .collect();
+
+        if !beatmap_ids.is_empty() {
+            for beatmap in ctx.osu_api.get_beatmaps(&beatmap_ids).await? {
+                self.beatmaps.insert(beatmap.id as i64, beatmap.metadata());
+            }
+        }
+
+        let user_ids = self
+            .games
+            .iter()
+            .flat_map(|x| x.scores.iter().map(|x| x.user_id))
+            .collect();
+
+        fill_usernames(ctx, &mut self.usernames, user_ids).await?;
+
+        Ok(self)
+    }
+
+    pub fn username(&self, user_id: i64) -> &str {
+        self.usernames
+            .get(&user_id)
+            .map_or("Unknown", |x| x.as_str())
+    }
+
+    /// Whether majority of the games were played in teams
+    pub fn is_team(&self) -> bool {
+        let team_games =
+            self.games.iter().filter(|x| x.team_kind.is_team()).count();
+
+        team_games * 2 > self.games.len()
+    }
+
+    /// Red and blue team names of the tournament match,
+    /// other matches are using team colors
+    pub fn team_names(&self) -> (String, String) {
+        self.teams
+            .clone()
+            .unwrap_or_else(|| (String::from("Red"), String::from("Blue")))
+    }
+
+    /// Maps won by red and blue teams in the first `games` games
+    pub fn team_wins(&self, games: usize) -> (usize, usize) {
+        self.games
+            .iter()
+            .take(games)
+            .fold((0, 0), |(red, blue), game| match game.winner() {
+                Some(GameWinner::Team(OsuScoreMatchTeam::Red)) => {
+                    (red + 1, blue)
+                }
+                Some(GameWinner::Team(OsuScoreMatchTeam::Blue)) => {
+                    (red, blue + 1)
+                }
+                _ => (red, blue),
+            })
+    }
+
+    /// Players sorted by their match cost
+    pub fn players(&self) -> Vec<PlayerSummary> {
+        let match_costs = match_costs(&self.games);
+        let mut players: HashMap<i64, PlayerSummary> = HashMap::new();
+
+        for game in &self.games {
+            let winner = game.winner();
+
+            for score in &game.scores {
+                let player =
+                    players.entry(score.user_id).or_insert(PlayerSummary {
+                        user_id: score.user_id,
+                        team: score.team,
+                        maps_played: 0,
+                        maps_won: 0,
+                        average_score: 0.0,
+                        average_accuracy: 0.0,
+                        match_cost: match_costs
+                            .get(&score.user_id)
+                            .copied()
+                            .unwrap_or(0.0),
+                    });
+
+                let won = match winner {
+                    Some(GameWinner::Team(team)) => {
+                        team as u8 == score.team as u8
+                    }
+                    Some(GameWinner::Player(user_id)) => {
+                        user_id == score.user_id
+                    }
+                    None => false,
+                };
+
+                player.team = score.team;
+                player.maps_played += 1;
+                player.maps_won += won as usize;
+                player.average_score += score.score as f64;
+                player.average_accuracy += score.accuracy as f64;
+            }
+        }
+
+        let mut players: Vec<PlayerSummary> = players
+            .into_values()
+            .map(|mut x| {
+                x.average_score /= x.maps_played as f64;
+                x.average_accuracy /= x.maps_played as f64;
+                x
+            })
+            .collect();
+
+        players.sort_by(|a, b| b.match_cost.total_cmp(&a.match_cost));
+
+        players
+    }
+}
+
+pub fn team_emoji(team: OsuScoreMatchTeam) -> &'static str {
+    match team {
+        OsuScoreMatchTeam::Red => "🔴",
+        OsuScoreMatchTeam::Blue => "🔵",
+        OsuScoreMatchTeam::None => "⚪",
+    }
+}
+
+/// Match id or link to the match
+pub fn parse_match_id(text: &str) -> Option<i64> {
+    let text = text.trim();
+
+    if let Ok(match_id) = text.parse() {
+        return Some(match_id);
+    }
+
+    find_osu_links(text).find_map(|link| match link {
+        OsuLink::Match(match_id) => Some(match_id),
+        _ => None,
+    })
+}
+
+#[listing]
+pub struct MatchListing {
+    summary: MatchSummary,
+    players: Vec<PlayerSummary>,
+}
+
+impl ListingTrait for MatchListing {
+    async fn handle_interaction_component(
+        &mut self,
+        ctx: &FumoContext,
+        component: &InteractionComponent,
+    ) {
+        let _ = component.defer(ctx).await;
+
+        if let Some(data) = &component.data {
+            match data.custom_id.as_ref() {
+                "B1" => self.previous_page(),
+                "B2" => self.next_page(),
+                _ => {}
+            }
+        }
+    }
+
+    fn update(&mut self) {
+        let summary = &self.summary;
+        let is_team = summary.is_team();
+        let (red_name, blue_name) = summary.team_names();
+
+        let footer = EmbedFooterBuilder::new(format!(
+            "Maps: {} • Page {}/{}",
+            summary.games.len(),
+            self.current_page,
+            self.max_pages
+        ));
+
+        let mut description = String::with_capacity(1000);
+
+        let _ = write!(
+            description,
+            "Started <t:{}:f>",
+            summary.start_time.and_utc().timestamp()
+        );
+
+        if summary.end_time.is_none() {
+            description.push_str(" • In progress");
+        }
+
+        description.push('\n');
+
+        if is_team {
+            let (red, blue) = summary.team_wins(summary.games.len());
+
+            let _ = writeln!(
+                description,
+                "## {} {red_name} {red} – {blue} {blue_name} {}",
+                team_emoji(OsuScoreMatchTeam::Red),
+                team_emoji(OsuScoreMatchTeam::Blue),
+            );
+        }
+
+        let start_at = (self.current_page - 1) * self.entries_per_page;
+
+        let games_iter = summary
+            .games
+            .iter()
+            .enumerate()
+            .skip(start_at)
+            .take(self.entries_per_page);
+
+        for (index, game) in games_iter {
+            let beatmap = summary
+                .beatmaps
+                .get(&game.beatmap_id)
+                .map_or("Unknown beatmap", |x| x.as_str());
+
+            let _ = writeln!(
+                description,
+                "**{}. [{}](https://osu.ppy.sh/b/{})** +{}",
+                index + 1,
+                beatmap,
+                game.beatmap_id,
+                game.mods
+            );
+
+            match game.winner() {
+                Some(GameWinner::Team(team)) => {
+                    let (red, blue) = game.team_totals();
+                    let (red_wins, blue_wins) = summary.team_wins(index + 1);
+
+                    let winner = match team {
+                        OsuScoreMatchTeam::Blue => &blue_name,
+                        _ => &red_name,
+                    };
+
+                    let _ = writeln!(
+                        description,
+                        "{} {} • {} – {} • {}–{}",
+                        team_emoji(team),
+                        winner,
+                        game.format_value(red),
+                        game.format_value(blue),
+                        red_wins,
+                        blue_wins
+                    );
+                }
+                Some(GameWinner::Player(user_id)) => {
+                    let score = game
+                        .scores
+                        .iter()
+                        .find(|x| x.user_id == user_id)
+                        .map_or(0.0, |x| game.score_value(x));
+
+                    let _ = writeln!(
+                        description,
+                        "🏆 {} • {}",
+                        summary.username(user_id),
+                        game.format_value(score)
+                    );
+                }
+                None => {
+                    let _ = writeln!(description, "Draw");
+                }
+            }
+        }
+
+        let mut players = String::with_capacity(500);
+
+        for player in self.players.iter().take(MAX_PLAYERS_SHOWN) {
+            if is_team {
+                let _ = write!(players, "{} ", team_emoji(player.team));
+            }
+
+            let _ = writeln!(
+                players,
+                "**{}** • MC {:.2} • {} • {:.2}% • {}/{} won",
+                summary.username(player.user_id),
+                player.match_cost,
+                (player.average_score.round() as i64)
+                    .to_formatted_string(&Locale::en),
+                player.average_accuracy * 100.0,
+                player.maps_won,
+                player.maps_played
+            );
+        }
+
+        if self.players.len() > MAX_PLAYERS_SHOWN {
+            let _ = writeln!(
+                players,
+                "and {} more",
+                self.players.len() - MAX_PLAYERS_SHOWN
+            );
+        }
+
+        let embed = EmbedBuilder::new()
+            .color(123432)
+            .title(&summary.name)
+            .url(format!(
+                "https://osu.ppy.sh/community/matches/{}",
+                summary.id
+            ))
+            .description(description)
+            .field(EmbedFieldBuilder::new("Player averages", players))
+            .footer(footer)
+            .build();
+
+        self.embed = Some(embed);
+    }
+}
+
+/// Show summary of the multiplayer match
+#[derive(CommandModel, CreateCommand, Debug)]
+#[command(name = "match")]
+pub struct MultiplayerMatch {
+    /// Match ID or link to the match
+    #[command(min_length = 1, max_length = 256)]
+    pub osu_match: String,
+}
+
+impl MultiplayerMatch {
+    pub async fn run(
+        &self,
+        ctx: &FumoContext,
+        cmd: InteractionCommand,
+    ) -> Result<()> {
+        cmd.defer(ctx).await?;
+
+        let Some(match_id) = parse_match_id(&self.osu_match) else {
+            let builder = MessageBuilder::new()
+                .content("Please provide valid match ID or link");
+            cmd.update(ctx, &builder).await?;
+            return Ok(());
+        };
+
+        let summary = match MatchSummary::load(ctx, match_id).await? {
+            Some(summary) if !summary.games.is_empty() => summary,
+            Some(_) => {
+                let builder = MessageBuilder::new()
+                    .content("No maps were played in this match!");
+                cmd.update(ctx, &builder).await?;
+                return Ok(());
+            }
+            None => {
+                let builder = MessageBuilder::new().content("Match not found!");
+                cmd.update(ctx, &builder).await?;
+                return Ok(());
+            }
+        };
+
+        let players = summary.players();
+        let games_len = summary.games.len();
+
+        let mut match_list =
+            MatchListing::new(summary, players).calculate_pages(games_len, 8);
+
+        match_list.update();
+
+        let mut msg_builder = MessageBuilder::new()
+            .embed(
+                match_list
+                    .embed
+                    .as_ref()
+                    .expect("embed should be present")
+                    .clone(),
+            )
+            .components(pages_components());
+
+        let msg = cmd.update(ctx, &msg_builder).await?.model().await?;
+        let msg_stream = component_stream!(ctx, msg);
+
+        tokio::pin!(msg_stream);
+
+        while let Some(Ok(component)) = msg_stream.next().await {
+            match_list
+                .handle_interaction_component(ctx, &component)
+                .await;
+            match_list.update();
+
+            msg_builder = msg_builder.embed(
+                match_list
+                    .embed
+                    .as_ref()
+                    .expect("embed should be present")
+                    .clone(),
+            );
+
+            cmd.update(ctx, &msg_builder).await?;
+        }
+
+        // Clearing components
+        msg_builder.clear_components();
+        cmd.update(ctx, &msg_builder).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,341 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    time::Duration,
+};
+
+use eyre::Result;
+use fumo_database::osu::tournament::{
+    normalize_acronym, OsuTournamentBeatmap, OsuTournamentStage,
+};
+use fumo_macro::listing;
+use fumo_twilight::message::MessageBuilder;
+use num_format::{Locale, ToFormattedString};
+use osu_api::models::{OsuMods, UserId};
+use tokio_stream::StreamExt;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+    application::interaction::{Interaction, InteractionData},
+    http::attachment::Attachment,
+};
+use twilight_util::builder::embed::{
+    EmbedBuilder, EmbedFieldBuilder, EmbedFooterBuilder,
+};
+
+use crate::{
+    components::listing::ListingTrait,
+    fumo_context::FumoContext,
+    utils::{
+        interaction::{InteractionCommand, InteractionComponent},
+        static_components::pages_components,
+    },
+};
+
+/// Embed field value limit is 1024 characters
+const TIMELINE_MAX_LENGTH: usize = 1000;
+
+fn mods_label(mods: i64) -> String {
+    OsuMods::from_bits_truncate(mods as u32).to_string()
+}
+
+/// Quotes the csv value if it contains separators or quotes
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+#[listing]
+pub struct TournamentListing {
+    acronym: String,
+    beatmaps: Vec<OsuTournamentBeatmap>,
+    stages: Vec<OsuTournamentStage>,
+    /// Beatmap id to `{Artist} - {Title} [{Version}]`
+    names: HashMap<i64, String>,
+}
+
+impl TournamentListing {
+    fn beatmap_name(&self, beatmap_id: i64) -> String {
+        self.names
+            .get(&beatmap_id)
+            .cloned()
+            .unwrap_or_else(|| format!("Beatmap {beatmap_id}"))
+    }
+
+    fn top_player(beatmap: &OsuTournamentBeatmap) -> String {
+        match (&beatmap.top_username, beatmap.top_user_id) {
+            (Some(username), _) => username.clone(),
+            (None, Some(user_id)) => user_id.to_string(),
+            (None, None) => String::from("-"),
+        }
+    }
+
+    fn timeline_field(&self) -> String {
+        let mut timeline = String::with_capacity(TIMELINE_MAX_LENGTH);
+
+        for stage in &self.stages {
+            let line = format!(
+                "`{}` • Matches: **{}** • Maps: **{}** • Pool: **{}**\n",
+                stage.week.format("%Y-%m-%d"),
+                stage.matches,
+                stage.games,
+                stage.beatmaps
+            );
+
+            if timeline.len() + line.len() > TIMELINE_MAX_LENGTH {
+                timeline.push_str("...");
+                break;
+            }
+
+            timeline.push_str(&line);
+        }
+
+        timeline
+    }
+
+    /// Whole mappool as a csv file
+    fn csv(&self) -> String {
+        let mut csv = String::from(
+            "beatmap_id,beatmap,mods,picks,average_score,top_score,top_player,first_played\n",
+        );
+
+        for beatmap in &self.beatmaps {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{:.0},{},{},{}",
+                beatmap.beatmap_id,
+                csv_field(&self.beatmap_name(beatmap.beatmap_id)),
+                mods_label(beatmap.mods),
+                beatmap.picks,
+                beatmap.average_score,
+                beatmap.top_score.map_or(String::new(), |x| x.to_string()),
+                csv_field(&Self::top_player(beatmap)),
+                beatmap.first_played.format("%Y-%m-%d %H:%M:%S")
+            );
+        }
+
+        csv
+    }
+}
+
+impl ListingTrait for TournamentListing {
+    async fn handle_interaction_component(
+        &mut self,
+        ctx: &FumoContext,
+        component: &InteractionComponent,
+    ) {
+        let _ = component.defer(ctx).await;
+
+        if let Some(data) = &component.data {
+            match data.custom_id.as_ref() {
+                "B1" => self.previous_page(),
+                "B2" => self.next_page(),
+                _ => {}
+            }
+        }
+    }
+
+    fn update(&mut self) {
+        let footer = EmbedFooterBuilder::new(format!(
+            "Page {}/{}",
+            self.current_page, self.max_pages
+        ));
+
+        let matches: i64 = self.stages.iter().map(|x| x.matches).sum();
+        let games: i64 = self.stages.iter().map(|x| x.games).sum();
+
+        let mut description = String::with_capacity(2000);
+
+        let _ = writeln!(
+            description,
+            "Matches: **{matches}** • Maps played: **{games}** • Beatmaps: **{}**\n",
+            self.beatmaps.len()
+        );
+
+        let start_at = (self.current_page - 1) * self.entries_per_page;
+
+        let beatmaps = self
+            .beatmaps
+            .iter()
+            .enumerate()
+            .skip(start_at)
+            .take(self.entries_per_page);
+
+        for (index, beatmap) in beatmaps {
+            let _ = writeln!(
+                description,
+                "**{}.** [{}](https://osu.ppy.sh/b/{}) `{}`",
+                index + 1,
+                self.beatmap_name(beatmap.beatmap_id),
+                beatmap.beatmap_id,
+                mods_label(beatmap.mods)
+            );
+
+            let _ = write!(
+                description,
+                "Picks: **{}** • Avg: **{}**",
+                beatmap.picks,
+                (beatmap.average_score as i64).to_formatted_string(&Locale::en)
+            );
+
+            if let Some(top_score) = beatmap.top_score {
+                let _ = write!(
+                    description,
+                    " • Top: **{}** by **{}**",
+                    top_score.to_formatted_string(&Locale::en),
+                    Self::top_player(beatmap)
+                );
+            }
+
+            let _ = writeln!(
+                description,
+                " • <t:{}:d>",
+                beatmap.first_played.and_utc().timestamp()
+            );
+        }
+
+        let embed = EmbedBuilder::new()
+            .color(123432)
+            .title(format!("{} mappool", self.acronym))
+            .description(description)
+            .field(EmbedFieldBuilder::new("Timeline", self.timeline_field()))
+            .footer(footer)
+            .build();
+
+        self.embed = Some(embed);
+    }
+}
+
+/// Mappool and stages of the tournament derived from the played matches
+#[derive(CommandModel, CreateCommand, Debug)]
+#[command(name = "tournament")]
+pub struct MultiplayerTournament {
+    /// Tournament acronym e.g. OWC2023
+    acronym: String,
+
+    /// Attach the whole mappool as a csv file
+    export: Option<bool>,
+}
+
+impl MultiplayerTournament {
+    pub async fn run(
+        &self,
+        ctx: &FumoContext,
+        cmd: InteractionCommand,
+    ) -> Result<()> {
+        cmd.defer(ctx).await?;
+
+        let acronym = normalize_acronym(&self.acronym);
+
+        let (mut beatmaps, stages) = tokio::try_join!(
+            ctx.db.select_tournament_beatmaps(&acronym),
+            ctx.db.select_tournament_stages(&acronym),
+        )?;
+
+        if beatmaps.is_empty() {
+            let msg = MessageBuilder::new()
+                .content(format!("No matches found for `{acronym}`!"));
+            cmd.update(ctx, &msg).await?;
+            return Ok(());
+        }
+
+        let beatmap_ids: Vec<i64> =
+            beatmaps.iter().map(|x| x.beatmap_id).collect();
+
+        let names: HashMap<i64, String> = ctx
+            .osu_api
+            .get_beatmaps(&beatmap_ids)
+            .await?
+            .into_iter()
+            .map(|x| (x.id as i64, x.metadata()))
+            .collect();
+
+        // Looking up players that don't have a cached username
+        let missing: Vec<UserId> = beatmaps
+            .iter()
+            .filter(|x| x.top_username.is_none())
+            .filter_map(|x| x.top_user_id)
+            .collect::<HashSet<i64>>()
+            .into_iter()
+            .map(UserId::Id)
+            .collect();
+
+        if !missing.is_empty() {
+            let users = ctx.osu_api.lookup_users(&missing).await?.users;
+
+            for user in &users {
+                ctx.db.insert_username(user.id, &user.username).await?;
+            }
+
+            for beatmap in beatmaps.iter_mut() {
+                if beatmap.top_username.is_none() {
+                    beatmap.top_username = users
+                        .iter()
+                        .find(|x| Some(x.id) == beatmap.top_user_id)
+                        .map(|x| x.username.clone());
+                }
+            }
+        }
+
+        let beatmaps_len = beatmaps.len();
+
+        let mut tournament_list =
+            TournamentListing::new(acronym, beatmaps, stages, names)
+                .calculate_pages(beatmaps_len, 8);
+
+        tournament_list.update();
+
+        let mut msg_builder = MessageBuilder::new()
+            .embed(
+                tournament_list
+                    .embed
+                    .as_ref()
+                    .expect("embed should be present")
+                    .clone(),
+            )
+            .components(pages_components());
+
+        if self.export.unwrap_or(false) {
+            msg_builder =
+                msg_builder.attachments(vec![Attachment::from_bytes(
+                    format!("{}_mappool.csv", tournament_list.acronym),
+                    tournament_list.csv().into_bytes(),
+                    1,
+                )]);
+        }
+
+        let msg = cmd.update(ctx, &msg_builder).await?.model().await?;
+
+        // Keeping already uploaded csv file on the page updates
+        msg_builder.attachments = None;
+
+        let msg_stream = component_stream!(ctx, msg);
+
+        tokio::pin!(msg_stream);
+
+        while let Some(Ok(component)) = msg_stream.next().await {
+            tournament_list
+                .handle_interaction_component(ctx, &component)
+                .await;
+            tournament_list.update();
+
+            msg_builder = msg_builder.embed(
+                tournament_list
+                    .embed
+                    .as_ref()
+                    .expect("embed should be present")
+                    .clone(),
+            );
+
+            cmd.update(ctx, &msg_builder).await?;
+        }
+
+        // Clearing components
+        msg_builder.clear_components();
+        cmd.update(ctx, &msg_builder).await?;
+
+        Ok(())
+    }
+}
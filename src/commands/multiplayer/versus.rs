@@ -0,0 +1,387 @@
+use std::{collections::BTreeMap, fmt::Write, time::Duration};
+
+use chrono::NaiveDateTime;
+use eyre::Result;
+use fumo_database::osu::career::OsuVersusGame;
+use fumo_macro::listing;
+use fumo_twilight::message::MessageBuilder;
+use num_format::{Locale, ToFormattedString};
+use osu_api::models::{
+    osu_matches::ScoringKind, OsuMods, OsuUserExtended, UserId,
+};
+use tokio_stream::StreamExt;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::application::interaction::{Interaction, InteractionData};
+use twilight_util::builder::embed::{
+    EmbedBuilder, EmbedFieldBuilder, EmbedFooterBuilder,
+};
+
+use crate::{
+    components::listing::ListingTrait,
+    fumo_context::FumoContext,
+    utils::{
+        interaction::{InteractionCommand, InteractionComponent},
+        static_components::pages_components,
+    },
+};
+
+use super::{tournament_acronym, ListKind};
+
+/// Map wins and differentials from the first player perspective
+#[derive(Default)]
+pub struct VersusRecord {
+    maps: usize,
+    wins1: usize,
+    wins2: usize,
+    score_diff: i64,
+    accuracy_diff: f64,
+}
+
+impl VersusRecord {
+    fn add(&mut self, game: &OsuVersusGame) {
+        self.maps += 1;
+
+        let (value1, value2) = game_values(game);
+
+        // Failed score can't win the map
+        match (game.pass1, game.pass2) {
+            (true, false) => self.wins1 += 1,
+            (false, true) => self.wins2 += 1,
+            (true, true) => match value1.total_cmp(&value2) {
+                std::cmp::Ordering::Greater => self.wins1 += 1,
+                std::cmp::Ordering::Less => self.wins2 += 1,
+                std::cmp::Ordering::Equal => {}
+            },
+            (false, false) => {}
+        }
+
+        self.score_diff += game.score1 - game.score2;
+        self.accuracy_diff += game.accuracy1 - game.accuracy2;
+    }
+
+    fn average_score_diff(&self) -> String {
+        let diff = self.score_diff / self.maps.max(1) as i64;
+        let sign = if diff > 0 { "+" } else { "" };
+
+        format!("{sign}{}", diff.to_formatted_string(&Locale::en))
+    }
+
+    fn average_accuracy_diff(&self) -> String {
+        let diff = self.accuracy_diff / self.maps.max(1) as f64 * 100.0;
+        let sign = if diff > 0.0 { "+" } else { "" };
+
+        format!("{sign}{diff:.2}%")
+    }
+}
+
+/// Values of both players the game winner is decided by
+fn game_values(game: &OsuVersusGame) -> (f64, f64) {
+    let kind = ScoringKind::try_from(game.scoring_kind as u8)
+        .unwrap_or(ScoringKind::Score);
+
+    match kind {
+        ScoringKind::Score | ScoringKind::ScoreV2 => {
+            (game.score1 as f64, game.score2 as f64)
+        }
+        ScoringKind::Accuracy => (game.accuracy1, game.accuracy2),
+        ScoringKind::Combo => (game.max_combo1 as f64, game.max_combo2 as f64),
+    }
+}
+
+/// Match where both players played
+pub struct VersusMatch {
+    match_id: i64,
+    match_name: String,
+    start_time: NaiveDateTime,
+    record: VersusRecord,
+}
+
+/// Mods the map was played with, `FM` if players picked different mods.
+/// No fail is skipped since it is forced in most of the tournaments
+fn game_mods(game: &OsuVersusGame) -> String {
+    let game_mods = OsuMods::from_bits_truncate(game.game_mods as u32);
+
+    let mods1 = (game_mods | OsuMods::from_bits_truncate(game.mods1 as u32))
+        - OsuMods::NOFAIL;
+    let mods2 = (game_mods | OsuMods::from_bits_truncate(game.mods2 as u32))
+        - OsuMods::NOFAIL;
+
+    if mods1 == mods2 {
+        mods1.to_string()
+    } else {
+        String::from("FM")
+    }
+}
+
+#[listing]
+pub struct VersusListing {
+    user1: OsuUserExtended,
+    user2: OsuUserExtended,
+    total: VersusRecord,
+    /// Games where players were in the same team
+    teammates: usize,
+    mods: Vec<(String, VersusRecord)>,
+    matches: Vec<VersusMatch>,
+}
+
+impl ListingTrait for VersusListing {
+    async fn handle_interaction_component(
+        &mut self,
+        ctx: &FumoContext,
+        component: &InteractionComponent,
+    ) {
+        let _ = component.defer(ctx).await;
+
+        if let Some(data) = &component.data {
+            match data.custom_id.as_ref() {
+                "B1" => self.previous_page(),
+                "B2" => self.next_page(),
+                _ => {}
+            }
+        }
+    }
+
+    fn update(&mut self) {
+        let footer = EmbedFooterBuilder::new(format!(
+            "Matches: {} • Page {}/{}",
+            self.matches.len(),
+            self.current_page,
+            self.max_pages
+        ));
+
+        let mut description = String::with_capacity(1000);
+
+        let _ = writeln!(
+            description,
+            "## {} {} – {} {}",
+            self.user1.username,
+            self.total.wins1,
+            self.total.wins2,
+            self.user2.username
+        );
+
+        let _ = writeln!(
+            description,
+            "Maps played against each other: **{}** ({} as teammates)",
+            self.total.maps, self.teammates
+        );
+
+        let _ = writeln!(
+            description,
+            "Average difference: **{}** • **{}**\n",
+            self.total.average_score_diff(),
+            self.total.average_accuracy_diff()
+        );
+
+        let start_at = (self.current_page - 1) * self.entries_per_page;
+
+        let matches = self
+            .matches
+            .iter()
+            .skip(start_at)
+            .take(self.entries_per_page);
+
+        for versus_match in matches {
+            let _ = writeln!(
+                description,
+                "- **[{}](https://osu.ppy.sh/community/matches/{})**",
+                versus_match.match_name, versus_match.match_id
+            );
+            let _ = writeln!(
+                description,
+                "{}–{} • {} • <t:{}:R>",
+                versus_match.record.wins1,
+                versus_match.record.wins2,
+                versus_match.record.average_score_diff(),
+                versus_match.start_time.and_utc().timestamp()
+            );
+        }
+
+        let mut mods = String::with_capacity(300);
+
+        for (label, record) in &self.mods {
+            let _ = writeln!(
+                mods,
+                "`{label}` {}–{} • {} • {}",
+                record.wins1,
+                record.wins2,
+                record.average_score_diff(),
+                record.average_accuracy_diff()
+            );
+        }
+
+        let embed = EmbedBuilder::new()
+            .color(123432)
+            .title(format!(
+                "{} vs {}",
+                self.user1.username, self.user2.username
+            ))
+            .description(description)
+            .field(EmbedFieldBuilder::new("Mods", mods))
+            .footer(footer)
+            .build();
+
+        self.embed = Some(embed);
+    }
+}
+
+/// Compare two players in the games they played together
+#[derive(CommandModel, CreateCommand, Debug)]
+#[command(name = "versus")]
+pub struct MultiplayerVersus {
+    /// First osu! user id or username
+    user1: String,
+
+    /// Second osu! user id or username
+    user2: String,
+
+    /// All matches or only tournament related, tournament by default
+    kind: Option<ListKind>,
+
+    /// Tournament acronym e.g. OWC2023, implies tournament matches
+    tournament: Option<String>,
+}
+
+impl MultiplayerVersus {
+    pub async fn run(
+        &self,
+        ctx: &FumoContext,
+        cmd: InteractionCommand,
+    ) -> Result<()> {
+        cmd.defer(ctx).await?;
+
+        let (user1, user2) = tokio::try_join!(
+            ctx.osu_api
+                .get_user(UserId::from(self.user1.as_ref()), None),
+            ctx.osu_api
+                .get_user(UserId::from(self.user2.as_ref()), None),
+        )?;
+
+        let (Some(user1), Some(user2)) = (user1, user2) else {
+            let msg = MessageBuilder::new()
+                .content("Can't find one of the players on osu!");
+            cmd.update(ctx, &msg).await?;
+            return Ok(());
+        };
+
+        if user1.id == user2.id {
+            let msg = MessageBuilder::new()
+                .content("Please provide two different players");
+            cmd.update(ctx, &msg).await?;
+            return Ok(());
+        }
+
+        let tournament = tournament_acronym(&self.tournament);
+        let is_tournament = tournament.is_some()
+            || self.kind.as_ref().is_none_or(|x| x.is_tournament());
+
+        let games = ctx
+            .db
+            .select_versus_games(
+                user1.id,
+                user2.id,
+                is_tournament,
+                tournament.as_deref(),
+            )
+            .await?;
+
+        if games.is_empty() {
+            let msg = MessageBuilder::new()
+                .content("These players never played in the same match!");
+            cmd.update(ctx, &msg).await?;
+            return Ok(());
+        }
+
+        let mut total = VersusRecord::default();
+        let mut teammates = 0;
+        let mut mods: BTreeMap<String, VersusRecord> = BTreeMap::new();
+        let mut matches: Vec<VersusMatch> = Vec::new();
+
+        for game in &games {
+            // Players of the same team didn't play against each other
+            if game.team1 != 0 && game.team1 == game.team2 {
+                teammates += 1;
+                continue;
+            }
+
+            total.add(game);
+            mods.entry(game_mods(game)).or_default().add(game);
+
+            // Games are sorted by time so games of the match are together
+            match matches.last_mut() {
+                Some(last) if last.match_id == game.match_id => {
+                    last.record.add(game)
+                }
+                _ => {
+                    let mut record = VersusRecord::default();
+                    record.add(game);
+
+                    matches.push(VersusMatch {
+                        match_id: game.match_id,
+                        match_name: game.match_name.clone(),
+                        start_time: game.start_time,
+                        record,
+                    });
+                }
+            }
+        }
+
+        if total.maps == 0 {
+            let msg = MessageBuilder::new()
+                .content("These players only played as teammates!");
+            cmd.update(ctx, &msg).await?;
+            return Ok(());
+        }
+
+        matches.reverse();
+
+        let mut mods: Vec<(String, VersusRecord)> = mods.into_iter().collect();
+        mods.sort_by_key(|x| std::cmp::Reverse(x.1.maps));
+
+        let matches_len = matches.len();
+
+        let mut versus_list =
+            VersusListing::new(user1, user2, total, teammates, mods, matches)
+                .calculate_pages(matches_len, 8);
+
+        versus_list.update();
+
+        let mut msg_builder = MessageBuilder::new()
+            .embed(
+                versus_list
+                    .embed
+                    .as_ref()
+                    .expect("embed should be present")
+                    .clone(),
+            )
+            .components(pages_components());
+
+        let msg = cmd.update(ctx, &msg_builder).await?.model().await?;
+        let msg_stream = component_stream!(ctx, msg);
+
+        tokio::pin!(msg_stream);
+
+        while let Some(Ok(component)) = msg_stream.next().await {
+            versus_list
+                .handle_interaction_component(ctx, &component)
+                .await;
+            versus_list.update();
+
+            msg_builder = msg_builder.embed(
+                versus_list
+                    .embed
+                    .as_ref()
+                    .expect("embed should be present")
+                    .clone(),
+            );
+
+            cmd.update(ctx, &msg_builder).await?;
+        }
+
+        // Clearing components
+        msg_builder.clear_components();
+        cmd.update(ctx, &msg_builder).await?;
+
+        Ok(())
+    }
+}
@@ -1,15 +1,22 @@
 use eyre::Result;
 use fumo_twilight::message::MessageBuilder;
 use twilight_interactions::command::{CommandModel, CreateCommand};
-use twilight_model::channel::message::MessageFlags;
+use twilight_model::{channel::message::MessageFlags, guild::Permissions};
 
 use crate::{
-    fumo_context::FumoContext, utils::interaction::InteractionCommand,
+    fumo_context::FumoContext,
+    utils::{
+        countries::{country_flag, country_name},
+        interaction::InteractionCommand,
+    },
 };
 
 use osu_api::models::UserId;
 
-use super::{attributes::OsuAttributes, osu_tracking::OsuTracking};
+use super::{
+    attributes::OsuAttributes, osu_tracking::OsuTracking,
+    server_rankings::OsuServerRankings, snipes::OsuServerSnipes,
+};
 
 /// All osu! related commands
 #[derive(CommandModel, CreateCommand, Debug)]
@@ -23,6 +30,12 @@ pub enum OsuCommands {
     Attributes(OsuAttributes),
     #[command(name = "tracking")]
     Tracking(OsuTracking),
+    #[command(name = "server-country")]
+    ServerCountry(OsuServerCountry),
+    #[command(name = "server-rankings")]
+    ServerRankings(OsuServerRankings),
+    #[command(name = "server-snipes")]
+    ServerSnipes(OsuServerSnipes),
 }
 
 impl OsuCommands {
@@ -41,6 +54,30 @@ impl OsuCommands {
                 ctx.stats.bot.cmd.with_label_values(&["osu_unlink"]).inc();
                 command.run(ctx, cmd).await
             }
+            OsuCommands::ServerCountry(command) => {
+                ctx.stats
+                    .bot
+                    .cmd
+                    .with_label_values(&["osu_server_country"])
+                    .inc();
+                command.run(ctx, cmd).await
+            }
+            OsuCommands::ServerRankings(command) => {
+                ctx.stats
+                    .bot
+                    .cmd
+                    .with_label_values(&["osu_server_rankings"])
+                    .inc();
+                command.run(ctx, cmd).await
+            }
+            OsuCommands::ServerSnipes(command) => {
+                ctx.stats
+                    .bot
+                    .cmd
+                    .with_label_values(&["osu_server_snipes"])
+                    .inc();
+                command.run(ctx, cmd).await
+            }
             OsuCommands::Attributes(attrs) => match attrs {
                 OsuAttributes::Ar(command) => {
                     ctx.stats
@@ -59,49 +96,197 @@ impl OsuCommands {
                     command.run(ctx, cmd).await
                 }
             },
-            OsuCommands::Tracking(command) => match command {
-                OsuTracking::Add(command) => {
-                    ctx.stats
-                        .bot
-                        .cmd
-                        .with_label_values(&["osu_tracking_ad"])
-                        .inc();
-                    command.run(ctx, cmd).await
-                }
-                OsuTracking::Remove(command) => {
-                    ctx.stats
-                        .bot
-                        .cmd
-                        .with_label_values(&["osu_tracking_remove"])
-                        .inc();
-                    command.run(ctx, cmd).await
-                }
-                OsuTracking::AddBulk(command) => {
-                    ctx.stats
-                        .bot
-                        .cmd
-                        .with_label_values(&["osu_tracking_addbulk"])
-                        .inc();
-                    command.run(ctx, cmd).await
-                }
-                OsuTracking::RemoveAll(command) => {
-                    ctx.stats
-                        .bot
-                        .cmd
-                        .with_label_values(&["osu_tracking_removeall"])
-                        .inc();
-                    command.run(ctx, cmd).await
+            OsuCommands::Tracking(command) => {
+                remember_tracking_guild(ctx, &cmd).await;
+
+                match command {
+                    OsuTracking::Add(command) => {
+                        ctx.stats
+                            .bot
+                            .cmd
+                            .with_label_values(&["osu_tracking_ad"])
+                            .inc();
+                        command.run(ctx, cmd).await
+                    }
+                    OsuTracking::Remove(command) => {
+                        ctx.stats
+                            .bot
+                            .cmd
+                            .with_label_values(&["osu_tracking_remove"])
+                            .inc();
+                        command.run(ctx, cmd).await
+                    }
+                    OsuTracking::AddBulk(command) => {
+                        ctx.stats
+                            .bot
+                            .cmd
+                            .with_label_values(&["osu_tracking_addbulk"])
+                            .inc();
+                        command.run(ctx, cmd).await
+                    }
+                    OsuTracking::RemoveAll(command) => {
+                        ctx.stats
+                            .bot
+                            .cmd
+                            .with_label_values(&["osu_tracking_removeall"])
+                            .inc();
+                        command.run(ctx, cmd).await
+                    }
+                    OsuTracking::List(command) => {
+                        ctx.stats
+                            .bot
+                            .cmd
+                            .with_label_values(&["osu_tracking_list"])
+                            .inc();
+                        command.run(ctx, cmd).await
+                    }
+                    OsuTracking::Style(command) => {
+                        ctx.stats
+                            .bot
+                            .cmd
+                            .with_label_values(&["osu_tracking_style"])
+                            .inc();
+                        command.run(ctx, cmd).await
+                    }
+                    OsuTracking::Preview(command) => {
+                        ctx.stats
+                            .bot
+                            .cmd
+                            .with_label_values(&["osu_tracking_preview"])
+                            .inc();
+                        command.run(ctx, cmd).await
+                    }
+                    OsuTracking::Webhook(command) => {
+                        ctx.stats
+                            .bot
+                            .cmd
+                            .with_label_values(&["osu_tracking_webhook"])
+                            .inc();
+                        command.run(ctx, cmd).await
+                    }
+                    OsuTracking::Export(command) => {
+                        ctx.stats
+                            .bot
+                            .cmd
+                            .with_label_values(&["osu_tracking_export"])
+                            .inc();
+                        command.run(ctx, cmd).await
+                    }
+                    OsuTracking::Import(command) => {
+                        ctx.stats
+                            .bot
+                            .cmd
+                            .with_label_values(&["osu_tracking_import"])
+                            .inc();
+                        command.run(ctx, cmd).await
+                    }
+                    OsuTracking::Digest(command) => {
+                        ctx.stats
+                            .bot
+                            .cmd
+                            .with_label_values(&["osu_tracking_digest"])
+                            .inc();
+                        command.run(ctx, cmd).await
+                    }
+                    OsuTracking::Mode(command) => {
+                        ctx.stats
+                            .bot
+                            .cmd
+                            .with_label_values(&["osu_tracking_mode"])
+                            .inc();
+                        command.run(ctx, cmd).await
+                    }
                 }
-                OsuTracking::List(command) => {
-                    ctx.stats
-                        .bot
-                        .cmd
-                        .with_label_values(&["osu_tracking_list"])
-                        .inc();
-                    command.run(ctx, cmd).await
+            }
+        }
+    }
+}
+
+/// Fills the guild of tracking channels that were set up before it was
+/// stored, so maintenance can notify the owner when channel is disabled
+async fn remember_tracking_guild(ctx: &FumoContext, cmd: &InteractionCommand) {
+    let Some(guild_id) = cmd.guild_id else {
+        return;
+    };
+
+    let _ = ctx
+        .db
+        .set_osu_tracking_guild(
+            cmd.channel_id.get() as i64,
+            guild_id.get() as i64,
+        )
+        .await
+        .inspect_err(|e| {
+            tracing::warn!("Failed to store osu tracking channel guild: {e}")
+        });
+}
+
+/// Set default country of this server for the country leaderboard
+#[derive(CommandModel, CreateCommand, Debug)]
+#[command(name = "server-country")]
+pub struct OsuServerCountry {
+    /// Country code, default country is removed if not specified
+    #[command(autocomplete = true, min_length = 2, max_length = 2)]
+    country: Option<String>,
+}
+
+impl OsuServerCountry {
+    pub async fn run(
+        &self,
+        ctx: &FumoContext,
+        cmd: InteractionCommand,
+    ) -> Result<()> {
+        let mut msg = MessageBuilder::new().flags(MessageFlags::EPHEMERAL);
+
+        let Some(guild_id) = cmd.guild_id else {
+            msg = msg.content("This command is available only on servers!");
+            cmd.response(ctx, &msg).await?;
+            return Ok(());
+        };
+
+        let can_manage = cmd
+            .member
+            .as_ref()
+            .and_then(|x| x.permissions)
+            .is_some_and(|x| x.contains(Permissions::MANAGE_GUILD));
+
+        if !can_manage {
+            msg =
+                msg.content("You need `Manage Server` permission to do that!");
+            cmd.response(ctx, &msg).await?;
+            return Ok(());
+        }
+
+        let country = self.country.as_ref().map(|x| x.to_uppercase());
+
+        let name = match &country {
+            Some(code) => match country_name(code) {
+                Some(name) => Some(name),
+                None => {
+                    msg = msg.content("Unknown country code!");
+                    cmd.response(ctx, &msg).await?;
+                    return Ok(());
                 }
             },
-        }
+            None => None,
+        };
+
+        ctx.db
+            .set_guild_country(guild_id.get() as i64, country.as_deref())
+            .await?;
+
+        msg = match (country, name) {
+            (Some(code), Some(name)) => msg.content(format!(
+                "Default country of this server is {} {}",
+                country_flag(&code),
+                name
+            )),
+            _ => msg.content("Default country of this server is removed"),
+        };
+
+        cmd.response(ctx, &msg).await?;
+
+        Ok(())
     }
 }
 
@@ -168,9 +353,15 @@ impl OsuLink {
 
         match user {
             Some(user) => {
-                ctx.db
-                    .link_osu(discord_id!(cmd).get() as i64, user.id)
-                    .await?;
+                let discord_id = discord_id!(cmd).get() as i64;
+
+                ctx.db.link_osu(discord_id, user.id).await?;
+
+                if let Some(guild_id) = cmd.guild_id {
+                    ctx.db
+                        .add_osu_user_guild(discord_id, guild_id.get() as i64)
+                        .await?;
+                }
             }
             None => {
                 msg = msg.content("User not found!");
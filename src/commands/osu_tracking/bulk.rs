@@ -0,0 +1,368 @@
+use std::{collections::HashSet, fmt::Write, time::Duration};
+
+use eyre::Result;
+use fumo_macro::listing;
+use fumo_twilight::message::MessageBuilder;
+use osu_api::models::{GetRanking, RankingFilter, RankingKind, UserId};
+use tokio_stream::StreamExt;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+    application::interaction::{Interaction, InteractionData},
+    channel::message::MessageFlags,
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFooterBuilder};
+
+use crate::{
+    commands::GameModeOption,
+    components::listing::ListingTrait,
+    fumo_context::FumoContext,
+    utils::{
+        interaction::{InteractionCommand, InteractionComponent},
+        static_components::pages_components,
+    },
+};
+
+/// Max amount of users that could be added with a single command
+const MAX_BULK_USERS: usize = 500;
+
+/// Size of the rankings page in the osu! api
+const RANKINGS_PAGE_SIZE: i64 = 50;
+
+#[derive(CommandModel, CreateCommand, Debug)]
+#[command(
+    name = "add-bulk",
+    desc = "
+    Add multiple users to the tracking from leaderboards or usernames list
+"
+)]
+pub struct OsuTrackingAddBulk {
+    /// Amount of users to add, 50 by default
+    #[command(min_value = 1, max_value = 500)]
+    amount: Option<i64>,
+
+    /// Country code, if not specified then global leaderboard
+    /// is going to be used
+    #[command(autocomplete = true, min_length = 2, max_length = 2)]
+    country: Option<String>,
+
+    /// Ruleset of the leaderboard, osu! by default
+    mode: Option<GameModeOption>,
+
+    /// Starting page (1 page = 50 players)
+    #[command(min_value = 1, max_value = 200)]
+    page: Option<i64>,
+
+    /// Starting rank, overrides page
+    #[command(min_value = 1, max_value = 10000)]
+    from: Option<i64>,
+
+    /// Ending rank (inclusive), overrides amount
+    #[command(min_value = 1, max_value = 10000)]
+    to: Option<i64>,
+
+    /// Comma separated list of usernames, overrides leaderboards
+    #[command(min_length = 2, max_length = 4000)]
+    users: Option<String>,
+}
+
+enum BulkAddStatus {
+    Added,
+    AlreadyTracked,
+    NotFound,
+}
+
+struct BulkAddEntry {
+    username: String,
+    rank: Option<u32>,
+    status: BulkAddStatus,
+}
+
+#[listing]
+pub struct BulkAddListing {
+    entries: Vec<BulkAddEntry>,
+    added: usize,
+}
+
+impl ListingTrait for BulkAddListing {
+    async fn handle_interaction_component(
+        &mut self,
+        ctx: &FumoContext,
+        component: &InteractionComponent,
+    ) {
+        let _ = component.defer(ctx).await;
+
+        if let Some(data) = &component.data {
+            match data.custom_id.as_ref() {
+                "B1" => self.previous_page(),
+                "B2" => self.next_page(),
+                _ => {}
+            }
+        }
+    }
+
+    fn update(&mut self) {
+        let footer = EmbedFooterBuilder::new(format!(
+            "Added: {} • Skipped: {} • Page {}/{}",
+            self.added,
+            self.entries.len() - self.added,
+            self.current_page,
+            self.max_pages
+        ));
+
+        let start_at = (self.current_page - 1) * self.entries_per_page;
+
+        let mut description = String::with_capacity(256);
+
+        for entry in self
+            .entries
+            .iter()
+            .skip(start_at)
+            .take(self.entries_per_page)
+        {
+            if let Some(rank) = entry.rank {
+                let _ = write!(description, "`#{}` ", rank);
+            }
+
+            let status = match entry.status {
+                BulkAddStatus::Added => "Added",
+                BulkAddStatus::AlreadyTracked => "Already tracked",
+                BulkAddStatus::NotFound => "Not found",
+            };
+
+            let _ = writeln!(description, "{} - {}", entry.username, status);
+        }
+
+        let embed = EmbedBuilder::new()
+            .color(0xbd49ff)
+            .title("Bulk tracking")
+            .description(description)
+            .footer(footer)
+            .build();
+
+        self.embed = Some(embed);
+    }
+}
+
+impl OsuTrackingAddBulk {
+    /// Usernames from the comma separated list
+    fn usernames(&self) -> Option<Vec<&str>> {
+        self.users.as_ref().map(|users| {
+            users
+                .split(',')
+                .map(str::trim)
+                .filter(|x| !x.is_empty())
+                .collect()
+        })
+    }
+
+    /// Ranks range requested by the user
+    fn ranks(&self) -> (i64, i64) {
+        let amount = self.amount.unwrap_or(RANKINGS_PAGE_SIZE);
+
+        let from = match self.from {
+            Some(from) => from,
+            None => (self.page.unwrap_or(1) - 1) * RANKINGS_PAGE_SIZE + 1,
+        };
+
+        let to = self.to.unwrap_or(from + amount - 1);
+
+        (from, to)
+    }
+
+    /// Resolves requested players, returns entries of
+    /// players that weren't found along with found ones
+    async fn fetch_players(
+        &self,
+        ctx: &FumoContext,
+    ) -> Result<(Vec<BulkAddEntry>, Vec<(i64, String, Option<u32>)>)> {
+        if let Some(usernames) = self.usernames() {
+            let requested: Vec<UserId> = usernames
+                .into_iter()
+                .map(|x| UserId::Username(x.to_owned()))
+                .collect();
+
+            let found = ctx.osu_api.lookup_users(&requested).await?.users;
+
+            let not_found = requested
+                .into_iter()
+                .filter(|x| {
+                    !found.iter().any(|user| {
+                        user.username.eq_ignore_ascii_case(&x.to_string())
+                    })
+                })
+                .map(|x| BulkAddEntry {
+                    username: x.to_string(),
+                    rank: None,
+                    status: BulkAddStatus::NotFound,
+                })
+                .collect();
+
+            let players = found
+                .into_iter()
+                .map(|user| (user.id, user.username, None))
+                .collect();
+
+            return Ok((not_found, players));
+        }
+
+        let (from, to) = self.ranks();
+
+        let get_ranking = GetRanking {
+            mode: self.mode.unwrap_or(GameModeOption::Osu).into(),
+            kind: RankingKind::Performance,
+            filter: RankingFilter::All,
+            country: self.country.as_ref().map(|x| x.to_uppercase()),
+            page: None,
+        };
+
+        let rankings = ctx
+            .osu_api
+            .get_rankings_range(&get_ranking, from as usize..=to as usize)
+            .await?;
+
+        let players = rankings
+            .ranking
+            .into_iter()
+            .enumerate()
+            .map(|(i, stats)| {
+                let rank = from as u32 + i as u32;
+
+                (stats.user.id, stats.user.username, Some(rank))
+            })
+            .collect();
+
+        Ok((Vec::new(), players))
+    }
+
+    pub async fn run(
+        &self,
+        ctx: &FumoContext,
+        cmd: InteractionCommand,
+    ) -> Result<()> {
+        let channel_id = cmd.channel_id.get().try_into()?;
+
+        let error = match self.usernames() {
+            Some(usernames) if usernames.len() > MAX_BULK_USERS => {
+                Some(format!(
+                    "Provided {} usernames, at most {} could be added at once",
+                    usernames.len(),
+                    MAX_BULK_USERS
+                ))
+            }
+            Some(_) => None,
+            None => {
+                let (from, to) = self.ranks();
+
+                let amount = to - from + 1;
+
+                (amount < 1 || amount as usize > MAX_BULK_USERS).then(|| {
+                    format!(
+                        "Ranks range should contain from 1 to {} players",
+                        MAX_BULK_USERS
+                    )
+                })
+            }
+        };
+
+        if let Some(error) = error {
+            let msg = MessageBuilder::new()
+                .flags(MessageFlags::EPHEMERAL)
+                .content(error);
+
+            cmd.response(ctx, &msg).await?;
+
+            return Ok(());
+        }
+
+        cmd.defer(ctx).await?;
+
+        ctx.db.add_discord_channel(channel_id).await?;
+        ctx.db
+            .enable_osu_tracking_channel(
+                channel_id,
+                cmd.guild_id.map(|x| x.get() as i64),
+            )
+            .await?;
+
+        let (not_found, players) = self.fetch_players(ctx).await?;
+
+        let (osu_ids, usernames): (Vec<i64>, Vec<String>) = players
+            .iter()
+            .map(|(osu_id, username, _)| (*osu_id, username.clone()))
+            .unzip();
+
+        let added: HashSet<i64> = ctx
+            .db
+            .add_osu_tracking_bulk(channel_id, &osu_ids, &usernames)
+            .await?
+            .into_iter()
+            .collect();
+
+        let added_len = added.len();
+
+        // Found players are going first
+        let mut entries: Vec<BulkAddEntry> = players
+            .into_iter()
+            .map(|(osu_id, username, rank)| {
+                let status = if added.contains(&osu_id) {
+                    BulkAddStatus::Added
+                } else {
+                    BulkAddStatus::AlreadyTracked
+                };
+
+                BulkAddEntry {
+                    username,
+                    rank,
+                    status,
+                }
+            })
+            .collect();
+
+        entries.extend(not_found);
+
+        let entries_len = entries.len();
+
+        let mut listing = BulkAddListing::new(entries, added_len)
+            .calculate_pages(entries_len, 15);
+
+        listing.update();
+
+        let mut msg_builder = MessageBuilder::new()
+            .embed(
+                listing
+                    .embed
+                    .as_ref()
+                    .expect("embed should be present")
+                    .clone(),
+            )
+            .components(pages_components());
+
+        let msg = cmd.update(ctx, &msg_builder).await?.model().await?;
+        let msg_stream = component_stream!(ctx, msg);
+
+        tokio::pin!(msg_stream);
+
+        while let Some(Ok(component)) = msg_stream.next().await {
+            listing.handle_interaction_component(ctx, &component).await;
+
+            listing.update();
+
+            msg_builder = msg_builder
+                .embed(
+                    listing
+                        .embed
+                        .as_ref()
+                        .expect("embed should be present")
+                        .clone(),
+                )
+                .components(pages_components());
+
+            cmd.update(ctx, &msg_builder).await?;
+        }
+
+        msg_builder.clear_components();
+        cmd.update(ctx, &msg_builder).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,401 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use eyre::Result;
+use fumo_database::osu::tracking::OsuTrackingDelivery;
+use tokio::task::JoinSet;
+use twilight_http::error::ErrorType;
+use twilight_model::{channel::message::Embed, id::Id};
+
+use crate::fumo_context::FumoContext;
+
+/// How often queue is checked even if nothing was enqueued
+const DELIVERY_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Time given for the burst of scores to land in the queue
+/// before sending anything, so they could be collapsed together
+const DELIVERY_COLLECT_DELAY: Duration = Duration::from_secs(3);
+
+/// Amount of pending deliveries after which new ones are dropped
+const DELIVERY_QUEUE_CAPACITY: i64 = 5000;
+
+const DELIVERY_BATCH_SIZE: i64 = 500;
+const DELIVERY_MAX_ATTEMPTS: i16 = 5;
+const DELIVERY_RETRY_BASE_SECS: f64 = 30.0;
+
+/// Discord limit of embeds per message
+const MAX_EMBEDS_PER_MESSAGE: usize = 10;
+
+/// Score notification ready to be enqueued
+pub struct TrackingDelivery<'a> {
+    pub osu_id: i64,
+    pub osu_username: &'a str,
+    pub avatar_url: &'a str,
+    pub channel_ids: Vec<i64>,
+    pub embeds: Vec<String>,
+}
+
+/// Persists notification and wakes up delivery worker
+pub async fn enqueue_tracking_delivery(
+    ctx: &FumoContext,
+    delivery: TrackingDelivery<'_>,
+) -> Result<()> {
+    let pending = ctx.db.count_osu_tracking_deliveries().await?;
+
+    if pending >= DELIVERY_QUEUE_CAPACITY {
+        tracing::warn!(
+            osu_id = delivery.osu_id,
+            pending = pending,
+            "Tracking delivery queue is full, dropping notification"
+        );
+
+        ctx.stats
+            .bot
+            .deliveries
+            .with_label_values(&["overflow"])
+            .inc_by(delivery.channel_ids.len() as u64);
+
+        return Ok(());
+    }
+
+    ctx.db
+        .add_osu_tracking_deliveries(
+            delivery.osu_id,
+            delivery.osu_username,
+            delivery.avatar_url,
+            &delivery.channel_ids,
+            &delivery.embeds,
+        )
+        .await?;
+
+    ctx.osu_tracking_delivery.notify_one();
+
+    Ok(())
+}
+
+enum DeliveryOutcome {
+    Sent,
+    /// Discord won't ever accept this message, no reason to retry
+    Rejected,
+    /// Bot lost access to the channel or it was deleted
+    NoAccess,
+    /// Channel webhook is gone, message is retried as a regular one
+    WebhookMissing,
+    Failed,
+}
+
+/// Consecutive scores of the same player for a single channel
+struct DeliveryGroup {
+    channel_id: i64,
+    osu_id: i64,
+    ids: Vec<i64>,
+    attempts: i16,
+    osu_username: String,
+    avatar_url: String,
+    webhook: Option<(i64, String)>,
+    embeds: Vec<Embed>,
+}
+
+/// Splits pending deliveries into groups that could be sent right now.
+///
+/// Only head of the every channel queue is taken to preserve
+/// per-channel ordering, following entries from the same player
+/// are collapsed into the single message.
+/// Deliveries that can't be sent at all are returned separately.
+fn collect_groups(
+    deliveries: Vec<OsuTrackingDelivery>,
+) -> (Vec<DeliveryGroup>, Vec<i64>) {
+    let mut groups: Vec<DeliveryGroup> = Vec::new();
+    let mut broken = Vec::new();
+
+    // Channel -> index inside `groups` or `None` if channel is blocked
+    let mut channels: HashMap<i64, Option<usize>> = HashMap::new();
+
+    for delivery in deliveries {
+        let group_index = match channels.get(&delivery.channel_id) {
+            Some(None) => continue,
+            Some(Some(index)) => {
+                let group = &groups[*index];
+
+                let same_burst = group.ids.len() < MAX_EMBEDS_PER_MESSAGE
+                    && group.osu_id == delivery.osu_id
+                    && delivery.due;
+
+                if !same_burst {
+                    channels.insert(delivery.channel_id, None);
+                    continue;
+                }
+
+                Some(*index)
+            }
+            None => None,
+        };
+
+        let embed: Embed = match serde_json::from_str(&delivery.embed) {
+            Ok(v) => v,
+            Err(e) => {
+                tracing::error!(
+                    id = delivery.id,
+                    "Failed to deserialize tracking embed: {e}"
+                );
+                broken.push(delivery.id);
+                continue;
+            }
+        };
+
+        match group_index {
+            Some(index) => {
+                let group = &mut groups[index];
+                group.ids.push(delivery.id);
+                group.embeds.push(embed);
+                group.attempts = group.attempts.max(delivery.attempts);
+            }
+            None if !delivery.due => {
+                channels.insert(delivery.channel_id, None);
+            }
+            None => {
+                channels.insert(delivery.channel_id, Some(groups.len()));
+
+                let webhook = delivery.webhook_id.zip(delivery.webhook_token);
+
+                groups.push(DeliveryGroup {
+                    channel_id: delivery.channel_id,
+                    osu_id: delivery.osu_id,
+                    ids: vec![delivery.id],
+                    attempts: delivery.attempts,
+                    osu_username: delivery.osu_username,
+                    avatar_url: delivery.avatar_url,
+                    webhook,
+                    embeds: vec![embed],
+                })
+            }
+        }
+    }
+
+    (groups, broken)
+}
+
+async fn send_group(
+    ctx: &FumoContext,
+    group: &DeliveryGroup,
+) -> DeliveryOutcome {
+    let res = match &group.webhook {
+        Some((webhook_id, webhook_token)) => {
+            let Some(webhook_id) = Id::new_checked(*webhook_id as u64) else {
+                return DeliveryOutcome::WebhookMissing;
+            };
+
+            let request = ctx
+                .http
+                .execute_webhook(webhook_id, webhook_token)
+                .avatar_url(&group.avatar_url)
+                .username(&group.osu_username)
+                .and_then(|x| x.embeds(&group.embeds));
+
+            match request {
+                Ok(request) => request.await.map(|_| ()),
+                Err(e) => {
+                    tracing::error!(
+                        channel_id = group.channel_id,
+                        "Invalid tracking webhook message: {e}"
+                    );
+                    return DeliveryOutcome::Rejected;
+                }
+            }
+        }
+        None => {
+            let request = ctx
+                .http
+                .create_message(Id::new(group.channel_id as u64))
+                .embeds(&group.embeds);
+
+            match request {
+                Ok(request) => request.await.map(|_| ()),
+                Err(e) => {
+                    tracing::error!(
+                        channel_id = group.channel_id,
+                        "Invalid tracking message: {e}"
+                    );
+                    return DeliveryOutcome::Rejected;
+                }
+            }
+        }
+    };
+
+    let Err(err) = res else {
+        return DeliveryOutcome::Sent;
+    };
+
+    tracing::error!(
+        channel_id = group.channel_id,
+        "Error during delivery of tracking message: {err}"
+    );
+
+    match err.kind() {
+        ErrorType::Response { status, .. }
+            if status.get() == 404 && group.webhook.is_some() =>
+        {
+            DeliveryOutcome::WebhookMissing
+        }
+        ErrorType::Response { status, .. }
+            if matches!(status.get(), 403 | 404) =>
+        {
+            DeliveryOutcome::NoAccess
+        }
+        ErrorType::Response { status, .. } if status.get() == 400 => {
+            DeliveryOutcome::Rejected
+        }
+        _ => DeliveryOutcome::Failed,
+    }
+}
+
+async fn handle_outcome(
+    ctx: &FumoContext,
+    group: &DeliveryGroup,
+    outcome: DeliveryOutcome,
+) -> Result<()> {
+    match outcome {
+        DeliveryOutcome::Sent => {
+            ctx.stats
+                .bot
+                .deliveries
+                .with_label_values(&["sent"])
+                .inc_by(group.ids.len() as u64);
+
+            ctx.db.remove_osu_tracking_deliveries(&group.ids).await?;
+            ctx.db.reset_osu_tracking_failures(group.channel_id).await?;
+        }
+        DeliveryOutcome::NoAccess => {
+            ctx.stats
+                .bot
+                .deliveries
+                .with_label_values(&["no_access"])
+                .inc_by(group.ids.len() as u64);
+
+            // Channel is disabled later by maintenance
+            ctx.db.add_osu_tracking_failure(group.channel_id).await?;
+            ctx.db.remove_osu_tracking_deliveries(&group.ids).await?;
+        }
+        DeliveryOutcome::Rejected => {
+            ctx.stats
+                .bot
+                .deliveries
+                .with_label_values(&["rejected"])
+                .inc_by(group.ids.len() as u64);
+
+            ctx.db.remove_osu_tracking_deliveries(&group.ids).await?;
+        }
+        DeliveryOutcome::WebhookMissing => {
+            tracing::warn!(
+                channel_id = group.channel_id,
+                "Tracking webhook is missing, falling back to messages"
+            );
+
+            ctx.db
+                .set_osu_tracking_webhook(group.channel_id, None)
+                .await?;
+        }
+        DeliveryOutcome::Failed
+            if group.attempts + 1 >= DELIVERY_MAX_ATTEMPTS =>
+        {
+            tracing::error!(
+                channel_id = group.channel_id,
+                "Giving up on tracking delivery after {} attempts",
+                DELIVERY_MAX_ATTEMPTS
+            );
+
+            ctx.stats
+                .bot
+                .deliveries
+                .with_label_values(&["dropped"])
+                .inc_by(group.ids.len() as u64);
+
+            ctx.db.remove_osu_tracking_deliveries(&group.ids).await?;
+        }
+        DeliveryOutcome::Failed => {
+            ctx.stats
+                .bot
+                .deliveries
+                .with_label_values(&["retried"])
+                .inc_by(group.ids.len() as u64);
+
+            let delay =
+                DELIVERY_RETRY_BASE_SECS * 2f64.powi(group.attempts.into());
+
+            ctx.db
+                .postpone_osu_tracking_deliveries(&group.ids, delay)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Sends everything that is due, returns amount of sent groups
+async fn process_deliveries(ctx: &Arc<FumoContext>) -> Result<usize> {
+    let deliveries = ctx
+        .db
+        .select_osu_tracking_deliveries(
+            DELIVERY_BATCH_SIZE,
+            MAX_EMBEDS_PER_MESSAGE as i64,
+        )
+        .await?;
+
+    let (groups, broken) = collect_groups(deliveries);
+    let len = groups.len();
+
+    if !broken.is_empty() {
+        ctx.db.remove_osu_tracking_deliveries(&broken).await?;
+    }
+
+    // Channels are independent from each other, so one
+    // rate limited channel shouldn't hold others
+    let mut set = JoinSet::new();
+
+    for group in groups {
+        let ctx = Arc::clone(ctx);
+
+        set.spawn(async move {
+            let outcome = send_group(&ctx, &group).await;
+            handle_outcome(&ctx, &group, outcome).await
+        });
+    }
+
+    while let Some(res) = set.join_next().await {
+        match res {
+            Ok(Err(e)) => {
+                tracing::error!("Failed to update tracking delivery: {e}")
+            }
+            Err(e) => tracing::error!("Tracking delivery task failed: {e}"),
+            Ok(Ok(())) => {}
+        }
+    }
+
+    Ok(len)
+}
+
+pub async fn osu_tracking_delivery_worker(ctx: Arc<FumoContext>) {
+    tracing::info!("Starting osu tracking delivery worker!");
+
+    loop {
+        tokio::select! {
+            _ = ctx.osu_tracking_delivery.notified() => {
+                tokio::time::sleep(DELIVERY_COLLECT_DELAY).await;
+            }
+            _ = tokio::time::sleep(DELIVERY_INTERVAL) => {}
+        }
+
+        // Draining everything that is due right now
+        loop {
+            match process_deliveries(&ctx).await {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to process tracking deliveries: {e}"
+                    );
+                    break;
+                }
+            }
+        }
+    }
+}
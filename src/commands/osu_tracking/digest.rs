@@ -0,0 +1,350 @@
+use std::fmt::Write;
+
+use chrono::{DateTime, TimeDelta, Utc, Weekday};
+use eyre::Result;
+use fumo_database::osu::digest::{
+    OsuDigestScore, OsuTrackingDigest, OsuTrackingDigestSchedule,
+};
+use fumo_twilight::message::MessageBuilder;
+use twilight_interactions::command::{
+    CommandModel, CommandOption, CreateCommand, CreateOption,
+};
+use twilight_model::{
+    channel::message::{Embed, MessageFlags},
+    guild::Permissions,
+    id::Id,
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+use crate::{
+    fumo_context::FumoContext, scheduler::last_weekly_occurrence,
+    utils::interaction::InteractionCommand,
+};
+
+/// Amount of entries in every digest section
+const DIGEST_SECTION_SIZE: i64 = 5;
+
+/// Digests that weren't sent in time (e.g. bot was offline)
+/// are skipped after this delay
+const MAX_DIGEST_DELAY: TimeDelta = TimeDelta::hours(12);
+
+const MAX_TITLE_LENGTH: usize = 60;
+
+#[derive(Debug, CommandOption, CreateOption, Clone, Copy)]
+pub enum DigestWeekday {
+    #[option(name = "Monday", value = 0)]
+    Monday,
+    #[option(name = "Tuesday", value = 1)]
+    Tuesday,
+    #[option(name = "Wednesday", value = 2)]
+    Wednesday,
+    #[option(name = "Thursday", value = 3)]
+    Thursday,
+    #[option(name = "Friday", value = 4)]
+    Friday,
+    #[option(name = "Saturday", value = 5)]
+    Saturday,
+    #[option(name = "Sunday", value = 6)]
+    Sunday,
+}
+
+impl DigestWeekday {
+    fn as_i16(&self) -> i16 {
+        *self as i16
+    }
+}
+
+/// Days since monday, unknown values are treated as monday
+fn weekday_from_i16(value: i16) -> Weekday {
+    Weekday::try_from(value.clamp(0, 6) as u8).unwrap_or(Weekday::Mon)
+}
+
+/// Last scheduled digest time that is not after `now`
+fn last_occurrence(
+    schedule: &OsuTrackingDigestSchedule,
+    now: DateTime<Utc>,
+) -> DateTime<Utc> {
+    last_weekly_occurrence(
+        now,
+        weekday_from_i16(schedule.weekday),
+        schedule.hour.clamp(0, 23) as u32,
+    )
+}
+
+/// Configure weekly digest of tracked players for current channel
+#[derive(CommandModel, CreateCommand, Debug)]
+#[command(name = "digest")]
+pub struct OsuTrackingDigestSettings {
+    /// Whether digest should be posted on this channel
+    enabled: bool,
+
+    /// Day of the week when digest is posted, applies to the whole server
+    day: Option<DigestWeekday>,
+
+    /// Hour (UTC) when digest is posted, applies to the whole server
+    #[command(min_value = 0, max_value = 23)]
+    hour: Option<i64>,
+}
+
+impl OsuTrackingDigestSettings {
+    pub async fn run(
+        &self,
+        ctx: &FumoContext,
+        cmd: InteractionCommand,
+    ) -> Result<()> {
+        let mut msg = MessageBuilder::new().flags(MessageFlags::EPHEMERAL);
+
+        let Some(guild_id) = cmd.guild_id else {
+            msg = msg.content("Digest is available only on servers!");
+            cmd.response(ctx, &msg).await?;
+            return Ok(());
+        };
+
+        // Schedule is shared by the whole server
+        if self.day.is_some() || self.hour.is_some() {
+            let can_manage = cmd
+                .member
+                .as_ref()
+                .and_then(|x| x.permissions)
+                .is_some_and(|x| x.contains(Permissions::MANAGE_GUILD));
+
+            if !can_manage {
+                msg = msg.content(
+                    "You need `Manage Server` permission to change the schedule!",
+                );
+                cmd.response(ctx, &msg).await?;
+                return Ok(());
+            }
+        }
+
+        let channel_id: i64 = cmd.channel_id.get().try_into()?;
+        let guild_id: i64 = guild_id.get().try_into()?;
+
+        ctx.db.add_discord_channel(channel_id).await?;
+        ctx.db
+            .set_osu_tracking_digest(channel_id, guild_id, self.enabled)
+            .await?;
+
+        let schedule = ctx
+            .db
+            .set_osu_tracking_digest_schedule(
+                guild_id,
+                self.day.map(|x| x.as_i16()),
+                self.hour.map(|x| x as i16),
+            )
+            .await?;
+
+        let next = last_occurrence(&schedule, Utc::now()) + TimeDelta::weeks(1);
+
+        msg = if self.enabled {
+            msg.content(format!(
+                "Weekly digest is enabled on this channel, \
+                next one is going to be posted <t:{}:F>",
+                next.timestamp()
+            ))
+        } else {
+            msg.content("Weekly digest is disabled on this channel")
+        };
+
+        cmd.response(ctx, &msg).await?;
+
+        Ok(())
+    }
+}
+
+fn mode_name(mode: i16) -> &'static str {
+    match mode {
+        1 => "taiko",
+        2 => "catch",
+        3 => "mania",
+        _ => "osu!",
+    }
+}
+
+fn beatmap_link(beatmap_id: i64, title: &str) -> String {
+    let title = if title.chars().count() > MAX_TITLE_LENGTH {
+        let mut title: String = title.chars().take(MAX_TITLE_LENGTH).collect();
+        title.push('…');
+        title
+    } else {
+        title.to_owned()
+    };
+
+    format!("[{}](https://osu.ppy.sh/b/{})", title, beatmap_id)
+}
+
+fn write_scores(scores: &[OsuDigestScore]) -> String {
+    let mut text = String::with_capacity(512);
+
+    for score in scores {
+        let _ = write!(
+            text,
+            "`{:.0}pp` **{}** {}",
+            score.pp,
+            score.osu_username,
+            beatmap_link(score.beatmap_id, &score.beatmap_title)
+        );
+
+        if let Some(pos) = score.top_score_pos {
+            let _ = write!(text, " • personal best #{}", pos);
+        }
+
+        let _ = writeln!(text);
+    }
+
+    text
+}
+
+/// Builds digest embed for the period `from..to`
+pub fn build_digest_embed(
+    digest: &OsuTrackingDigest,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Embed {
+    let mut embed = EmbedBuilder::new()
+        .color(0xbd49ff)
+        .title("Weekly digest")
+        .description(format!(
+            "Tracked players from <t:{}:d> to <t:{}:d>",
+            from.timestamp(),
+            to.timestamp()
+        ));
+
+    if !digest.top_plays.is_empty() {
+        embed = embed.field(EmbedFieldBuilder::new(
+            "Top plays",
+            write_scores(&digest.top_plays),
+        ));
+    }
+
+    if !digest.pp_gains.is_empty() {
+        let mut text = String::with_capacity(256);
+
+        for gain in &digest.pp_gains {
+            let _ = writeln!(
+                text,
+                "**{}** +{:.1}pp → `{:.0}pp` ({})",
+                gain.osu_username,
+                gain.gain,
+                gain.user_pp,
+                mode_name(gain.mode)
+            );
+        }
+
+        embed = embed.field(EmbedFieldBuilder::new("Biggest pp gains", text));
+    }
+
+    if !digest.beatmaps.is_empty() {
+        let mut text = String::with_capacity(512);
+
+        for beatmap in &digest.beatmaps {
+            let _ = writeln!(
+                text,
+                "{} • {} plays by {} players",
+                beatmap_link(beatmap.beatmap_id, &beatmap.beatmap_title),
+                beatmap.plays,
+                beatmap.players
+            );
+        }
+
+        embed = embed.field(EmbedFieldBuilder::new("Most played maps", text));
+    }
+
+    if !digest.new_firsts.is_empty() {
+        embed = embed.field(EmbedFieldBuilder::new(
+            "New #1s",
+            write_scores(&digest.new_firsts),
+        ));
+    }
+
+    embed.build()
+}
+
+/// Posts digest to every digest channel of the guild
+async fn send_guild_digest(
+    ctx: &FumoContext,
+    guild_id: i64,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<()> {
+    let channels = ctx.db.select_osu_tracking_digest_channels(guild_id).await?;
+
+    for channel_id in channels {
+        let digest = ctx
+            .db
+            .select_osu_tracking_digest(
+                channel_id,
+                from,
+                to,
+                DIGEST_SECTION_SIZE,
+            )
+            .await?;
+
+        if digest.is_empty() {
+            continue;
+        }
+
+        let embed = build_digest_embed(&digest, from, to);
+
+        let res = ctx
+            .http
+            .create_message(Id::new(channel_id as u64))
+            .embeds(&[embed])?
+            .await;
+
+        if let Err(e) = res {
+            tracing::warn!(
+                channel_id = channel_id,
+                guild_id = guild_id,
+                "Failed to send osu tracking digest: {e}"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Sends digests of all guilds whose schedule has come
+pub async fn send_due_digests(
+    ctx: &FumoContext,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let schedules = ctx.db.select_osu_tracking_digest_schedules().await?;
+
+    for schedule in schedules {
+        let occurrence = last_occurrence(&schedule, now);
+
+        let already_sent = schedule
+            .last_sent_at
+            .is_some_and(|x| x.and_utc() >= occurrence);
+
+        if already_sent {
+            continue;
+        }
+
+        if now - occurrence <= MAX_DIGEST_DELAY {
+            let from = occurrence - TimeDelta::weeks(1);
+
+            if let Err(e) =
+                send_guild_digest(ctx, schedule.guild_id, from, occurrence)
+                    .await
+            {
+                tracing::error!(
+                    guild_id = schedule.guild_id,
+                    "Failed to send osu tracking digest: {e}"
+                );
+            }
+        } else {
+            tracing::warn!(
+                guild_id = schedule.guild_id,
+                "Skipping outdated osu tracking digest"
+            );
+        }
+
+        ctx.db
+            .set_osu_tracking_digest_sent(schedule.guild_id, now)
+            .await?;
+    }
+
+    Ok(())
+}
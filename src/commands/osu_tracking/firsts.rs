@@ -0,0 +1,382 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    str::FromStr,
+};
+
+use eyre::Result;
+use fumo_database::osu::digest::OsuTrackingFirstScore;
+use fumo_twilight::message::MessageBuilder;
+use osu_api::models::{
+    osu_leaderboard::{OsuLeaderboardLazer, OsuScoreLazer},
+    UserId,
+};
+use twilight_interactions::command::{
+    CommandModel, CommandOption, CreateCommand, CreateOption,
+};
+use twilight_model::channel::message::MessageFlags;
+
+use crate::{
+    fumo_context::FumoContext, utils::interaction::InteractionCommand,
+};
+
+use super::{
+    delivery::{enqueue_tracking_delivery, TrackingDelivery},
+    templates::{TrackingScore, TrackingStyle},
+};
+
+/// Leaderboard that is checked for new #1s
+#[derive(
+    Debug, CommandOption, CreateOption, Clone, Copy, PartialEq, Eq, Hash,
+)]
+pub enum TrackingFirsts {
+    #[option(name = "Off", value = "off")]
+    Off = 0,
+    #[option(name = "Global", value = "global")]
+    Global = 1,
+    #[option(name = "Country", value = "country")]
+    Country = 2,
+}
+
+impl TrackingFirsts {
+    pub fn as_i16(&self) -> i16 {
+        *self as i16
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrackingFirsts::Off => "off",
+            TrackingFirsts::Global => "global",
+            TrackingFirsts::Country => "country",
+        }
+    }
+
+    fn is_country(&self) -> bool {
+        matches!(self, TrackingFirsts::Country)
+    }
+}
+
+impl FromStr for TrackingFirsts {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Self::Off),
+            "global" => Ok(Self::Global),
+            "country" => Ok(Self::Country),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<i16> for TrackingFirsts {
+    fn from(value: i16) -> Self {
+        match value {
+            1 => Self::Global,
+            2 => Self::Country,
+            _ => Self::Off,
+        }
+    }
+}
+
+/// Channel that wants to know about new #1s
+pub struct FirstsChannel {
+    pub channel_id: i64,
+    pub firsts: TrackingFirsts,
+    pub style: TrackingStyle,
+}
+
+/// New #1 made by the tracked player
+struct NewFirst {
+    firsts: TrackingFirsts,
+    /// Country of the leaderboard, empty for the global one
+    country_code: String,
+    /// Username of the previous #1 holder,
+    /// `None` if player improved their own #1
+    displaced: Option<String>,
+}
+
+impl NewFirst {
+    fn header(&self) -> String {
+        let mut header = match self.firsts {
+            TrackingFirsts::Country => {
+                format!(
+                    ":first_place: __**New country #1 ({})**__",
+                    self.country_code
+                )
+            }
+            _ => String::from(":first_place: __**New global #1**__"),
+        };
+
+        match &self.displaced {
+            Some(displaced) => {
+                header.push_str(&format!(" • displaced **{displaced}**"))
+            }
+            None => header.push_str(" • improved own #1"),
+        }
+
+        header
+    }
+}
+
+/// Checks whether score took the first place on the leaderboard,
+/// returns `None` if it didn't or if it was already announced.
+/// Country leaderboard belongs to the bot session country,
+/// so it's only checked for the players from the same country
+async fn check_leaderboard(
+    ctx: &FumoContext,
+    score: &OsuScoreLazer,
+    firsts: TrackingFirsts,
+    player_country: Option<&str>,
+) -> Result<Option<NewFirst>> {
+    let OsuLeaderboardLazer { scores } = ctx
+        .osu_api
+        .get_leaderboard_hidden(
+            score.beatmap_id as i32,
+            firsts.is_country(),
+            None,
+            Some(score.ruleset_id),
+        )
+        .await?;
+
+    let country_code = match player_country {
+        Some(player_country) => {
+            let session_country = scores
+                .iter()
+                .find_map(|x| x.user.as_ref())
+                .map(|x| x.country_code.as_str());
+
+            if session_country != Some(player_country) {
+                return Ok(None);
+            }
+
+            player_country.to_owned()
+        }
+        None => String::new(),
+    };
+
+    let Some(first) = scores.first() else {
+        return Ok(None);
+    };
+
+    if first.id != score.id {
+        return Ok(None);
+    }
+
+    let username = first
+        .user
+        .as_ref()
+        .map(|x| x.username.as_str())
+        .unwrap_or_default();
+
+    let previous = ctx
+        .db
+        .replace_osu_tracking_first(
+            score.beatmap_id,
+            score.ruleset_id.as_u8().into(),
+            &country_code,
+            score.user_id,
+            username,
+            score.id,
+        )
+        .await?;
+
+    let displaced = match previous {
+        Some(previous) if previous.score_id == score.id => return Ok(None),
+        Some(previous) if previous.osu_id == score.user_id => None,
+        Some(previous) => Some(previous.osu_username),
+        // Leaderboard contains only best score of every player,
+        // so without history the runner up is the previous holder.
+        None => scores
+            .get(1)
+            .filter(|x| x.user_id != score.user_id)
+            .and_then(|x| x.user.as_ref())
+            .map(|x| x.username.clone()),
+    };
+
+    Ok(Some(NewFirst {
+        firsts,
+        country_code,
+        displaced,
+    }))
+}
+
+/// Announces score if it became a new #1 on the global
+/// or country leaderboard, depending on channels settings
+pub async fn announce_firsts(
+    ctx: &FumoContext,
+    score: &OsuScoreLazer,
+    channels: &[FirstsChannel],
+) -> Result<()> {
+    let mut firsts = Vec::with_capacity(2);
+
+    let wants =
+        |kind: TrackingFirsts| channels.iter().any(|x| x.firsts == kind);
+
+    if wants(TrackingFirsts::Global) {
+        let first =
+            check_leaderboard(ctx, score, TrackingFirsts::Global, None).await?;
+
+        firsts.extend(first);
+    }
+
+    if wants(TrackingFirsts::Country) {
+        let player_country = match &score.user {
+            Some(user) => Some(user.country_code.clone()),
+            None => ctx
+                .osu_api
+                .get_user(UserId::Id(score.user_id), Some(score.ruleset_id))
+                .await?
+                .map(|x| x.country_code),
+        };
+
+        if let Some(player_country) = player_country {
+            let first = check_leaderboard(
+                ctx,
+                score,
+                TrackingFirsts::Country,
+                Some(&player_country),
+            )
+            .await?;
+
+            firsts.extend(first);
+        }
+    }
+
+    if firsts.is_empty() {
+        return Ok(());
+    }
+
+    let (osu_user, osu_beatmap, osu_beatmap_attributes) = tokio::try_join!(
+        ctx.osu_api
+            .get_user(UserId::Id(score.user_id), Some(score.ruleset_id)),
+        ctx.osu_api.get_beatmap(score.beatmap_id),
+        ctx.osu_api
+            .get_beatmap_attributes(score.beatmap_id, Some(&score.mods))
+    )?;
+
+    let Some(osu_user) = osu_user else {
+        return Ok(());
+    };
+
+    let tracking_score = TrackingScore {
+        score,
+        user: &osu_user,
+        beatmap: &osu_beatmap,
+        attributes: &osu_beatmap_attributes.attributes,
+        top_score_pos: None,
+    };
+
+    let mut rendered: HashMap<(TrackingFirsts, TrackingStyle), String> =
+        HashMap::with_capacity(1);
+
+    let mut channel_ids = Vec::with_capacity(channels.len());
+    let mut embeds = Vec::with_capacity(channels.len());
+
+    for channel in channels {
+        let Some(first) = firsts.iter().find(|x| x.firsts == channel.firsts)
+        else {
+            continue;
+        };
+
+        let embed = match rendered.entry((channel.firsts, channel.style)) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let mut embed = channel.style.render(&tracking_score)?;
+
+                let header = first.header();
+
+                embed.description = Some(match embed.description {
+                    Some(description) => format!("{header}\n{description}"),
+                    None => header,
+                });
+
+                entry.insert(serde_json::to_string(&embed)?)
+            }
+        };
+
+        channel_ids.push(channel.channel_id);
+        embeds.push(embed.clone());
+    }
+
+    let delivery = TrackingDelivery {
+        osu_id: osu_user.id,
+        osu_username: &osu_user.username,
+        avatar_url: &osu_user.avatar_url,
+        channel_ids,
+        embeds,
+    };
+
+    enqueue_tracking_delivery(ctx, delivery).await?;
+
+    let beatmap_title = osu_beatmap.metadata();
+
+    for first in &firsts {
+        let history_score = OsuTrackingFirstScore {
+            score_id: score.id,
+            country: first.firsts.is_country(),
+            osu_id: osu_user.id,
+            beatmap_id: score.beatmap_id,
+            beatmap_title: &beatmap_title,
+            pp: score.pp.unwrap_or(0.0),
+            played_at: score.ended_at,
+        };
+
+        ctx.db.add_osu_tracking_first_score(&history_score).await?;
+    }
+
+    Ok(())
+}
+
+/// Choose which scores are announced on current channel
+#[derive(CommandModel, CreateCommand, Debug)]
+#[command(name = "mode")]
+pub struct OsuTrackingMode {
+    /// Announce new top 100 plays, enabled by default
+    top_plays: Option<bool>,
+
+    /// Announce new #1s on the beatmap leaderboards, disabled by default
+    firsts: Option<TrackingFirsts>,
+}
+
+impl OsuTrackingMode {
+    pub async fn run(
+        &self,
+        ctx: &FumoContext,
+        cmd: InteractionCommand,
+    ) -> Result<()> {
+        let channel_id: i64 = cmd.channel_id.get().try_into()?;
+
+        ctx.db.add_discord_channel(channel_id).await?;
+
+        let settings = ctx
+            .db
+            .set_osu_tracking_mode(
+                channel_id,
+                self.top_plays,
+                self.firsts.map(|x| x.as_i16()),
+            )
+            .await?;
+
+        let firsts = match TrackingFirsts::from(settings.firsts) {
+            TrackingFirsts::Off => "disabled",
+            TrackingFirsts::Global => "global leaderboard",
+            TrackingFirsts::Country => "country leaderboard",
+        };
+
+        let msg = MessageBuilder::new()
+            .flags(MessageFlags::EPHEMERAL)
+            .content(format!(
+                "Top plays: **{}**\nNew #1s: **{}**",
+                if settings.top_plays {
+                    "enabled"
+                } else {
+                    "disabled"
+                },
+                firsts
+            ));
+
+        cmd.response(ctx, &msg).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use eyre::Result;
+use twilight_model::id::Id;
+
+use crate::fumo_context::FumoContext;
+
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Amount of failed deliveries after which channel is disabled
+pub const MAX_CHANNEL_FAILURES: i16 = 5;
+
+/// Amount of players refreshed per maintenance run
+const PLAYERS_REFRESH_BATCH: i64 = 500;
+
+/// Announced scores are kept for digests only,
+/// so there is no need to keep them for long
+const SCORES_HISTORY_DAYS: i32 = 60;
+
+async fn notify_guild_owner(
+    ctx: &FumoContext,
+    channel_id: i64,
+    guild_id: i64,
+) -> Result<()> {
+    let guild = ctx
+        .http
+        .guild(Id::new(guild_id as u64))
+        .await?
+        .model()
+        .await?;
+
+    let dm_channel = ctx
+        .http
+        .create_private_channel(guild.owner_id)
+        .await?
+        .model()
+        .await?;
+
+    let content = format!(
+        "osu! tracking in <#{}> (**{}**) has been disabled, \
+        since bot can't send messages there anymore. \
+        Use `/osu tracking add` in that channel to enable it again.",
+        channel_id, guild.name
+    );
+
+    ctx.http
+        .create_message(dm_channel.id)
+        .content(&content)?
+        .await?;
+
+    Ok(())
+}
+
+/// Guild of the channel that doesn't have it stored, bot usually can
+/// still see the channel even if it can't send messages there
+async fn channel_guild(ctx: &FumoContext, channel_id: i64) -> Option<i64> {
+    let channel = ctx
+        .http
+        .channel(Id::new(channel_id as u64))
+        .await
+        .ok()?
+        .model()
+        .await
+        .ok()?;
+
+    channel.guild_id.map(|x| x.get() as i64)
+}
+
+/// Disables channels that are constantly rejecting tracking messages
+async fn prune_channels(ctx: &FumoContext) -> Result<()> {
+    let channels = ctx
+        .db
+        .select_failing_osu_tracking_channels(MAX_CHANNEL_FAILURES)
+        .await?;
+
+    for (channel_id, guild_id) in channels {
+        tracing::warn!(
+            channel_id = channel_id,
+            "Disabling osu tracking for unreachable channel"
+        );
+
+        ctx.db.disable_osu_tracking_channel(channel_id).await?;
+
+        let guild_id = match guild_id {
+            Some(guild_id) => Some(guild_id),
+            None => channel_guild(ctx, channel_id).await,
+        };
+
+        if let Some(guild_id) = guild_id {
+            let _ = notify_guild_owner(ctx, channel_id, guild_id)
+                .await
+                .inspect_err(|e| {
+                    tracing::warn!(
+                        channel_id = channel_id,
+                        guild_id = guild_id,
+                        "Failed to notify guild owner about disabled tracking: {e}"
+                    )
+                });
+        }
+    }
+
+    Ok(())
+}
+
+/// Syncs usernames with osu! and flags restricted or deleted accounts
+async fn refresh_players(ctx: &FumoContext) -> Result<()> {
+    let osu_ids = ctx
+        .db
+        .select_outdated_osu_players(PLAYERS_REFRESH_BATCH)
+        .await?;
+
+    if osu_ids.is_empty() {
+        return Ok(());
+    }
+
+    let users = ctx.osu_api.get_users(&osu_ids).await?.users;
+
+    let mut found = HashSet::with_capacity(users.len());
+    let mut ids = Vec::with_capacity(users.len());
+    let mut usernames = Vec::with_capacity(users.len());
+    let mut deleted = Vec::with_capacity(users.len());
+
+    for user in users {
+        found.insert(user.id);
+        ids.push(user.id);
+        usernames.push(user.username);
+        deleted.push(user.is_deleted);
+    }
+
+    ctx.db
+        .update_osu_players(&ids, &usernames, &deleted)
+        .await?;
+
+    // Restricted users are silently omitted by the api
+    let restricted: Vec<i64> =
+        osu_ids.into_iter().filter(|x| !found.contains(x)).collect();
+
+    if !restricted.is_empty() {
+        tracing::info!(
+            amount = restricted.len(),
+            "Flagging restricted osu players"
+        );
+
+        ctx.db.set_osu_players_restricted(&restricted).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn osu_tracking_maintenance_worker(ctx: Arc<FumoContext>) {
+    tracing::info!("Starting osu tracking maintenance worker!");
+
+    loop {
+        if let Err(e) = prune_channels(&ctx).await {
+            tracing::error!("Failed to prune osu tracking channels: {e}");
+        }
+
+        if let Err(e) = refresh_players(&ctx).await {
+            tracing::error!("Failed to refresh osu tracking players: {e}");
+        }
+
+        if let Err(e) = ctx
+            .db
+            .remove_old_osu_tracking_scores(SCORES_HISTORY_DAYS)
+            .await
+        {
+            tracing::error!("Failed to prune osu tracking scores: {e}");
+        }
+
+        tokio::time::sleep(MAINTENANCE_INTERVAL).await;
+    }
+}
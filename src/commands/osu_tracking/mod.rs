@@ -1,252 +1,78 @@
+mod bulk;
+mod delivery;
+mod digest;
+mod firsts;
+mod maintenance;
+mod templates;
+mod transfer;
+
 use fumo_twilight::message::MessageBuilder;
 use rosu_pp::{
     model::mods::rosu_mods::{GameMod, GameMods},
     Performance,
 };
-use std::{collections::HashMap, sync::Arc, time::Duration};
-use twilight_util::builder::embed::EmbedAuthorBuilder;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    sync::Arc,
+    time::Duration,
+};
 
-use num_format::{Locale, ToFormattedString};
 use tokio_stream::StreamExt;
 
 use std::fmt::Write;
 
 use crate::{
+    commands::{
+        country_leaderboard::store_country_scores, snipes::check_feed_snipes,
+    },
     fumo_context::FumoContext,
     utils::{
-        calc_ar, calc_od,
         interaction::{InteractionCommand, InteractionComponent},
         static_components::pages_components,
     },
 };
 use eyre::Result;
+use fumo_database::osu::{digest::OsuTrackingScore, OsuLinkedTrackedUser};
 use osu_api::{
     error::OsuApiError,
     models::{
-        osu_leaderboard::OsuScoreLazer, GetRanking, GetUserScores, OsuBeatmap,
-        OsuBeatmapAttributesContainer, OsuGameMode, OsuUserExtended,
-        RankingFilter, RankingKind, ScoresType, UserId,
+        osu_leaderboard::OsuScoreLazer, GetUserScores, OsuGameMode, ScoresType,
+        UserId,
     },
 };
 use twilight_interactions::command::{CommandModel, CreateCommand};
 use twilight_model::{
     application::interaction::{Interaction, InteractionData},
-    channel::message::{Embed, MessageFlags},
+    channel::message::MessageFlags,
     id::Id,
 };
-use twilight_util::builder::embed::{
-    EmbedBuilder, EmbedFooterBuilder, ImageSource,
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFooterBuilder};
+
+use self::{
+    bulk::OsuTrackingAddBulk,
+    delivery::{enqueue_tracking_delivery, TrackingDelivery},
+    digest::OsuTrackingDigestSettings,
+    firsts::{announce_firsts, FirstsChannel, OsuTrackingMode, TrackingFirsts},
+    templates::{SampleScore, TrackingScore, TrackingStyle},
+    transfer::{OsuTrackingExport, OsuTrackingImport},
+};
+pub use self::{
+    delivery::osu_tracking_delivery_worker, digest::send_due_digests,
+    maintenance::osu_tracking_maintenance_worker,
 };
 
 const OSU_TRACKING_INTERVAL: Duration = Duration::from_secs(60);
 const OSU_TRACKING_BATCH_SIZE: usize = 850;
 
-fn create_tracking_embed(
-    score: &OsuScoreLazer,
-    user: &OsuUserExtended,
-    beatmap: &OsuBeatmap,
-    beatmap_attrs: &OsuBeatmapAttributesContainer,
-    top_score_pos: Option<usize>,
-) -> eyre::Result<Embed> {
-    let mut description_text = String::with_capacity(100);
-
-    let _ = write!(
-        description_text,
-        "{} [**{} - {} [{}]**](https://osu.ppy.sh/b/{}) ",
-        score.ruleset_id.to_emoji(),
-        beatmap.beatmapset.artist,
-        beatmap.beatmapset.title,
-        beatmap.version,
-        beatmap.id
-    );
-
-    let (star_rating, max_combo) =
-        (beatmap_attrs.star_rating, beatmap.max_combo.unwrap_or(0));
-
-    let _ = writeln!(description_text, "[{:.2}★]", star_rating);
-
-    if let Some(top_score_position) = top_score_pos {
-        let _ = writeln!(
-            description_text,
-            ":trophy: __**New top score #{}**__",
-            top_score_position
-        );
-    }
-
-    let mut mods_string = String::with_capacity(24);
-    if !score.mods.mods.is_empty() {
-        let _ = write!(mods_string, "{}", &score.mods);
-
-        if let Some(speed_change) = score.mods.speed_changes() {
-            let _ = write!(mods_string, " (x{})", speed_change);
-        }
+/// Marks accounts that won't produce any new scores
+fn account_status(user: &OsuLinkedTrackedUser) -> &'static str {
+    if user.deleted {
+        " (deleted)"
+    } else if user.restricted {
+        " (restricted)"
     } else {
-        let _ = write!(mods_string, "NM");
-    }
-
-    let _ = writeln!(
-        description_text,
-        "**{} • +{} • {} • {:.2}%**",
-        score.rank.to_emoji(),
-        &mods_string,
-        score.total_score.to_formatted_string(&Locale::en),
-        score.accuracy * 100.0
-    );
-
-    let _ = writeln!(
-        description_text,
-        "**{:.2}pp** • <t:{}:R>",
-        score.pp.unwrap_or(0.0),
-        score.ended_at.timestamp()
-    );
-
-    match score.ruleset_id {
-        OsuGameMode::Mania => {
-            let x320 = score.stats.perfect.unwrap_or(0);
-            let x300 = score.stats.great.unwrap_or(0);
-            let x200 = score.stats.good.unwrap_or(0);
-
-            let ma_ratio = x320 as f32 / x300 as f32;
-            let pa_ratio = x300 as f32 / x200 as f32;
-
-            let _ = writeln!(
-                description_text,
-                "[{}/{}/{}/{}/{}/{}] • x{}/{} • MA: {:.2} PA: {:.2}",
-                score.stats.perfect.unwrap_or(0),
-                score.stats.great.unwrap_or(0),
-                score.stats.good.unwrap_or(0),
-                score.stats.ok.unwrap_or(0),
-                score.stats.meh.unwrap_or(0),
-                score.stats.miss.unwrap_or(0),
-                score.max_combo,
-                max_combo,
-                ma_ratio,
-                pa_ratio
-            );
-        }
-        _ => {
-            let _ = writeln!(
-                description_text,
-                "[{}/{}/{}/{}] • x{}/{}",
-                score.stats.great.unwrap_or(0),
-                score.stats.ok.unwrap_or(0),
-                score.stats.meh.unwrap_or(0),
-                score.stats.miss.unwrap_or(0),
-                score.max_combo,
-                max_combo
-            );
-        }
+        ""
     }
-
-    let bpm = beatmap.bpm.map(|x| {
-        if let Some(speed_change) = score.mods.speed_changes() {
-            return x * speed_change;
-        };
-
-        if score.mods.contains("DT") {
-            return x * 1.5;
-        }
-
-        if score.mods.contains("HT") {
-            return x * 0.75;
-        }
-
-        x
-    });
-
-    let beatmap_ar = beatmap.ar.ok_or(eyre::eyre!("beatmap ar is empty"))?;
-
-    let beatmap_od =
-        beatmap.accuracy.ok_or(eyre::eyre!("beatmap od is empty"))?;
-
-    let beatmap_cs = beatmap.cs.ok_or(eyre::eyre!("beatmap cs is empty"))?;
-
-    let beatmap_hp = beatmap.drain.ok_or(eyre::eyre!("beatmap hp is empty"))?;
-
-    // AR
-    let approach_rate = calc_ar(beatmap_ar, &score.mods);
-
-    // OD
-    let overall_difficulty =
-        calc_od(beatmap_od, &score.mods, &score.ruleset_id);
-
-    let mut circle_size = beatmap_cs;
-
-    if score.mods.contains("HR") {
-        circle_size = (circle_size * 1.3).min(10.0);
-    }
-
-    if score.mods.contains("EZ") {
-        circle_size /= 2.0;
-    }
-
-    let mut hp_drain = beatmap_hp;
-
-    if score.mods.contains("EZ") {
-        hp_drain /= 2.0;
-    }
-
-    if score.mods.contains("HR") {
-        hp_drain = (hp_drain * 1.4).min(10.0);
-    }
-
-    match score.ruleset_id {
-        OsuGameMode::Fruits => {
-            let _ = write!(
-                description_text,
-                "`AR: {:.2} CS: {:.2} HP: {:.2}",
-                approach_rate, circle_size, hp_drain
-            );
-        }
-        OsuGameMode::Mania => {
-            let _ = write!(
-                description_text,
-                "`OD: {:.2} HP: {:.2}",
-                overall_difficulty, hp_drain
-            );
-        }
-        OsuGameMode::Osu => {
-            let _ = write!(
-                description_text,
-                "`AR: {:.2} OD: {:.2} CS: {:.2} HP: {:.2}",
-                approach_rate, overall_difficulty, circle_size, hp_drain
-            );
-        }
-        OsuGameMode::Taiko => {
-            let _ = write!(
-                description_text,
-                "`OD: {:.2} HP: {:.2}",
-                overall_difficulty, hp_drain
-            );
-        }
-    }
-
-    let _ = writeln!(description_text, " BPM: {:.2}`", bpm.unwrap_or(0.0));
-
-    let thumb_url =
-        format!("https://b.ppy.sh/thumb/{}l.jpg", beatmap.beatmapset_id);
-
-    let footer = EmbedFooterBuilder::new(format!(
-        "Mapper {}",
-        beatmap.beatmapset.creator
-    ));
-
-    let author = EmbedAuthorBuilder::new(format!(
-        "{}: {:.2}pp (#{})",
-        &user.username,
-        user.statistics.pp,
-        user.statistics.global_rank.unwrap_or(0),
-    ))
-    .url(format!("https://osu.ppy.sh/u/{}", user.id));
-
-    Ok(EmbedBuilder::new()
-        .color(0xbd49ff)
-        .description(description_text)
-        .footer(footer)
-        .author(author)
-        .thumbnail(ImageSource::url(thumb_url).unwrap())
-        .url(format!("https://osu.ppy.sh/u/{}", user.id))
-        .build())
 }
 
 async fn osu_track_checker(
@@ -268,7 +94,51 @@ async fn osu_track_checker(
         .await?;
 
     for score in scores.iter_mut() {
-        if let Some(channels_to_notify) = linked_channels.get(&score.user_id) {
+        if let Some((_, Some(channels))) = linked_channels.get(&score.user_id) {
+            let settings = ctx
+                .db
+                .select_osu_tracking_channel_settings(channels)
+                .await?;
+
+            let channel_settings = |channel_id: &i64| {
+                settings.get(channel_id).copied().unwrap_or_default()
+            };
+
+            if score.ranked {
+                let firsts_channels: Vec<FirstsChannel> = channels
+                    .iter()
+                    .map(|x| (x, channel_settings(x)))
+                    .filter(|(_, x)| x.firsts != TrackingFirsts::Off.as_i16())
+                    .map(|(channel_id, x)| FirstsChannel {
+                        channel_id: *channel_id,
+                        firsts: TrackingFirsts::from(x.firsts),
+                        style: TrackingStyle::from(x.style),
+                    })
+                    .collect();
+
+                if !firsts_channels.is_empty() {
+                    if let Err(e) =
+                        announce_firsts(ctx, score, &firsts_channels).await
+                    {
+                        tracing::error!(
+                            user_id = score.user_id,
+                            score_id = score.id,
+                            "Failed to check score for new #1: {e}"
+                        );
+                    }
+                }
+            }
+
+            let channels: Vec<i64> = channels
+                .iter()
+                .filter(|x| channel_settings(x).top_plays)
+                .copied()
+                .collect();
+
+            if channels.is_empty() {
+                continue;
+            }
+
             let min_top_score = if let Some(min_top_score) =
                 top_scores_hash.get(&(score.user_id, score.ruleset_id))
             {
@@ -497,29 +367,58 @@ async fn osu_track_checker(
                 })
                 .map(|(i, _x)| i + 1);
 
-            let embed = create_tracking_embed(
+            let tracking_score = TrackingScore {
                 score,
-                &osu_user,
-                &osu_beatmap,
-                &osu_beatmap_attributes.attributes,
-                top_score_position,
-            )?;
-
-            let embeds = &[embed];
-
-            if let Some(channels) = &channels_to_notify.1 {
-                for discord_channel in channels {
-                    let _ = ctx
-                        .http
-                        .create_message(Id::new(*discord_channel as u64))
-                        .embeds(embeds)
-                        .unwrap()
-                        .await
-                        .inspect_err(|err| {
-                            tracing::error!("Error during creation of embed messages for tracking: {err}")
-                        });
-                }
+                user: &osu_user,
+                beatmap: &osu_beatmap,
+                attributes: &osu_beatmap_attributes.attributes,
+                top_score_pos: top_score_position,
+            };
+
+            // Every style is rendered only once per score
+            let mut rendered: HashMap<TrackingStyle, String> =
+                HashMap::with_capacity(1);
+
+            let mut embeds = Vec::with_capacity(channels.len());
+
+            for discord_channel in &channels {
+                let style = TrackingStyle::from(
+                    channel_settings(discord_channel).style,
+                );
+
+                let embed = match rendered.entry(style) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => entry.insert(
+                        serde_json::to_string(&style.render(&tracking_score)?)?,
+                    ),
+                };
+
+                embeds.push(embed.clone());
             }
+
+            let delivery = TrackingDelivery {
+                osu_id: osu_user.id,
+                osu_username: &osu_user.username,
+                avatar_url: &osu_user.avatar_url,
+                channel_ids: channels,
+                embeds,
+            };
+
+            enqueue_tracking_delivery(ctx, delivery).await?;
+
+            let history_score = OsuTrackingScore {
+                score_id: score.id,
+                osu_id: osu_user.id,
+                mode: score.ruleset_id.as_u8().into(),
+                beatmap_id: score.beatmap_id,
+                beatmap_title: &osu_beatmap.metadata(),
+                pp: score.pp.unwrap_or(0.0),
+                top_score_pos: top_score_position.map(|x| x as i16),
+                user_pp: osu_user.statistics.pp,
+                played_at: score.ended_at,
+            };
+
+            ctx.db.add_osu_tracking_score(&history_score).await?;
         }
     }
 
@@ -550,11 +449,7 @@ pub async fn osu_tracking_worker(ctx: Arc<FumoContext>) {
                         "cursor is too old, incrementing by 1000"
                     );
 
-                    cursor = cursor.and_then(|x| Some(x + 1000));
-
-                    if let Some(mut cursor) = &mut cursor {
-                        cursor += 1000;
-                    };
+                    cursor = cursor.map(|x| x + 1000);
 
                     continue;
                 };
@@ -596,6 +491,21 @@ pub async fn osu_tracking_worker(ctx: Arc<FumoContext>) {
             )
         }
 
+        match store_country_scores(&ctx, &batch.scores).await {
+            Ok(stored) => {
+                if let Err(err) = check_feed_snipes(&ctx, &stored).await {
+                    tracing::error!(
+                        cursor = cursor,
+                        "Failed to check country snipes: {err}"
+                    )
+                }
+            }
+            Err(err) => tracing::error!(
+                cursor = cursor,
+                "Failed to store country leaderboard scores: {err}"
+            ),
+        }
+
         cursor = Some(current_newest_score_id);
 
         let mut state_lock = ctx.state.lock().await;
@@ -618,6 +528,20 @@ pub enum OsuTracking {
     RemoveAll(OsuTrackingRemoveAll),
     #[command(name = "list")]
     List(OsuTrackingList),
+    #[command(name = "style")]
+    Style(OsuTrackingStyle),
+    #[command(name = "preview")]
+    Preview(OsuTrackingPreview),
+    #[command(name = "webhook")]
+    Webhook(OsuTrackingWebhook),
+    #[command(name = "export")]
+    Export(OsuTrackingExport),
+    #[command(name = "import")]
+    Import(OsuTrackingImport),
+    #[command(name = "digest")]
+    Digest(OsuTrackingDigestSettings),
+    #[command(name = "mode")]
+    Mode(OsuTrackingMode),
 }
 
 /// Remove osu user from tracking
@@ -712,6 +636,16 @@ impl OsuTrackingAdd {
                 let osu_tracked =
                     ctx.db.select_osu_tracking(channel_id, osu_user.id).await?;
 
+                // Adding any user enables channel back
+                // after it was disabled for failing deliveries
+                ctx.db.add_discord_channel(channel_id).await?;
+                ctx.db
+                    .enable_osu_tracking_channel(
+                        channel_id,
+                        cmd.guild_id.map(|x| x.get() as i64),
+                    )
+                    .await?;
+
                 match osu_tracked {
                     Some(_) => {
                         msg = msg.content("User is already tracked");
@@ -739,98 +673,6 @@ impl OsuTrackingAdd {
     }
 }
 
-#[derive(CommandModel, CreateCommand, Debug)]
-#[command(
-    name = "add-bulk",
-    desc = "
-    Add multiple users to the tracking, either based on country 
-    or global leaderboards
-"
-)]
-pub struct OsuTrackingAddBulk {
-    /// Amount of users to add
-    #[command(min_value = 1, max_value = 50)]
-    amount: i64,
-
-    /// Country code, if not specified then global leaderboard
-    /// is going to be used
-    #[command(min_length = 2, max_length = 2)]
-    country: Option<String>,
-
-    /// Starting page (1 page = 50 players)
-    #[command(min_value = 1, max_value = 200)]
-    page: Option<i64>,
-}
-
-impl OsuTrackingAddBulk {
-    pub async fn run(
-        &self,
-        ctx: &FumoContext,
-        cmd: InteractionCommand,
-    ) -> Result<()> {
-        let channel_id = cmd.channel_id.get().try_into()?;
-
-        ctx.db.add_discord_channel(channel_id).await?;
-
-        // Fetch all tracked users in current channel
-        let tracked_users =
-            ctx.db.select_osu_tracking_by_channel(channel_id).await?;
-
-        let (ranking_kind, country_code) = match &self.country {
-            Some(country_code) => {
-                (RankingKind::Performance, Some(country_code.clone()))
-            }
-            None => (RankingKind::Performance, None),
-        };
-
-        let page = self.page.unwrap_or(0);
-
-        let get_ranking = GetRanking {
-            mode: OsuGameMode::Osu,
-            kind: ranking_kind,
-            filter: RankingFilter::All,
-            country: country_code,
-            page: Some(page as u32),
-        };
-
-        // Fetch users that should be added
-        let rankings = ctx
-            .osu_api
-            .get_rankings(&get_ranking, self.amount as usize)
-            .await?;
-
-        let mut str = String::new();
-
-        let _ = writeln!(str, "```");
-
-        for stats in rankings.ranking {
-            // TODO lmao wtf is this refactor ASAP
-            if tracked_users.iter().any(|x| x.osu_id == stats.user.id) {
-                let _ =
-                    writeln!(str, "{} - Already tracked", stats.user.username);
-            } else {
-                ctx.db
-                    .add_osu_player(stats.user.id, &stats.user.username)
-                    .await?;
-
-                ctx.db.add_osu_tracking(channel_id, stats.user.id).await?;
-
-                let _ = writeln!(str, "{} - Added", stats.user.username);
-            }
-        }
-
-        let _ = writeln!(str, "```");
-
-        let msg = MessageBuilder::new()
-            .flags(MessageFlags::EPHEMERAL)
-            .content(str);
-
-        cmd.response(ctx, &msg).await?;
-
-        Ok(())
-    }
-}
-
 #[derive(CommandModel, CreateCommand, Debug)]
 #[command(
     name = "remove-all",
@@ -902,7 +744,12 @@ impl OsuTrackingList {
         let mut body_text = String::with_capacity(100);
 
         for tracked_user in tracked_users.iter().take(elem_per_page as usize) {
-            let _ = writeln!(body_text, "{}", &tracked_user.osu_username);
+            let _ = writeln!(
+                body_text,
+                "{}{}",
+                &tracked_user.osu_username,
+                account_status(tracked_user)
+            );
         }
 
         let embed = EmbedBuilder::new()
@@ -949,8 +796,9 @@ impl OsuTrackingList {
                     {
                         let _ = writeln!(
                             description,
-                            "{}",
-                            &tracked_user.osu_username
+                            "{}{}",
+                            &tracked_user.osu_username,
+                            account_status(tracked_user)
                         );
                     }
                 }
@@ -980,3 +828,167 @@ impl OsuTrackingList {
         Ok(())
     }
 }
+
+/// Change style of the tracking notifications on current channel
+#[derive(CommandModel, CreateCommand, Debug)]
+#[command(name = "style")]
+pub struct OsuTrackingStyle {
+    /// Notification style
+    style: TrackingStyle,
+}
+
+impl OsuTrackingStyle {
+    pub async fn run(
+        &self,
+        ctx: &FumoContext,
+        cmd: InteractionCommand,
+    ) -> Result<()> {
+        let channel_id: i64 = cmd.channel_id.get().try_into()?;
+
+        ctx.db.add_discord_channel(channel_id).await?;
+        ctx.db
+            .set_osu_tracking_style(channel_id, self.style.as_i16())
+            .await?;
+
+        let sample = SampleScore::new();
+        let embed = self.style.render(&sample.as_tracking_score())?;
+
+        let msg = MessageBuilder::new()
+            .flags(MessageFlags::EPHEMERAL)
+            .content(format!(
+                "Tracking notifications on this channel will use `{}` style",
+                self.style.as_str()
+            ))
+            .embed(embed);
+
+        cmd.response(ctx, &msg).await?;
+
+        Ok(())
+    }
+}
+
+/// Preview tracking notification on a sample score
+#[derive(CommandModel, CreateCommand, Debug)]
+#[command(name = "preview")]
+pub struct OsuTrackingPreview {
+    /// Notification style, current channel style is used by default
+    style: Option<TrackingStyle>,
+}
+
+impl OsuTrackingPreview {
+    pub async fn run(
+        &self,
+        ctx: &FumoContext,
+        cmd: InteractionCommand,
+    ) -> Result<()> {
+        let style = match self.style {
+            Some(style) => style,
+            None => {
+                let channel_id: i64 = cmd.channel_id.get().try_into()?;
+
+                ctx.db
+                    .get_osu_tracking_style(channel_id)
+                    .await?
+                    .map(TrackingStyle::from)
+                    .unwrap_or(TrackingStyle::Default)
+            }
+        };
+
+        let sample = SampleScore::new();
+        let embed = style.render(&sample.as_tracking_score())?;
+
+        let msg = MessageBuilder::new()
+            .flags(MessageFlags::EPHEMERAL)
+            .embed(embed);
+
+        cmd.response(ctx, &msg).await?;
+
+        Ok(())
+    }
+}
+
+/// Send tracking notifications through webhook with player avatars
+#[derive(CommandModel, CreateCommand, Debug)]
+#[command(name = "webhook")]
+pub struct OsuTrackingWebhook {
+    /// Whether webhook should be used on current channel
+    enabled: bool,
+}
+
+impl OsuTrackingWebhook {
+    pub async fn run(
+        &self,
+        ctx: &FumoContext,
+        cmd: InteractionCommand,
+    ) -> Result<()> {
+        let channel_id: i64 = cmd.channel_id.get().try_into()?;
+
+        ctx.db.add_discord_channel(channel_id).await?;
+
+        let mut msg = MessageBuilder::new().flags(MessageFlags::EPHEMERAL);
+
+        // Old webhook is removed in both cases
+        if let Some(webhook_id) =
+            ctx.db.get_osu_tracking_webhook(channel_id).await?
+        {
+            let _ = ctx
+                .http
+                .delete_webhook(Id::new(webhook_id as u64))
+                .await
+                .inspect_err(|err| {
+                    tracing::warn!(
+                        channel_id = channel_id,
+                        "Failed to delete tracking webhook: {err}"
+                    )
+                });
+
+            ctx.db.set_osu_tracking_webhook(channel_id, None).await?;
+        }
+
+        if !self.enabled {
+            msg =
+                msg.content("Tracking notifications will be sent as messages");
+            cmd.response(ctx, &msg).await?;
+            return Ok(());
+        }
+
+        let webhook = match ctx
+            .http
+            .create_webhook(cmd.channel_id, "FumoPotato tracking")?
+            .await
+        {
+            Ok(v) => v.model().await?,
+            Err(e) => {
+                tracing::warn!(
+                    channel_id = channel_id,
+                    "Failed to create tracking webhook: {e}"
+                );
+
+                msg = msg.content(
+                    "Failed to create webhook, make sure bot has `Manage Webhooks` permission",
+                );
+                cmd.response(ctx, &msg).await?;
+                return Ok(());
+            }
+        };
+
+        let Some(token) = &webhook.token else {
+            msg = msg.content("Discord didn't provide webhook token");
+            cmd.response(ctx, &msg).await?;
+            return Ok(());
+        };
+
+        ctx.db
+            .set_osu_tracking_webhook(
+                channel_id,
+                Some((webhook.id.get() as i64, token)),
+            )
+            .await?;
+
+        msg =
+            msg.content("Tracking notifications will be sent through webhook");
+        cmd.response(ctx, &msg).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,578 @@
+use std::{fmt::Write, str::FromStr};
+
+use chrono::DateTime;
+use eyre::Result;
+use num_format::{Locale, ToFormattedString};
+use osu_api::models::{
+    osu_leaderboard::{OsuScoreLazer, StatisticsLazer},
+    osu_mods::{OsuModLazer, OsuModsLazer},
+    OsuBeatmap, OsuBeatmapAttributesContainer, OsuBeatmapsetCompact,
+    OsuGameMode, OsuGrade, OsuUserExtended, OsuUserExtendedStatistics,
+    RankStatus,
+};
+use twilight_interactions::command::{CommandOption, CreateOption};
+use twilight_model::channel::message::Embed;
+use twilight_util::builder::embed::{
+    EmbedAuthorBuilder, EmbedBuilder, EmbedFieldBuilder, EmbedFooterBuilder,
+    ImageSource,
+};
+
+use crate::utils::{calc_ar, calc_od};
+
+const TRACKING_EMBED_COLOR: u32 = 0xbd49ff;
+
+/// Named layouts of the tracking notifications
+#[derive(
+    Debug, CommandOption, CreateOption, Clone, Copy, PartialEq, Eq, Hash,
+)]
+pub enum TrackingStyle {
+    #[option(name = "Default", value = "default")]
+    Default = 0,
+    #[option(name = "Compact", value = "compact")]
+    Compact = 1,
+    #[option(name = "Detailed", value = "detailed")]
+    Detailed = 2,
+}
+
+impl TrackingStyle {
+    pub fn as_i16(&self) -> i16 {
+        *self as i16
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrackingStyle::Default => "default",
+            TrackingStyle::Compact => "compact",
+            TrackingStyle::Detailed => "detailed",
+        }
+    }
+
+    /// Renders provided score according to the layout
+    pub fn render(&self, data: &TrackingScore<'_>) -> Result<Embed> {
+        match self {
+            TrackingStyle::Default => render_default(data),
+            TrackingStyle::Compact => Ok(render_compact(data)),
+            TrackingStyle::Detailed => render_detailed(data),
+        }
+    }
+}
+
+impl FromStr for TrackingStyle {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(Self::Default),
+            "compact" => Ok(Self::Compact),
+            "detailed" => Ok(Self::Detailed),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Unknown values are falling back to the default style
+impl From<i16> for TrackingStyle {
+    fn from(value: i16) -> Self {
+        match value {
+            1 => Self::Compact,
+            2 => Self::Detailed,
+            _ => Self::Default,
+        }
+    }
+}
+
+/// Everything that is needed to render a tracking notification
+pub struct TrackingScore<'a> {
+    pub score: &'a OsuScoreLazer,
+    pub user: &'a OsuUserExtended,
+    pub beatmap: &'a OsuBeatmap,
+    pub attributes: &'a OsuBeatmapAttributesContainer,
+    pub top_score_pos: Option<usize>,
+}
+
+/// Beatmap attributes with applied mods
+struct ModdedDifficulty {
+    ar: f64,
+    od: f64,
+    cs: f32,
+    hp: f32,
+    bpm: f32,
+}
+
+impl TrackingScore<'_> {
+    fn beatmap_url(&self) -> String {
+        format!("https://osu.ppy.sh/b/{}", self.beatmap.id)
+    }
+
+    fn user_url(&self) -> String {
+        format!("https://osu.ppy.sh/u/{}", self.user.id)
+    }
+
+    fn mods_string(&self) -> String {
+        let mods = &self.score.mods;
+        let mut mods_string = String::with_capacity(24);
+
+        if !mods.mods.is_empty() {
+            let _ = write!(mods_string, "{}", mods);
+
+            if let Some(speed_change) = mods.speed_changes() {
+                let _ = write!(mods_string, " (x{})", speed_change);
+            }
+        } else {
+            let _ = write!(mods_string, "NM");
+        }
+
+        mods_string
+    }
+
+    fn difficulty(&self) -> Result<ModdedDifficulty> {
+        let (score, beatmap) = (self.score, self.beatmap);
+
+        let bpm = beatmap.bpm.map(|x| {
+            if let Some(speed_change) = score.mods.speed_changes() {
+                return x * speed_change;
+            };
+
+            if score.mods.contains("DT") {
+                return x * 1.5;
+            }
+
+            if score.mods.contains("HT") {
+                return x * 0.75;
+            }
+
+            x
+        });
+
+        let beatmap_ar =
+            beatmap.ar.ok_or(eyre::eyre!("beatmap ar is empty"))?;
+
+        let beatmap_od =
+            beatmap.accuracy.ok_or(eyre::eyre!("beatmap od is empty"))?;
+
+        let beatmap_cs =
+            beatmap.cs.ok_or(eyre::eyre!("beatmap cs is empty"))?;
+
+        let beatmap_hp =
+            beatmap.drain.ok_or(eyre::eyre!("beatmap hp is empty"))?;
+
+        let mut circle_size = beatmap_cs;
+
+        if score.mods.contains("HR") {
+            circle_size = (circle_size * 1.3).min(10.0);
+        }
+
+        if score.mods.contains("EZ") {
+            circle_size /= 2.0;
+        }
+
+        let mut hp_drain = beatmap_hp;
+
+        if score.mods.contains("EZ") {
+            hp_drain /= 2.0;
+        }
+
+        if score.mods.contains("HR") {
+            hp_drain = (hp_drain * 1.4).min(10.0);
+        }
+
+        Ok(ModdedDifficulty {
+            ar: calc_ar(beatmap_ar, &score.mods),
+            od: calc_od(beatmap_od, &score.mods, &score.ruleset_id),
+            cs: circle_size,
+            hp: hp_drain,
+            bpm: bpm.unwrap_or(0.0),
+        })
+    }
+
+    /// `{mode} [**{Artist} - {Title} [{Version}]**](link) [{stars}★]`
+    fn write_title(&self, s: &mut String) {
+        let _ = write!(
+            s,
+            "{} [**{} - {} [{}]**]({}) ",
+            self.score.ruleset_id.to_emoji(),
+            self.beatmap.beatmapset.artist,
+            self.beatmap.beatmapset.title,
+            self.beatmap.version,
+            self.beatmap_url()
+        );
+
+        let _ = writeln!(s, "[{:.2}★]", self.attributes.star_rating);
+    }
+
+    fn write_top_position(&self, s: &mut String) {
+        if let Some(top_score_position) = self.top_score_pos {
+            let _ = writeln!(
+                s,
+                ":trophy: __**New top score #{}**__",
+                top_score_position
+            );
+        }
+    }
+
+    fn write_score(&self, s: &mut String) {
+        let score = self.score;
+
+        let _ = writeln!(
+            s,
+            "**{} • +{} • {} • {:.2}%**",
+            score.rank.to_emoji(),
+            self.mods_string(),
+            score.total_score.to_formatted_string(&Locale::en),
+            score.accuracy * 100.0
+        );
+
+        let _ = writeln!(
+            s,
+            "**{:.2}pp** • <t:{}:R>",
+            score.pp.unwrap_or(0.0),
+            score.ended_at.timestamp()
+        );
+    }
+
+    fn write_hits(&self, s: &mut String) {
+        let score = self.score;
+        let max_combo = self.beatmap.max_combo.unwrap_or(0);
+
+        match score.ruleset_id {
+            OsuGameMode::Mania => {
+                let x320 = score.stats.perfect.unwrap_or(0);
+                let x300 = score.stats.great.unwrap_or(0);
+                let x200 = score.stats.good.unwrap_or(0);
+
+                let ma_ratio = x320 as f32 / x300 as f32;
+                let pa_ratio = x300 as f32 / x200 as f32;
+
+                let _ = writeln!(
+                    s,
+                    "[{}/{}/{}/{}/{}/{}] • x{}/{} • MA: {:.2} PA: {:.2}",
+                    score.stats.perfect.unwrap_or(0),
+                    score.stats.great.unwrap_or(0),
+                    score.stats.good.unwrap_or(0),
+                    score.stats.ok.unwrap_or(0),
+                    score.stats.meh.unwrap_or(0),
+                    score.stats.miss.unwrap_or(0),
+                    score.max_combo,
+                    max_combo,
+                    ma_ratio,
+                    pa_ratio
+                );
+            }
+            _ => {
+                let _ = writeln!(
+                    s,
+                    "[{}/{}/{}/{}] • x{}/{}",
+                    score.stats.great.unwrap_or(0),
+                    score.stats.ok.unwrap_or(0),
+                    score.stats.meh.unwrap_or(0),
+                    score.stats.miss.unwrap_or(0),
+                    score.max_combo,
+                    max_combo
+                );
+            }
+        }
+    }
+
+    fn write_difficulty(&self, s: &mut String, diff: &ModdedDifficulty) {
+        match self.score.ruleset_id {
+            OsuGameMode::Fruits => {
+                let _ = write!(
+                    s,
+                    "`AR: {:.2} CS: {:.2} HP: {:.2}",
+                    diff.ar, diff.cs, diff.hp
+                );
+            }
+            OsuGameMode::Mania | OsuGameMode::Taiko => {
+                let _ = write!(s, "`OD: {:.2} HP: {:.2}", diff.od, diff.hp);
+            }
+            OsuGameMode::Osu => {
+                let _ = write!(
+                    s,
+                    "`AR: {:.2} OD: {:.2} CS: {:.2} HP: {:.2}",
+                    diff.ar, diff.od, diff.cs, diff.hp
+                );
+            }
+        }
+
+        let _ = writeln!(s, " BPM: {:.2}`", diff.bpm);
+    }
+
+    fn author(&self) -> EmbedAuthorBuilder {
+        EmbedAuthorBuilder::new(format!(
+            "{}: {:.2}pp (#{})",
+            &self.user.username,
+            self.user.statistics.pp,
+            self.user.statistics.global_rank.unwrap_or(0),
+        ))
+        .url(self.user_url())
+    }
+
+    fn footer(&self) -> EmbedFooterBuilder {
+        EmbedFooterBuilder::new(format!(
+            "Mapper {}",
+            self.beatmap.beatmapset.creator
+        ))
+    }
+}
+
+fn render_default(data: &TrackingScore<'_>) -> Result<Embed> {
+    let mut description_text = String::with_capacity(100);
+
+    data.write_title(&mut description_text);
+    data.write_top_position(&mut description_text);
+    data.write_score(&mut description_text);
+    data.write_hits(&mut description_text);
+
+    let difficulty = data.difficulty()?;
+    data.write_difficulty(&mut description_text, &difficulty);
+
+    let thumb_url =
+        format!("https://b.ppy.sh/thumb/{}l.jpg", data.beatmap.beatmapset_id);
+
+    Ok(EmbedBuilder::new()
+        .color(TRACKING_EMBED_COLOR)
+        .description(description_text)
+        .footer(data.footer())
+        .author(data.author())
+        .thumbnail(ImageSource::url(thumb_url)?)
+        .url(data.user_url())
+        .build())
+}
+
+/// Single line layout for the busy channels
+fn render_compact(data: &TrackingScore<'_>) -> Embed {
+    let (score, beatmap) = (data.score, data.beatmap);
+
+    let mut description_text = String::with_capacity(100);
+
+    let _ = write!(
+        description_text,
+        "{} **[{}]({})** • {} +{} • **{:.2}pp** • {:.2}% • [{} - {} [{}]]({}) [{:.2}★]",
+        score.ruleset_id.to_emoji(),
+        data.user.username,
+        data.user_url(),
+        score.rank.to_emoji(),
+        data.mods_string(),
+        score.pp.unwrap_or(0.0),
+        score.accuracy * 100.0,
+        beatmap.beatmapset.artist,
+        beatmap.beatmapset.title,
+        beatmap.version,
+        data.beatmap_url(),
+        data.attributes.star_rating
+    );
+
+    if let Some(top_score_position) = data.top_score_pos {
+        let _ = write!(description_text, " • :trophy: #{}", top_score_position);
+    }
+
+    EmbedBuilder::new()
+        .color(TRACKING_EMBED_COLOR)
+        .description(description_text)
+        .build()
+}
+
+/// Default layout extended with full beatmap stats and background
+fn render_detailed(data: &TrackingScore<'_>) -> Result<Embed> {
+    let (score, beatmap) = (data.score, data.beatmap);
+
+    let mut description_text = String::with_capacity(100);
+
+    data.write_title(&mut description_text);
+    data.write_top_position(&mut description_text);
+    data.write_score(&mut description_text);
+    data.write_hits(&mut description_text);
+
+    let difficulty = data.difficulty()?;
+
+    // Showing original value only if mods changed it
+    let stat = |modded: f64, original: Option<f32>| -> String {
+        match original {
+            Some(original) if (original as f64 - modded).abs() > 0.01 => {
+                format!("{:.2} ({:.1})", modded, original)
+            }
+            _ => format!("{:.2}", modded),
+        }
+    };
+
+    let mut fields = vec![
+        EmbedFieldBuilder::new(
+            "Stars",
+            format!("{:.2}★", data.attributes.star_rating),
+        )
+        .inline(),
+        EmbedFieldBuilder::new(
+            "Max combo",
+            format!("x{}", beatmap.max_combo.unwrap_or(0)),
+        )
+        .inline(),
+        EmbedFieldBuilder::new("BPM", stat(difficulty.bpm as f64, beatmap.bpm))
+            .inline(),
+    ];
+
+    if matches!(score.ruleset_id, OsuGameMode::Osu | OsuGameMode::Fruits) {
+        fields.push(
+            EmbedFieldBuilder::new("AR", stat(difficulty.ar, beatmap.ar))
+                .inline(),
+        );
+
+        fields.push(
+            EmbedFieldBuilder::new(
+                "CS",
+                stat(difficulty.cs as f64, beatmap.cs),
+            )
+            .inline(),
+        );
+    }
+
+    if score.ruleset_id != OsuGameMode::Fruits {
+        fields.push(
+            EmbedFieldBuilder::new("OD", stat(difficulty.od, beatmap.accuracy))
+                .inline(),
+        );
+    }
+
+    fields.push(
+        EmbedFieldBuilder::new("HP", stat(difficulty.hp as f64, beatmap.drain))
+            .inline(),
+    );
+
+    let cover_url = format!(
+        "https://assets.ppy.sh/beatmaps/{}/covers/cover.jpg",
+        beatmap.beatmapset_id
+    );
+
+    let author = data
+        .author()
+        .icon_url(ImageSource::url(&data.user.avatar_url)?);
+
+    let mut embed = EmbedBuilder::new()
+        .color(TRACKING_EMBED_COLOR)
+        .description(description_text)
+        .footer(data.footer())
+        .author(author)
+        .image(ImageSource::url(cover_url)?)
+        .url(data.user_url());
+
+    for field in fields {
+        embed = embed.field(field);
+    }
+
+    Ok(embed.build())
+}
+
+/// Static score that is used to preview layouts
+pub struct SampleScore {
+    score: OsuScoreLazer,
+    user: OsuUserExtended,
+    beatmap: OsuBeatmap,
+    attributes: OsuBeatmapAttributesContainer,
+}
+
+impl SampleScore {
+    pub fn new() -> Self {
+        let ended_at = DateTime::from_timestamp(1700000000, 0)
+            .expect("timestamp should be valid");
+
+        let score = OsuScoreLazer {
+            ranked: true,
+            preserve: true,
+            beatmap_id: 129891,
+            mods: OsuModsLazer {
+                mods: vec![OsuModLazer {
+                    acronym: "HD".to_owned(),
+                    settings: None,
+                }],
+            },
+            best_id: None,
+            id: 0,
+            rank: OsuGrade::GradeSH,
+            stats: StatisticsLazer {
+                miss: Some(0),
+                meh: Some(0),
+                ok: Some(12),
+                good: None,
+                great: Some(1971),
+                perfect: None,
+                large_tick_hit: None,
+                small_tick_hit: None,
+                large_tick_miss: None,
+                small_tick_miss: None,
+                slider_tail_hit: None,
+            },
+            kind: "solo_score".to_owned(),
+            user_id: 6892711,
+            accuracy: 0.9959,
+            pp: Some(727.27),
+            total_score: 1_048_727,
+            legacy_total_score: 0,
+            max_combo: 2385,
+            ruleset_id: OsuGameMode::Osu,
+            user: None,
+            ended_at,
+        };
+
+        let user = OsuUserExtended {
+            id: 6892711,
+            username: "LoPij".to_owned(),
+            country_code: "BY".to_owned(),
+            cover_url: String::new(),
+            avatar_url: "https://a.ppy.sh/6892711".to_owned(),
+            discord: None,
+            has_supported: false,
+            interests: None,
+            join_date: ended_at,
+            location: None,
+            max_blocks: 0,
+            max_friends: 0,
+            occupation: None,
+            playmode: OsuGameMode::Osu,
+            statistics: OsuUserExtendedStatistics {
+                global_rank: Some(1337),
+                country_rank: Some(1),
+                pp: 10_000.0,
+            },
+        };
+
+        let beatmap = OsuBeatmap {
+            beatmapset_id: 39804,
+            id: 129891,
+            mode: "osu".to_owned(),
+            bpm: Some(222.22),
+            ar: Some(9.0),
+            cs: Some(4.0),
+            drain: Some(5.0),
+            accuracy: Some(8.0),
+            version: "FOUR DIMENSIONS".to_owned(),
+            beatmapset: OsuBeatmapsetCompact {
+                title: "FREEDOM DiVE".to_owned(),
+                artist: "xi".to_owned(),
+                creator: "Nakagawa-Kanon".to_owned(),
+            },
+            max_combo: Some(2385),
+            status: RankStatus::Ranked,
+        };
+
+        let attributes = OsuBeatmapAttributesContainer {
+            max_combo: 2385,
+            star_rating: 7.16,
+        };
+
+        Self {
+            score,
+            user,
+            beatmap,
+            attributes,
+        }
+    }
+
+    pub fn as_tracking_score(&self) -> TrackingScore<'_> {
+        TrackingScore {
+            score: &self.score,
+            user: &self.user,
+            beatmap: &self.beatmap,
+            attributes: &self.attributes,
+            top_score_pos: Some(1),
+        }
+    }
+}
@@ -0,0 +1,516 @@
+use std::{collections::HashSet, fmt::Write};
+
+use eyre::Result;
+use fumo_twilight::message::MessageBuilder;
+use osu_api::models::UserId;
+use serde::{Deserialize, Serialize};
+use twilight_interactions::command::{
+    CommandModel, CommandOption, CreateCommand, CreateOption,
+};
+use twilight_model::{
+    channel::{message::MessageFlags, Attachment},
+    http::attachment::Attachment as HttpAttachment,
+};
+use twilight_util::builder::embed::EmbedBuilder;
+
+use crate::{
+    fumo_context::FumoContext, utils::interaction::InteractionCommand,
+};
+
+use super::{firsts::TrackingFirsts, templates::TrackingStyle};
+
+/// Max size of the imported file in bytes
+const MAX_IMPORT_FILE_SIZE: u64 = 1024 * 1024;
+const MAX_IMPORT_ENTRIES: usize = 500;
+
+/// Max amount of usernames listed per category in the import report
+const MAX_REPORTED_ENTRIES: usize = 30;
+
+#[derive(Serialize, Deserialize, Debug)]
+struct TrackingExportUser {
+    osu_id: Option<i64>,
+    username: Option<String>,
+}
+
+/// Channel settings are optional, so files made by
+/// older exports or by hand are accepted too
+#[derive(Serialize, Deserialize, Debug)]
+struct TrackingExport {
+    style: Option<String>,
+    top_plays: Option<bool>,
+    firsts: Option<String>,
+    digest: Option<bool>,
+    users: Vec<TrackingExportUser>,
+}
+
+/// Channel settings restored by the import
+#[derive(Debug, Default, PartialEq)]
+struct TrackingImportSettings {
+    style: Option<TrackingStyle>,
+    top_plays: Option<bool>,
+    firsts: Option<TrackingFirsts>,
+    digest: Option<bool>,
+}
+
+impl TrackingImportSettings {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Header of the exported CSV, settings are
+/// written before it as `#key,value` lines
+const CSV_HEADER: &str = "osu_id,username";
+
+#[derive(Debug, CommandOption, CreateOption, Clone, Copy)]
+pub enum TrackingExportFormat {
+    #[option(name = "JSON", value = "json")]
+    Json,
+    #[option(name = "CSV", value = "csv")]
+    Csv,
+}
+
+/// Export tracked users of current channel into the file
+#[derive(CommandModel, CreateCommand, Debug)]
+#[command(name = "export")]
+pub struct OsuTrackingExport {
+    /// File format, JSON by default
+    format: Option<TrackingExportFormat>,
+}
+
+impl OsuTrackingExport {
+    pub async fn run(
+        &self,
+        ctx: &FumoContext,
+        cmd: InteractionCommand,
+    ) -> Result<()> {
+        let channel_id: i64 = cmd.channel_id.get().try_into()?;
+
+        let tracked_users =
+            ctx.db.select_osu_tracking_by_channel(channel_id).await?;
+
+        if tracked_users.is_empty() {
+            let msg = MessageBuilder::new()
+                .flags(MessageFlags::EPHEMERAL)
+                .content("No users are tracked on this channel!");
+
+            cmd.response(ctx, &msg).await?;
+
+            return Ok(());
+        }
+
+        let settings = ctx
+            .db
+            .select_osu_tracking_channel_settings(&[channel_id])
+            .await?
+            .remove(&channel_id)
+            .unwrap_or_default();
+
+        let digest = ctx.db.get_osu_tracking_digest(channel_id).await?;
+
+        let export = TrackingExport {
+            style: Some(
+                TrackingStyle::from(settings.style).as_str().to_owned(),
+            ),
+            top_plays: Some(settings.top_plays),
+            firsts: Some(
+                TrackingFirsts::from(settings.firsts).as_str().to_owned(),
+            ),
+            digest,
+            users: tracked_users
+                .iter()
+                .map(|x| TrackingExportUser {
+                    osu_id: Some(x.osu_id),
+                    username: Some(x.osu_username.clone()),
+                })
+                .collect(),
+        };
+
+        let (filename, file) =
+            match self.format.unwrap_or(TrackingExportFormat::Json) {
+                TrackingExportFormat::Json => {
+                    ("tracking.json", serde_json::to_vec_pretty(&export)?)
+                }
+                TrackingExportFormat::Csv => {
+                    ("tracking.csv", export_csv(&export).into_bytes())
+                }
+            };
+
+        let msg = MessageBuilder::new()
+            .flags(MessageFlags::EPHEMERAL)
+            .content(format!("Exported {} users", tracked_users.len()))
+            .attachments([HttpAttachment::from_bytes(
+                filename.to_owned(),
+                file,
+                1,
+            )]);
+
+        cmd.response(ctx, &msg).await?;
+
+        Ok(())
+    }
+}
+
+/// Import tracked users from JSON/CSV file made by export
+#[derive(CommandModel, CreateCommand, Debug)]
+#[command(name = "import")]
+pub struct OsuTrackingImport {
+    /// JSON or CSV file with osu! user ids or usernames
+    file: Attachment,
+}
+
+/// Parses user entry, ids are preferred over usernames
+fn parse_entry(osu_id: Option<&str>, username: Option<&str>) -> Option<UserId> {
+    let clean = |x: &str| x.trim().trim_matches('"').trim().to_owned();
+
+    if let Some(osu_id) = osu_id.map(clean).filter(|x| !x.is_empty()) {
+        return Some(UserId::from(osu_id.as_str()));
+    }
+
+    username
+        .map(clean)
+        .filter(|x| !x.is_empty())
+        .map(UserId::Username)
+}
+
+fn export_csv(export: &TrackingExport) -> String {
+    let mut csv = String::new();
+
+    let settings = [
+        ("style", export.style.clone()),
+        ("top_plays", export.top_plays.map(|x| x.to_string())),
+        ("firsts", export.firsts.clone()),
+        ("digest", export.digest.map(|x| x.to_string())),
+    ];
+
+    for (key, value) in settings {
+        if let Some(value) = value {
+            let _ = writeln!(csv, "#{key},{value}");
+        }
+    }
+
+    let _ = writeln!(csv, "{CSV_HEADER}");
+
+    for user in &export.users {
+        let _ = writeln!(
+            csv,
+            "{},{}",
+            user.osu_id.map(|x| x.to_string()).unwrap_or_default(),
+            user.username.as_deref().unwrap_or_default()
+        );
+    }
+
+    csv
+}
+
+/// Parses JSON made by export, otherwise treats file as CSV.
+/// Single column CSV is accepted as well, so plain list
+/// of usernames or ids works too.
+fn parse_import(
+    filename: &str,
+    bytes: &[u8],
+) -> Result<(TrackingImportSettings, Vec<UserId>)> {
+    if filename.ends_with(".json") {
+        let export: TrackingExport = serde_json::from_slice(bytes)?;
+
+        let settings = TrackingImportSettings {
+            style: export.style.and_then(|x| x.parse().ok()),
+            top_plays: export.top_plays,
+            firsts: export.firsts.and_then(|x| x.parse().ok()),
+            digest: export.digest,
+        };
+
+        let users = export
+            .users
+            .into_iter()
+            .filter_map(|x| match x.osu_id {
+                Some(id) => Some(UserId::Id(id)),
+                None => parse_entry(None, x.username.as_deref()),
+            })
+            .collect();
+
+        return Ok((settings, users));
+    }
+
+    let text = std::str::from_utf8(bytes)?;
+
+    let mut settings = TrackingImportSettings::default();
+    let mut users = Vec::new();
+    let mut is_first_row = true;
+
+    for line in text.lines().map(str::trim).filter(|x| !x.is_empty()) {
+        if let Some(setting) = line.strip_prefix('#') {
+            let Some((key, value)) = setting.split_once(',') else {
+                continue;
+            };
+
+            let value = value.trim();
+
+            match key.trim() {
+                "style" => settings.style = value.parse().ok(),
+                "top_plays" => settings.top_plays = value.parse().ok(),
+                "firsts" => settings.firsts = value.parse().ok(),
+                "digest" => settings.digest = value.parse().ok(),
+                _ => {}
+            }
+
+            continue;
+        }
+
+        if std::mem::take(&mut is_first_row)
+            && line.eq_ignore_ascii_case(CSV_HEADER)
+        {
+            continue;
+        }
+
+        let mut fields = line.split(',');
+        let first = fields.next();
+        let second = fields.next();
+
+        let user = match second {
+            Some(_) => parse_entry(first, second),
+            None => parse_entry(None, first),
+        };
+
+        users.extend(user);
+    }
+
+    Ok((settings, users))
+}
+
+impl OsuTrackingImport {
+    pub async fn run(
+        &self,
+        ctx: &FumoContext,
+        cmd: InteractionCommand,
+    ) -> Result<()> {
+        let channel_id: i64 = cmd.channel_id.get().try_into()?;
+
+        if self.file.size > MAX_IMPORT_FILE_SIZE {
+            let msg = MessageBuilder::new()
+                .flags(MessageFlags::EPHEMERAL)
+                .content("File is too big!");
+
+            cmd.response(ctx, &msg).await?;
+            return Ok(());
+        }
+
+        cmd.defer(ctx).await?;
+
+        let mut msg = MessageBuilder::new();
+
+        let bytes = reqwest::get(&self.file.url).await?.bytes().await?;
+
+        let (settings, mut entries) =
+            match parse_import(&self.file.filename, &bytes) {
+                Ok(v) => v,
+                Err(e) => {
+                    msg = msg.content(format!("Failed to parse file: {e}"));
+                    cmd.update(ctx, &msg).await?;
+                    return Ok(());
+                }
+            };
+
+        // Removing duplicates while keeping order
+        let mut seen = HashSet::with_capacity(entries.len());
+        entries.retain(|x| seen.insert(x.to_string().to_lowercase()));
+
+        if entries.is_empty() || entries.len() > MAX_IMPORT_ENTRIES {
+            msg = msg.content(format!(
+                "File should contain from 1 to {} users",
+                MAX_IMPORT_ENTRIES
+            ));
+            cmd.update(ctx, &msg).await?;
+            return Ok(());
+        }
+
+        let users = ctx.osu_api.lookup_users(&entries).await?.users;
+
+        ctx.db.add_discord_channel(channel_id).await?;
+        ctx.db
+            .enable_osu_tracking_channel(
+                channel_id,
+                cmd.guild_id.map(|x| x.get() as i64),
+            )
+            .await?;
+
+        if let Some(style) = settings.style {
+            ctx.db
+                .set_osu_tracking_style(channel_id, style.as_i16())
+                .await?;
+        }
+
+        if settings.top_plays.is_some() || settings.firsts.is_some() {
+            ctx.db
+                .set_osu_tracking_mode(
+                    channel_id,
+                    settings.top_plays,
+                    settings.firsts.map(|x| x.as_i16()),
+                )
+                .await?;
+        }
+
+        // Digest is scheduled per server, so it
+        // can't be restored outside of the servers
+        if let Some((digest, guild_id)) = settings.digest.zip(cmd.guild_id) {
+            ctx.db
+                .set_osu_tracking_digest(
+                    channel_id,
+                    guild_id.get() as i64,
+                    digest,
+                )
+                .await?;
+        }
+
+        let mut found = Vec::with_capacity(entries.len());
+        let mut unknown = Vec::new();
+
+        for entry in &entries {
+            let user = users.iter().find(|x| match entry {
+                UserId::Id(id) => x.id == *id,
+                UserId::Username(name) => x.username.eq_ignore_ascii_case(name),
+            });
+
+            match user {
+                Some(user) => found.push(user),
+                None => unknown.push(entry.to_string()),
+            }
+        }
+
+        let (osu_ids, usernames): (Vec<i64>, Vec<String>) =
+            found.iter().map(|x| (x.id, x.username.clone())).unzip();
+
+        let mut inserted: HashSet<i64> = ctx
+            .db
+            .add_osu_tracking_bulk(channel_id, &osu_ids, &usernames)
+            .await?
+            .into_iter()
+            .collect();
+
+        let mut added = Vec::new();
+        let mut skipped = Vec::new();
+
+        // Same user can be listed by id and by username
+        for user in found {
+            if inserted.remove(&user.id) {
+                added.push(user.username.clone());
+            } else {
+                skipped.push(user.username.clone());
+            }
+        }
+
+        let mut description = String::with_capacity(256);
+
+        for (title, list) in [
+            ("Added", &added),
+            ("Skipped", &skipped),
+            ("Unknown", &unknown),
+        ] {
+            let _ = writeln!(description, "**{}: {}**", title, list.len());
+
+            if list.is_empty() {
+                continue;
+            }
+
+            let _ = write!(
+                description,
+                "`{}`",
+                list.iter()
+                    .take(MAX_REPORTED_ENTRIES)
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+
+            if list.len() > MAX_REPORTED_ENTRIES {
+                let _ = write!(
+                    description,
+                    " and {} more",
+                    list.len() - MAX_REPORTED_ENTRIES
+                );
+            }
+
+            let _ = writeln!(description);
+        }
+
+        if !settings.is_empty() {
+            let _ = writeln!(description, "Channel settings were restored");
+        }
+
+        let embed = EmbedBuilder::new()
+            .color(0xbd49ff)
+            .title("Tracking import")
+            .description(description)
+            .build();
+
+        msg = msg.embed(embed);
+
+        cmd.update(ctx, &msg).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv() {
+        let csv = "#style,compact\n#firsts,country\nosu_id,username\n\
+            2,peppy\n,osu_id\nosu_id\n";
+
+        let (settings, users) = parse_import("tracking.csv", csv.as_bytes())
+            .expect("csv should be parsed");
+
+        let expected_settings = TrackingImportSettings {
+            style: Some(TrackingStyle::Compact),
+            firsts: Some(TrackingFirsts::Country),
+            ..Default::default()
+        };
+
+        assert_eq!(settings, expected_settings);
+
+        // Only the first row is treated as header
+        let expected_users = [
+            UserId::Id(2),
+            UserId::Username(String::from("osu_id")),
+            UserId::Username(String::from("osu_id")),
+        ];
+
+        assert_eq!(users.len(), expected_users.len());
+
+        for (user, expected) in users.iter().zip(&expected_users) {
+            assert_eq!(user.to_string(), expected.to_string());
+        }
+    }
+
+    #[test]
+    fn test_export_roundtrip() {
+        let export = TrackingExport {
+            style: Some(String::from("detailed")),
+            top_plays: Some(false),
+            firsts: Some(String::from("global")),
+            digest: Some(true),
+            users: vec![TrackingExportUser {
+                osu_id: Some(2),
+                username: Some(String::from("peppy")),
+            }],
+        };
+
+        let (settings, users) =
+            parse_import("tracking.csv", export_csv(&export).as_bytes())
+                .expect("csv should be parsed");
+
+        let expected = TrackingImportSettings {
+            style: Some(TrackingStyle::Detailed),
+            top_plays: Some(false),
+            firsts: Some(TrackingFirsts::Global),
+            digest: Some(true),
+        };
+
+        assert_eq!(settings, expected);
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].to_string(), "2");
+    }
+}
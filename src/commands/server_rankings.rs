@@ -0,0 +1,243 @@
+use std::{cmp::Ordering, fmt::Write, time::Duration};
+
+use eyre::Result;
+use fumo_macro::listing;
+use fumo_twilight::message::MessageBuilder;
+use num_format::{Locale, ToFormattedString};
+use osu_api::models::{OsuGameMode, OsuUserRulesetStatistics};
+use tokio_stream::StreamExt;
+use twilight_interactions::command::{
+    CommandModel, CommandOption, CreateCommand, CreateOption,
+};
+use twilight_model::application::interaction::{Interaction, InteractionData};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFooterBuilder};
+
+use crate::{
+    commands::GameModeOption,
+    components::listing::ListingTrait,
+    fumo_context::FumoContext,
+    utils::{
+        interaction::{InteractionCommand, InteractionComponent},
+        static_components::pages_components,
+    },
+};
+
+#[derive(Debug, CommandOption, CreateOption, Clone, Copy)]
+pub enum ServerRankingsSorting {
+    #[option(name = "pp", value = "pp")]
+    Pp,
+    #[option(name = "Accuracy", value = "accuracy")]
+    Accuracy,
+    #[option(name = "Playcount", value = "playcount")]
+    Playcount,
+}
+
+impl ServerRankingsSorting {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Pp => "pp",
+            Self::Accuracy => "accuracy",
+            Self::Playcount => "playcount",
+        }
+    }
+
+    fn compare(
+        &self,
+        a: &OsuUserRulesetStatistics,
+        b: &OsuUserRulesetStatistics,
+    ) -> Ordering {
+        match self {
+            Self::Pp => b.pp.partial_cmp(&a.pp).unwrap_or(Ordering::Equal),
+            Self::Accuracy => b
+                .hit_accuracy
+                .partial_cmp(&a.hit_accuracy)
+                .unwrap_or(Ordering::Equal),
+            Self::Playcount => b.play_count.cmp(&a.play_count),
+        }
+    }
+}
+
+struct ServerRankingsEntry {
+    osu_id: i64,
+    username: String,
+    stats: OsuUserRulesetStatistics,
+}
+
+#[listing]
+struct ServerRankingsListing {
+    entries: Vec<ServerRankingsEntry>,
+    mode: OsuGameMode,
+    sorting: ServerRankingsSorting,
+}
+
+impl ListingTrait for ServerRankingsListing {
+    async fn handle_interaction_component(
+        &mut self,
+        ctx: &FumoContext,
+        component: &InteractionComponent,
+    ) {
+        let _ = component.defer(ctx).await;
+
+        if let Some(data) = &component.data {
+            match data.custom_id.as_ref() {
+                "B1" => self.previous_page(),
+                "B2" => self.next_page(),
+                _ => {}
+            }
+        }
+    }
+
+    fn update(&mut self) {
+        let footer = EmbedFooterBuilder::new(format!(
+            "Sorted by {} • Page {}/{}",
+            self.sorting.name(),
+            self.current_page,
+            self.max_pages
+        ));
+
+        let start_at = (self.current_page - 1) * self.entries_per_page;
+
+        let mut description = String::with_capacity(1024);
+
+        for (index, entry) in self
+            .entries
+            .iter()
+            .enumerate()
+            .skip(start_at)
+            .take(self.entries_per_page)
+        {
+            let _ = write!(
+                description,
+                "{}. [{}](https://osu.ppy.sh/u/{}) • `{:.2}pp` • {:.2}% • {} plays",
+                index + 1,
+                entry.username,
+                entry.osu_id,
+                entry.stats.pp,
+                entry.stats.hit_accuracy,
+                entry.stats.play_count.to_formatted_string(&Locale::en)
+            );
+
+            if let Some(rank) = entry.stats.global_rank {
+                let _ = write!(
+                    description,
+                    " • #{}",
+                    rank.to_formatted_string(&Locale::en)
+                );
+            }
+
+            let _ = writeln!(description);
+        }
+
+        let embed = EmbedBuilder::new()
+            .color(0xbd49ff)
+            .title(format!("{} Server rankings", self.mode.to_emoji()))
+            .description(description)
+            .footer(footer)
+            .build();
+
+        self.embed = Some(embed);
+    }
+}
+
+/// Rank linked osu! accounts of the server members
+#[derive(CommandModel, CreateCommand, Debug)]
+#[command(name = "server-rankings")]
+pub struct OsuServerRankings {
+    /// Ranking criteria, pp by default
+    sort: Option<ServerRankingsSorting>,
+
+    /// Ruleset, osu! by default
+    mode: Option<GameModeOption>,
+}
+
+impl OsuServerRankings {
+    pub async fn run(
+        &self,
+        ctx: &FumoContext,
+        cmd: InteractionCommand,
+    ) -> Result<()> {
+        let Some(guild_id) = cmd.guild_id else {
+            let msg = MessageBuilder::new()
+                .content("This command is available only on servers!");
+            cmd.response(ctx, &msg).await?;
+            return Ok(());
+        };
+
+        cmd.defer(ctx).await?;
+
+        let mode: OsuGameMode =
+            self.mode.map(Into::into).unwrap_or(OsuGameMode::Osu);
+        let sorting = self.sort.unwrap_or(ServerRankingsSorting::Pp);
+
+        let osu_ids =
+            ctx.db.select_guild_osu_users(guild_id.get() as i64).await?;
+
+        let users = ctx.osu_api.get_users(&osu_ids).await?.users;
+
+        let mut entries: Vec<ServerRankingsEntry> = users
+            .into_iter()
+            .filter_map(|user| {
+                let stats = user.statistics_rulesets.get(mode)?.clone();
+
+                Some(ServerRankingsEntry {
+                    osu_id: user.id,
+                    username: user.username,
+                    stats,
+                })
+            })
+            .collect();
+
+        if entries.is_empty() {
+            let msg = MessageBuilder::new().content(
+                "No linked osu! accounts found among members of this server",
+            );
+            cmd.update(ctx, &msg).await?;
+            return Ok(());
+        }
+
+        entries.sort_by(|a, b| sorting.compare(&a.stats, &b.stats));
+
+        let entries_len = entries.len();
+
+        let mut listing = ServerRankingsListing::new(entries, mode, sorting)
+            .calculate_pages(entries_len, 10);
+
+        listing.update();
+
+        let mut msg_builder = MessageBuilder::new()
+            .embed(
+                listing
+                    .embed
+                    .as_ref()
+                    .expect("embed should be present")
+                    .clone(),
+            )
+            .components(pages_components());
+
+        let msg = cmd.update(ctx, &msg_builder).await?.model().await?;
+        let msg_stream = component_stream!(ctx, msg);
+
+        tokio::pin!(msg_stream);
+
+        while let Some(Ok(component)) = msg_stream.next().await {
+            listing.handle_interaction_component(ctx, &component).await;
+
+            listing.update();
+
+            msg_builder = msg_builder.embed(
+                listing
+                    .embed
+                    .as_ref()
+                    .expect("embed should be present")
+                    .clone(),
+            );
+
+            cmd.update(ctx, &msg_builder).await?;
+        }
+
+        msg_builder.clear_components();
+        cmd.update(ctx, &msg_builder).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,330 @@
+use std::{sync::Arc, time::Duration};
+
+use eyre::Result;
+use fumo_database::osu::{leaderboard::OsuCountryScore, snipes::CountryFirst};
+use fumo_twilight::message::MessageBuilder;
+use osu_api::models::{OsuGameMode, OsuGrade};
+use tokio::time::MissedTickBehavior;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+    channel::message::MessageFlags, guild::Permissions, id::Id,
+};
+use twilight_util::builder::embed::{
+    image_source::ImageSource, EmbedAuthorBuilder, EmbedBuilder,
+};
+
+use crate::{
+    fumo_context::FumoContext,
+    leaderboard_source::{
+        fetch_remote_country_leaderboard, mods_label, LeaderboardQuery,
+    },
+    utils::{countries::country_flag, interaction::InteractionCommand},
+};
+
+/// How often stored country #1s are re-checked
+const SNIPES_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Amount of country #1s re-checked on every tick
+const SNIPES_BATCH_SIZE: i64 = 10;
+
+fn country_first(score: &OsuCountryScore) -> CountryFirst {
+    CountryFirst {
+        beatmap_id: score.beatmap_id,
+        country: score.country.clone(),
+        mode: score.mode,
+        osu_id: score.osu_id,
+        osu_username: score.osu_username.clone(),
+        score_id: score.score_id,
+        total_score: score.total_score,
+    }
+}
+
+/// Posts "X sniped Y on MAP" to the snipe channels of the country
+async fn announce_snipe(
+    ctx: &FumoContext,
+    previous: &CountryFirst,
+    score: &OsuCountryScore,
+) -> Result<()> {
+    let channels = ctx.db.select_snipe_channels(&score.country).await?;
+
+    if channels.is_empty() {
+        return Ok(());
+    }
+
+    let beatmap = ctx.osu_api.get_beatmap(score.beatmap_id).await?;
+
+    let grade = score.grade.parse::<OsuGrade>().unwrap_or(OsuGrade::GradeD);
+    let mods = mods_label(&score.mods);
+
+    let mut description = format!(
+        "**[{}](https://osu.ppy.sh/u/{})** sniped **[{}](https://osu.ppy.sh/u/{})**\n\
+        {} +**{}** • {:.2}%",
+        score.osu_username,
+        score.osu_id,
+        previous.osu_username,
+        previous.osu_id,
+        grade.to_emoji(),
+        mods,
+        score.accuracy * 100.0,
+    );
+
+    if let Some(pp) = score.pp {
+        description.push_str(&format!(" • {pp:.2}pp"));
+    }
+
+    let author = EmbedAuthorBuilder::new(format!(
+        "{} New country #1 • {}",
+        country_flag(&score.country),
+        beatmap.metadata()
+    ))
+    .url(format!("https://osu.ppy.sh/b/{}", beatmap.id))
+    .build();
+
+    let embed = EmbedBuilder::new()
+        .color(0xbd49ff)
+        .author(author)
+        .thumbnail(ImageSource::url(format!(
+            "https://assets.ppy.sh/beatmaps/{}/covers/list.jpg",
+            beatmap.beatmapset_id
+        ))?)
+        .description(description)
+        .build();
+
+    for channel_id in channels {
+        let res = ctx
+            .http
+            .create_message(Id::new(channel_id as u64))
+            .embeds(std::slice::from_ref(&embed))?
+            .await;
+
+        if let Err(e) = res {
+            tracing::warn!(
+                channel_id = channel_id,
+                "Failed to send country snipe: {e}"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Fetches actual leaderboard and returns its #1,
+/// `None` if leaderboard is unavailable
+async fn fetch_country_first(
+    ctx: &FumoContext,
+    beatmap_id: i64,
+    country: &str,
+    mode: i16,
+) -> Result<Option<OsuCountryScore>> {
+    let Ok(mode) = OsuGameMode::try_from(mode as u8) else {
+        return Ok(None);
+    };
+
+    let query = LeaderboardQuery {
+        beatmap_id: beatmap_id as i32,
+        country,
+        mode,
+        mods: None,
+        best_per_player: true,
+    };
+
+    let Some((scores, _)) =
+        fetch_remote_country_leaderboard(ctx, &query).await?
+    else {
+        return Ok(None);
+    };
+
+    Ok(scores.into_iter().max_by_key(|x| x.total_score))
+}
+
+/// Checks stored feed scores against country #1s. New #1s of linked and
+/// tracked players start being watched from here, already held ones are
+/// seeded from the fetched leaderboards by the worker
+pub async fn check_feed_snipes(
+    ctx: &FumoContext,
+    scores: &[OsuCountryScore],
+) -> Result<()> {
+    if scores.is_empty() {
+        return Ok(());
+    }
+
+    let mut beatmap_ids: Vec<i64> =
+        scores.iter().map(|x| x.beatmap_id).collect();
+    beatmap_ids.sort_unstable();
+    beatmap_ids.dedup();
+
+    let mut osu_ids: Vec<i64> = scores.iter().map(|x| x.osu_id).collect();
+    osu_ids.sort_unstable();
+    osu_ids.dedup();
+
+    let mut firsts = ctx.db.select_country_firsts(&beatmap_ids).await?;
+    let players = ctx.db.select_snipe_players(&osu_ids).await?;
+
+    for score in scores {
+        let key = (score.beatmap_id, score.country.clone(), score.mode);
+
+        match firsts.get(&key) {
+            Some(first) if score.total_score > first.total_score => {
+                if first.osu_id != score.osu_id {
+                    announce_snipe(ctx, first, score).await?;
+                }
+            }
+            Some(_) => continue,
+            None if players.contains(&score.osu_id) => {
+                let top = fetch_country_first(
+                    ctx,
+                    score.beatmap_id,
+                    &score.country,
+                    score.mode,
+                )
+                .await?;
+
+                let is_first = top.is_some_and(|x| {
+                    x.osu_id == score.osu_id && x.mode == score.mode
+                });
+
+                if !is_first {
+                    continue;
+                }
+            }
+            None => continue,
+        }
+
+        let first = country_first(score);
+        ctx.db.set_country_first(&first).await?;
+        firsts.insert(key, first);
+    }
+
+    Ok(())
+}
+
+/// Re-checks country leaderboard of the stored #1
+async fn check_first(ctx: &FumoContext, first: &CountryFirst) -> Result<()> {
+    let top =
+        fetch_country_first(ctx, first.beatmap_id, &first.country, first.mode)
+            .await?;
+
+    match top {
+        Some(top)
+            if top.mode == first.mode
+                && top.osu_id != first.osu_id
+                && top.total_score > first.total_score =>
+        {
+            announce_snipe(ctx, first, &top).await?;
+            ctx.db.set_country_first(&country_first(&top)).await?;
+        }
+        _ => ctx.db.touch_country_first(first).await?,
+    }
+
+    Ok(())
+}
+
+/// Re-checks country leaderboards of the stored #1s,
+/// catches snipes that weren't seen in the scores feed
+async fn check_outdated_firsts(ctx: &FumoContext) -> Result<()> {
+    let seeded = ctx.db.seed_country_firsts().await?;
+
+    if seeded > 0 {
+        tracing::info!(amount = seeded, "Seeded country firsts");
+    }
+
+    let firsts = ctx
+        .db
+        .select_outdated_country_firsts(SNIPES_BATCH_SIZE)
+        .await?;
+
+    for first in firsts {
+        if let Err(e) = check_first(ctx, &first).await {
+            tracing::warn!(
+                beatmap_id = first.beatmap_id,
+                country = first.country,
+                "Failed to check country first: {e}"
+            );
+
+            // Otherwise the same broken beatmap is picked up every time
+            ctx.db.touch_country_first(&first).await?;
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn snipes_worker(ctx: Arc<FumoContext>) {
+    tracing::info!("Starting country snipes worker!");
+
+    let mut interval = tokio::time::interval(SNIPES_INTERVAL);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        if let Err(e) = check_outdated_firsts(&ctx).await {
+            tracing::error!("Failed to check country firsts: {e}");
+        }
+    }
+}
+
+/// Post country #1 snipes of the server country on current channel
+#[derive(CommandModel, CreateCommand, Debug)]
+#[command(name = "server-snipes")]
+pub struct OsuServerSnipes {
+    /// Whether snipes should be posted on this channel
+    enabled: bool,
+}
+
+impl OsuServerSnipes {
+    pub async fn run(
+        &self,
+        ctx: &FumoContext,
+        cmd: InteractionCommand,
+    ) -> Result<()> {
+        let mut msg = MessageBuilder::new().flags(MessageFlags::EPHEMERAL);
+
+        let Some(guild_id) = cmd.guild_id else {
+            msg = msg.content("This command is available only on servers!");
+            cmd.response(ctx, &msg).await?;
+            return Ok(());
+        };
+
+        let can_manage = cmd
+            .member
+            .as_ref()
+            .and_then(|x| x.permissions)
+            .is_some_and(|x| x.contains(Permissions::MANAGE_GUILD));
+
+        if !can_manage {
+            msg =
+                msg.content("You need `Manage Server` permission to do that!");
+            cmd.response(ctx, &msg).await?;
+            return Ok(());
+        }
+
+        let guild_id = guild_id.get() as i64;
+
+        let Some(country) = ctx.db.get_guild_country(guild_id).await? else {
+            msg = msg.content(
+                "Please set server country first with `/osu server-country`",
+            );
+            cmd.response(ctx, &msg).await?;
+            return Ok(());
+        };
+
+        let channel_id = self.enabled.then_some(cmd.channel_id.get() as i64);
+
+        ctx.db.set_guild_snipe_channel(guild_id, channel_id).await?;
+
+        msg = if self.enabled {
+            msg.content(format!(
+                "Country #1 snipes of {} {} are going to be posted here",
+                country_flag(&country),
+                country
+            ))
+        } else {
+            msg.content("Country #1 snipes are disabled on this server")
+        };
+
+        cmd.response(ctx, &msg).await?;
+
+        Ok(())
+    }
+}
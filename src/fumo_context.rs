@@ -1,13 +1,15 @@
 use crate::{
+    leaderboard_source::{LeaderboardHealth, UserScoresCache},
     stats::{BotMetrics, BotStats},
     twitch_api::TwitchApi,
+    utils::searching::KnownBots,
 };
 use fumo_database::Database;
 use osu_api::OsuApi;
 use std::io::Write;
 
 use serde::{Deserialize, Serialize};
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Notify};
 use twilight_gateway::{
     stream, Config, ConfigBuilder, EventTypeFlags, Intents, Shard, ShardId,
 };
@@ -17,7 +19,7 @@ use twilight_standby::Standby;
 
 use std::{
     collections::HashMap, env, fs::File, io::read_to_string, path::PathBuf,
-    sync::Arc,
+    sync::Arc, time::Instant,
 };
 
 use eyre::Result;
@@ -52,6 +54,22 @@ pub struct FumoContext {
     /// and value is if streamer is online or not
     pub twitch_checker_list: Mutex<HashMap<i64, bool>>,
 
+    /// Wakes up osu tracking delivery worker
+    /// whenever new messages are enqueued
+    pub osu_tracking_delivery: Notify,
+
+    /// Health of country leaderboard sources
+    pub leaderboard_health: LeaderboardHealth,
+
+    /// Best scores of the players used by server leaderboards
+    pub user_scores_cache: UserScoresCache,
+
+    /// Guild members that were recently stored, `(user_id, guild_id)`
+    pub guild_members_seen: Mutex<HashMap<(i64, i64), Instant>>,
+
+    /// Bots which embeds are searched for beatmap links first
+    pub known_bots: KnownBots,
+
     pub db: Database,
     pub stats: BotMetrics,
     pub http: Arc<Client>,
@@ -82,12 +100,15 @@ impl FumoContext {
             env::var("CLIENT_ID")?.parse()?,
             env::var("CLIENT_SECRET")?.as_str(),
             env::var("OSU_SESSION")?.as_str(),
-            env::var("FALLBACK_API")?.as_str(),
-            env::var("FALLBACK_API_KEY")?.as_str(),
+            env::var("FALLBACK_API").unwrap_or_default().as_str(),
+            env::var("FALLBACK_API_KEY").unwrap_or_default().as_str(),
             true,
         )
         .await?;
 
+        let known_bots =
+            KnownBots::new(env::var("KNOWN_BOTS").unwrap_or_default().as_str());
+
         let db = Database::init(env::var("DATABASE_URL")?.as_str()).await?;
 
         let http = Client::builder()
@@ -156,6 +177,11 @@ impl FumoContext {
             standby,
             stats,
             twitch_checker_list: Mutex::new(HashMap::new()),
+            osu_tracking_delivery: Notify::new(),
+            leaderboard_health: LeaderboardHealth::default(),
+            user_scores_cache: UserScoresCache::default(),
+            guild_members_seen: Mutex::new(HashMap::new()),
+            known_bots,
             state: Mutex::new(state),
         };
 
@@ -8,12 +8,17 @@ use crate::{
 use tokio_stream::StreamExt;
 use twilight_gateway::stream::ShardEventStream;
 
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use twilight_gateway::{Event, Shard};
 use twilight_model::application::{
-    command::{Command, CommandType},
-    interaction::{Interaction, InteractionData},
+    command::{
+        Command, CommandOptionChoice, CommandOptionChoiceValue, CommandType,
+    },
+    interaction::{Interaction, InteractionData, InteractionType},
 };
 use twilight_util::builder::command::{
     CommandBuilder, StringBuilder, SubCommandBuilder,
@@ -21,11 +26,58 @@ use twilight_util::builder::command::{
 
 use crate::commands::{country_leaderboard, twitch};
 
-use crate::utils::interaction::InteractionCommand;
+use crate::utils::{
+    countries::search_countries, interaction::InteractionCommand,
+};
 
 use eyre::Result;
 
+/// How often membership of the same user is refreshed
+const MEMBER_REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Bot doesn't receive guild members, so linked users are remembered
+/// whenever they use commands on the server. It's done in the background
+/// to not delay the response to the interaction
+fn remember_guild_member(ctx: &Arc<FumoContext>, cmd: &InteractionCommand) {
+    let (Some(guild_id), Some(user_id)) = (cmd.guild_id, cmd.user_id()) else {
+        return;
+    };
+
+    let key = (user_id.get() as i64, guild_id.get() as i64);
+    let ctx = Arc::clone(ctx);
+
+    tokio::spawn(async move {
+        {
+            let mut seen = ctx.guild_members_seen.lock().await;
+
+            if seen
+                .get(&key)
+                .is_some_and(|x| x.elapsed() < MEMBER_REFRESH_INTERVAL)
+            {
+                return;
+            }
+
+            seen.retain(|_, x| x.elapsed() < MEMBER_REFRESH_INTERVAL);
+        }
+
+        // Users without linked account are not stored, so
+        // they are checked again once they link the account
+        match ctx.db.add_osu_user_guild(key.0, key.1).await {
+            Ok(0) => {}
+            Ok(_) => {
+                ctx.guild_members_seen
+                    .lock()
+                    .await
+                    .insert(key, Instant::now());
+            }
+            Err(e) => tracing::warn!("Failed to add osu user guild: {e}"),
+        }
+    });
+}
+
 async fn handle_commands(ctx: Arc<FumoContext>, cmd: InteractionCommand) {
+    remember_guild_member(&ctx, &cmd);
+
     let res = match cmd.data.name.as_str() {
         "Leaderboard" => country_leaderboard::run(&ctx, cmd).await,
         "leaderboard" => LeaderboardCommand::handle(&ctx, cmd).await,
@@ -47,6 +99,30 @@ async fn handle_commands(ctx: Arc<FumoContext>, cmd: InteractionCommand) {
     }
 }
 
+/// Suggestions are based only on the option name,
+/// so same options share completions across commands
+async fn handle_autocomplete(ctx: Arc<FumoContext>, cmd: InteractionCommand) {
+    let Some((option, input)) = cmd.focused_option() else {
+        return;
+    };
+
+    let choices: Vec<CommandOptionChoice> = match option {
+        "country" => search_countries(input)
+            .into_iter()
+            .map(|(code, name)| CommandOptionChoice {
+                name: format!("{name} ({code})"),
+                name_localizations: None,
+                value: CommandOptionChoiceValue::String(code.to_owned()),
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    if let Err(e) = cmd.autocomplete(&ctx, choices).await {
+        tracing::error!("Failed to respond to autocomplete: {e}");
+    }
+}
+
 pub async fn event_loop(
     ctx: Arc<FumoContext>,
     shards: &mut [Shard],
@@ -128,8 +204,8 @@ async fn handle_interactions(
     let Interaction {
         channel,
         data,
-        // guild_id,
-        // kind,
+        guild_id,
+        kind,
         id,
         token,
         member,
@@ -143,14 +219,18 @@ async fn handle_interactions(
                 channel_id: channel.unwrap().id,
                 data: *data,
                 // kind,
-                // guild_id,
+                guild_id,
                 id,
                 token,
                 member,
                 user,
             };
 
-            handle_commands(ctx, cmd).await;
+            if kind == InteractionType::ApplicationCommandAutocomplete {
+                handle_autocomplete(ctx, cmd).await;
+            } else {
+                handle_commands(ctx, cmd).await;
+            }
         }
         Some(InteractionData::MessageComponent(_)) => {}
         Some(InteractionData::ModalSubmit(_)) => {}
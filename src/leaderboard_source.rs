@@ -0,0 +1,677 @@
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use eyre::Result;
+use fumo_database::osu::leaderboard::OsuCountryScore;
+use futures_util::{stream, StreamExt};
+use osu_api::{
+    error::OsuApiError,
+    fallback_models::FallbackBeatmapScores,
+    models::{
+        osu_leaderboard::{OsuLeaderboardLazer, OsuScoreLazer},
+        OsuGameMode, OsuScore, UserId,
+    },
+};
+use tokio::sync::Mutex;
+
+use crate::fumo_context::FumoContext;
+
+/// How long best scores of the players are cached
+const USER_SCORES_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Amount of players which best scores are requested at the same time
+const USER_SCORES_CONCURRENCY: usize = 8;
+
+/// Players leaderboard is limited to keep response time reasonable
+const MAX_LEADERBOARD_PLAYERS: usize = 100;
+
+/// Source is skipped after this amount of failures in a row
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+
+/// How long unhealthy source is skipped before trying it again
+const UNHEALTHY_COOLDOWN: Duration = Duration::from_secs(5 * 60);
+
+/// How long fetched leaderboard is served from the database,
+/// feed keeps improving stored scores in the meantime
+const LOCAL_LEADERBOARD_TTL: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LeaderboardSourceKind {
+    Local,
+    Hidden,
+    Fallback,
+    /// Best scores of the players fetched one by one
+    UserScores,
+}
+
+impl Display for LeaderboardSourceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Local => write!(f, "local"),
+            Self::Hidden => write!(f, "osu!"),
+            Self::Fallback => write!(f, "fallback api"),
+            Self::UserScores => write!(f, "osu! api"),
+        }
+    }
+}
+
+/// Country leaderboard request
+pub struct LeaderboardQuery<'a> {
+    pub beatmap_id: i32,
+    pub country: &'a str,
+    /// Ruleset, converts are requested if it differs from the beatmap one
+    pub mode: OsuGameMode,
+    /// Normalized mods, see [`normalize_mods`]
+    pub mods: Option<&'a str>,
+    /// Only the best score of every player, otherwise best
+    /// score of every mods combination if source supports that
+    pub best_per_player: bool,
+}
+
+/// Something that is able to provide country leaderboards
+pub trait LeaderboardSource {
+    fn kind(&self) -> LeaderboardSourceKind;
+
+    /// Returns `None` if source can't serve this query,
+    /// which isn't considered as a failure
+    async fn fetch(
+        &self,
+        ctx: &FumoContext,
+        query: &LeaderboardQuery<'_>,
+    ) -> Result<Option<Vec<OsuCountryScore>>>;
+}
+
+/// Recently fetched leaderboards, improved by the scores feed
+pub struct LocalSource;
+
+impl LeaderboardSource for LocalSource {
+    fn kind(&self) -> LeaderboardSourceKind {
+        LeaderboardSourceKind::Local
+    }
+
+    async fn fetch(
+        &self,
+        ctx: &FumoContext,
+        query: &LeaderboardQuery<'_>,
+    ) -> Result<Option<Vec<OsuCountryScore>>> {
+        // Feed scores alone are just a part of the leaderboard
+        let is_fresh = ctx
+            .db
+            .is_osu_country_leaderboard_fresh(
+                query.beatmap_id as i64,
+                query.country,
+                query.mode.as_u8().into(),
+                query.mods,
+                LOCAL_LEADERBOARD_TTL.as_secs() as i64,
+            )
+            .await?;
+
+        if !is_fresh {
+            return Ok(None);
+        }
+
+        let scores = ctx
+            .db
+            .select_osu_country_scores(
+                query.beatmap_id as i64,
+                query.country,
+                query.mode.as_u8().into(),
+                query.mods,
+                query.best_per_player,
+            )
+            .await?;
+
+        if scores.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(scores))
+    }
+}
+
+/// Website leaderboard, country scope works
+/// only for the country of the session owner
+pub struct HiddenSource;
+
+impl LeaderboardSource for HiddenSource {
+    fn kind(&self) -> LeaderboardSourceKind {
+        LeaderboardSourceKind::Hidden
+    }
+
+    async fn fetch(
+        &self,
+        ctx: &FumoContext,
+        query: &LeaderboardQuery<'_>,
+    ) -> Result<Option<Vec<OsuCountryScore>>> {
+        let OsuLeaderboardLazer { scores } = ctx
+            .osu_api
+            .get_leaderboard_hidden(
+                query.beatmap_id,
+                true,
+                query.mods,
+                Some(query.mode),
+            )
+            .await?;
+
+        let Some(session_country) = scores
+            .iter()
+            .find_map(|x| x.user.as_ref())
+            .map(|x| x.country_code.as_str())
+        else {
+            return Ok(None);
+        };
+
+        if session_country != query.country {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            scores
+                .iter()
+                .filter_map(|score| {
+                    let user = score.user.as_ref()?;
+
+                    Some(feed_country_score(
+                        score,
+                        &user.username,
+                        &user.country_code,
+                    ))
+                })
+                .collect(),
+        ))
+    }
+}
+
+/// Third party api, works only if `FALLBACK_API` is set
+pub struct FallbackSource;
+
+impl LeaderboardSource for FallbackSource {
+    fn kind(&self) -> LeaderboardSourceKind {
+        LeaderboardSourceKind::Fallback
+    }
+
+    async fn fetch(
+        &self,
+        ctx: &FumoContext,
+        query: &LeaderboardQuery<'_>,
+    ) -> Result<Option<Vec<OsuCountryScore>>> {
+        if !ctx.osu_api.has_fallback() {
+            return Ok(None);
+        }
+
+        let lb = ctx
+            .osu_api
+            .get_countryleaderboard_fallback(
+                query.beatmap_id,
+                query.country,
+                query.mods.map(mods_label),
+                query.mode,
+            )
+            .await?;
+
+        if lb.items.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(fallback_country_scores(
+            &lb,
+            query.beatmap_id as i64,
+            query.country,
+        )))
+    }
+}
+
+#[derive(Default)]
+struct SourceHealth {
+    failures: u32,
+    unhealthy_until: Option<Instant>,
+}
+
+/// Health of leaderboard sources shared between all requests
+#[derive(Default)]
+pub struct LeaderboardHealth {
+    sources: Mutex<HashMap<LeaderboardSourceKind, SourceHealth>>,
+}
+
+impl LeaderboardHealth {
+    async fn is_healthy(&self, kind: LeaderboardSourceKind) -> bool {
+        let sources = self.sources.lock().await;
+
+        sources
+            .get(&kind)
+            .and_then(|x| x.unhealthy_until)
+            .is_none_or(|until| Instant::now() >= until)
+    }
+
+    async fn report_success(&self, kind: LeaderboardSourceKind) {
+        let mut sources = self.sources.lock().await;
+        sources.insert(kind, SourceHealth::default());
+    }
+
+    async fn report_failure(&self, kind: LeaderboardSourceKind) {
+        let mut sources = self.sources.lock().await;
+        let health = sources.entry(kind).or_default();
+
+        health.failures += 1;
+
+        if health.failures >= MAX_CONSECUTIVE_FAILURES {
+            tracing::warn!(
+                source = %kind,
+                failures = health.failures,
+                "Leaderboard source is unhealthy"
+            );
+
+            health.unhealthy_until = Some(Instant::now() + UNHEALTHY_COOLDOWN);
+        }
+    }
+}
+
+async fn try_source<S: LeaderboardSource>(
+    ctx: &FumoContext,
+    source: &S,
+    query: &LeaderboardQuery<'_>,
+) -> Option<Vec<OsuCountryScore>> {
+    let kind = source.kind();
+
+    if !ctx.leaderboard_health.is_healthy(kind).await {
+        return None;
+    }
+
+    match source.fetch(ctx, query).await {
+        Ok(scores) => {
+            ctx.leaderboard_health.report_success(kind).await;
+            scores
+        }
+        Err(e) => {
+            tracing::warn!(
+                source = %kind,
+                beatmap_id = query.beatmap_id,
+                "Failed to fetch country leaderboard: {e}"
+            );
+
+            ctx.leaderboard_health.report_failure(kind).await;
+            None
+        }
+    }
+}
+
+/// Fetches country leaderboard from the first healthy source
+/// that is able to serve it. Scores from the remote sources
+/// are stored locally, so for a while they are served from the database.
+///
+/// Returns `None` if no source has the leaderboard
+pub async fn fetch_country_leaderboard(
+    ctx: &FumoContext,
+    query: &LeaderboardQuery<'_>,
+) -> Result<Option<(Vec<OsuCountryScore>, LeaderboardSourceKind)>> {
+    if let Some(scores) = try_source(ctx, &LocalSource, query).await {
+        return Ok(Some((scores, LeaderboardSourceKind::Local)));
+    }
+
+    fetch_remote_country_leaderboard(ctx, query).await
+}
+
+/// Same as [`fetch_country_leaderboard`] but skips the database,
+/// for cases when stored scores might be outdated
+pub async fn fetch_remote_country_leaderboard(
+    ctx: &FumoContext,
+    query: &LeaderboardQuery<'_>,
+) -> Result<Option<(Vec<OsuCountryScore>, LeaderboardSourceKind)>> {
+    let remote = match try_source(ctx, &HiddenSource, query).await {
+        Some(scores) => Some((scores, LeaderboardSourceKind::Hidden)),
+        None => try_source(ctx, &FallbackSource, query)
+            .await
+            .map(|scores| (scores, LeaderboardSourceKind::Fallback)),
+    };
+
+    if let Some((scores, _)) = &remote {
+        ctx.db.add_osu_country_scores(scores).await?;
+
+        ctx.db
+            .set_osu_country_leaderboard_fetched(
+                query.beatmap_id as i64,
+                query.country,
+                query.mode.as_u8().into(),
+                query.mods,
+            )
+            .await?;
+    }
+
+    Ok(remote)
+}
+
+/// Beatmap, ruleset, player and normalized mods
+type UserScoreKey = (i64, OsuGameMode, i64, Option<String>);
+
+/// Best scores of the players on beatmaps,
+/// `None` is cached as well when player has no score
+#[derive(Default)]
+pub struct UserScoresCache {
+    scores: Mutex<HashMap<UserScoreKey, (Instant, Option<OsuCountryScore>)>>,
+}
+
+impl UserScoresCache {
+    async fn get(&self, key: &UserScoreKey) -> Option<Option<OsuCountryScore>> {
+        let scores = self.scores.lock().await;
+
+        scores
+            .get(key)
+            .filter(|(cached_at, _)| cached_at.elapsed() < USER_SCORES_TTL)
+            .map(|(_, score)| score.clone())
+    }
+
+    async fn insert(&self, key: UserScoreKey, score: Option<OsuCountryScore>) {
+        let mut scores = self.scores.lock().await;
+
+        scores
+            .retain(|_, (cached_at, _)| cached_at.elapsed() < USER_SCORES_TTL);
+        scores.insert(key, (Instant::now(), score));
+    }
+}
+
+/// Best score of the player on the beatmap, cached for a while
+async fn fetch_user_score(
+    ctx: &FumoContext,
+    beatmap_id: i32,
+    mode: OsuGameMode,
+    osu_id: i64,
+    mods: Option<&str>,
+) -> Result<Option<OsuCountryScore>> {
+    let key = (beatmap_id as i64, mode, osu_id, mods.map(str::to_owned));
+
+    if let Some(score) = ctx.user_scores_cache.get(&key).await {
+        return Ok(score);
+    }
+
+    let score = match ctx
+        .osu_api
+        .get_user_beatmap_scores(
+            beatmap_id as i64,
+            UserId::Id(osu_id),
+            mods,
+            Some(mode),
+        )
+        .await
+    {
+        Ok(score) => Some(user_beatmap_score(&score.score, beatmap_id)),
+        Err(OsuApiError::NotFound { .. }) => None,
+        Err(e) => return Err(e.into()),
+    };
+
+    ctx.user_scores_cache.insert(key, score.clone()).await;
+
+    Ok(score)
+}
+
+/// Leaderboard of the beatmap among provided players, built from their
+/// best scores. Only first [`MAX_LEADERBOARD_PLAYERS`] players are looked up
+pub async fn fetch_players_leaderboard(
+    ctx: &FumoContext,
+    beatmap_id: i32,
+    mode: OsuGameMode,
+    osu_ids: &[i64],
+    mods: Option<&str>,
+) -> Result<Vec<OsuCountryScore>> {
+    let results: Vec<Result<Option<OsuCountryScore>>> =
+        stream::iter(osu_ids.iter().take(MAX_LEADERBOARD_PLAYERS).copied())
+            .map(|osu_id| fetch_user_score(ctx, beatmap_id, mode, osu_id, mods))
+            .buffer_unordered(USER_SCORES_CONCURRENCY)
+            .collect()
+            .await;
+
+    let mut scores = Vec::with_capacity(results.len());
+
+    for score in results {
+        scores.extend(score?);
+    }
+
+    scores.sort_by_key(|x| Reverse(x.total_score));
+
+    Ok(scores)
+}
+
+/// Separator of the acronyms in the normalized mods
+const MODS_SEPARATOR: &str = ",";
+
+/// Sorted acronyms without `CL` so scores from stable and lazer with
+/// the same mods are treated equally. Acronyms are separated by `,`
+/// since not all of them are two characters long, e.g. `10K` or `SV2`
+pub fn normalize_mods<'a>(acronyms: impl Iterator<Item = &'a str>) -> String {
+    let mut acronyms: Vec<String> = acronyms
+        .map(|x| x.to_uppercase())
+        .filter(|x| !x.is_empty() && x != "CL" && x != "NM")
+        .collect();
+
+    acronyms.sort_unstable();
+    acronyms.dedup();
+
+    acronyms.join(MODS_SEPARATOR)
+}
+
+/// Acronyms of the normalized mods
+pub fn mods_acronyms(mods: &str) -> impl Iterator<Item = &str> {
+    mods.split(MODS_SEPARATOR).filter(|x| !x.is_empty())
+}
+
+/// Normalized mods as they are shown to the user, e.g. `DTHD`
+pub fn mods_label(mods: &str) -> String {
+    if mods.is_empty() {
+        String::from("NM")
+    } else {
+        mods_acronyms(mods).collect()
+    }
+}
+
+/// Splits acronyms written together like `HDDT`, anything that
+/// isn't a letter or a digit separates acronyms as well.
+/// Mania keys (`10K`) and `SV2` are longer than two characters
+pub fn split_acronyms(text: &str) -> Vec<String> {
+    let mut acronyms = Vec::new();
+
+    for word in text.split(|x: char| !x.is_ascii_alphanumeric()) {
+        let chars: Vec<char> = word.to_ascii_uppercase().chars().collect();
+        let mut rest = chars.as_slice();
+
+        while !rest.is_empty() {
+            let len = if rest.starts_with(&['S', 'V', '2']) {
+                3
+            } else {
+                // Keys are digits followed by `K`
+                rest.iter().take_while(|x| x.is_ascii_digit()).count() + 1
+            };
+
+            let len = len.max(2).min(rest.len());
+
+            acronyms.push(rest[..len].iter().collect());
+            rest = &rest[len..];
+        }
+    }
+
+    acronyms
+}
+
+/// Lazer hits in the stable layout of the ruleset:
+/// `[300, 100, 50, geki, katu, miss]`
+fn legacy_hits(score: &OsuScoreLazer) -> [i32; 6] {
+    let stat = |x: Option<u32>| x.unwrap_or(0) as i32;
+    let stats = &score.stats;
+
+    match score.ruleset_id {
+        OsuGameMode::Osu | OsuGameMode::Taiko => [
+            stat(stats.great),
+            stat(stats.ok),
+            stat(stats.meh),
+            0,
+            0,
+            stat(stats.miss),
+        ],
+        OsuGameMode::Fruits => [
+            stat(stats.great),
+            stat(stats.large_tick_hit),
+            stat(stats.small_tick_hit),
+            0,
+            stat(stats.small_tick_miss),
+            stat(stats.miss) + stat(stats.large_tick_miss),
+        ],
+        OsuGameMode::Mania => [
+            stat(stats.great),
+            stat(stats.ok),
+            stat(stats.meh),
+            stat(stats.perfect),
+            stat(stats.good),
+            stat(stats.miss),
+        ],
+    }
+}
+
+pub fn feed_country_score(
+    score: &OsuScoreLazer,
+    osu_username: &str,
+    country: &str,
+) -> OsuCountryScore {
+    let [count_300, count_100, count_50, count_geki, count_katu, count_miss] =
+        legacy_hits(score);
+
+    OsuCountryScore {
+        score_id: score.id,
+        osu_id: score.user_id,
+        osu_username: osu_username.to_owned(),
+        country: country.to_owned(),
+        beatmap_id: score.beatmap_id,
+        mode: score.ruleset_id.as_u8().into(),
+        mods: normalize_mods(
+            score.mods.mods.iter().map(|x| x.acronym.as_str()),
+        ),
+        speed: score.mods.speed_changes(),
+        total_score: score.total_score as i64,
+        legacy_total_score: score.legacy_total_score as i64,
+        accuracy: score.accuracy,
+        max_combo: score.max_combo as i32,
+        pp: score.pp,
+        grade: score.rank.to_string(),
+        count_300,
+        count_100,
+        count_50,
+        count_geki,
+        count_katu,
+        count_miss,
+        played_at: score.ended_at.naive_utc(),
+    }
+}
+
+fn fallback_country_scores(
+    lb: &FallbackBeatmapScores,
+    beatmap_id: i64,
+    country: &str,
+) -> Vec<OsuCountryScore> {
+    lb.items
+        .iter()
+        .map(|score| OsuCountryScore {
+            score_id: score.id,
+            osu_id: score.player.id,
+            osu_username: score.player.username.clone(),
+            country: country.to_owned(),
+            beatmap_id,
+            mode: lb.ruleset.as_u8().into(),
+            mods: normalize_mods(
+                score
+                    .stats
+                    .mods
+                    .difficulty
+                    .iter()
+                    .map(|x| x.acronym.as_str()),
+            ),
+            speed: score.stats.mods.difficulty.iter().find_map(|x| x.speed),
+            total_score: score.stats.score.lazer,
+            legacy_total_score: score.stats.score.legacy,
+            accuracy: score.stats.accuracy / 100.0,
+            max_combo: score.stats.combo as i32,
+            pp: Some(score.stats.performance),
+            grade: score.stats.rank.to_string(),
+            count_300: score.counts.x300 as i32,
+            count_100: score.counts.x100 as i32,
+            count_50: score.counts.x50 as i32,
+            count_geki: score.counts.xgeki as i32,
+            count_katu: score.counts.xkatu as i32,
+            count_miss: score.counts.xmiss as i32,
+            played_at: score.date.naive_utc(),
+        })
+        .collect()
+}
+
+fn user_beatmap_score(score: &OsuScore, beatmap_id: i32) -> OsuCountryScore {
+    let (osu_username, country) = score
+        .user
+        .as_ref()
+        .map(|x| (x.username.clone(), x.country_code.clone()))
+        .unwrap_or_default();
+
+    let mods = score.mods.to_string();
+
+    OsuCountryScore {
+        score_id: score.id.unwrap_or_default(),
+        osu_id: score.user_id,
+        osu_username,
+        country,
+        beatmap_id: beatmap_id as i64,
+        mode: score.mode_int,
+        mods: normalize_mods(split_acronyms(&mods).iter().map(String::as_str)),
+        speed: None,
+        total_score: score.score,
+        legacy_total_score: score.legacy_total_score.unwrap_or(score.score),
+        accuracy: score.accuracy,
+        max_combo: score.max_combo.unwrap_or(0),
+        pp: score.pp,
+        grade: score.rank.to_string(),
+        count_300: score.stats.count300.unwrap_or(0),
+        count_100: score.stats.count100.unwrap_or(0),
+        count_50: score.stats.count50.unwrap_or(0),
+        count_geki: score.stats.countgeki.unwrap_or(0),
+        count_katu: score.stats.countkatu.unwrap_or(0),
+        count_miss: score.stats.countmiss.unwrap_or(0),
+        played_at: score.created_at.naive_utc(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalize(text: &str) -> String {
+        normalize_mods(split_acronyms(text).iter().map(String::as_str))
+    }
+
+    #[test]
+    fn test_normalize_mods() {
+        let cases = [
+            ("HDDT", "DT,HD"),
+            ("dt hd", "DT,HD"),
+            ("+HD,DT,CL", "DT,HD"),
+            ("NM", ""),
+            ("10KDT", "10K,DT"),
+            ("4KHR", "4K,HR"),
+            ("HDSV2", "HD,SV2"),
+        ];
+
+        for (text, expected) in cases {
+            assert_eq!(normalize(text), expected, "{text}");
+        }
+    }
+
+    #[test]
+    fn test_mods_acronyms() {
+        let mods = normalize("10KDTHD");
+
+        assert_eq!(
+            mods_acronyms(&mods).collect::<Vec<_>>(),
+            ["10K", "DT", "HD"]
+        );
+        assert_eq!(mods_label(&mods), "10KDTHD");
+        assert_eq!(mods_label(""), "NM");
+        assert_eq!(mods_acronyms("").count(), 0);
+    }
+}
@@ -2,6 +2,9 @@ mod commands;
 mod components;
 pub mod fumo_context;
 mod handlers;
+mod leaderboard_source;
+mod render;
+mod scheduler;
 mod server;
 mod stats;
 pub mod twitch_api;
@@ -51,6 +54,37 @@ async fn spawn_osu_worker(
             ) => {
                 tracing::error!("Osu tracking checker loop sudenly ended!");
             }
+            _ = commands::osu_tracking::osu_tracking_delivery_worker(
+                ctx.clone()
+            ) => {
+                tracing::error!("Osu tracking delivery loop sudenly ended!");
+            }
+            _ = commands::osu_tracking::osu_tracking_maintenance_worker(
+                ctx.clone()
+            ) => {
+                tracing::error!("Osu tracking maintenance loop sudenly ended!");
+            }
+            _ = commands::snipes::snipes_worker(ctx.clone()) => {
+                tracing::error!("Country snipes loop sudenly ended!");
+            }
+            _ = commands::multiplayer::match_follow_worker(ctx.clone()) => {
+                tracing::error!("Multiplayer follow loop sudenly ended!");
+            }
+            _ = rx => {
+            }
+        }
+    });
+}
+
+async fn spawn_scheduler(
+    ctx: Arc<FumoContext>,
+    rx: tokio::sync::oneshot::Receiver<()>,
+) {
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = scheduler::scheduler_worker(ctx.clone()) => {
+                tracing::error!("Scheduler loop sudenly ended!");
+            }
             _ = rx => {
             }
         }
@@ -108,6 +142,11 @@ async fn main() -> Result<()> {
     let osu_tracker_ctx = Arc::clone(&ctx);
     spawn_osu_worker(osu_tracker_ctx, rx).await;
 
+    // Spawn scheduler
+    let (scheduler_tx, rx) = channel::<()>();
+    let scheduler_ctx = Arc::clone(&ctx);
+    spawn_scheduler(scheduler_ctx, rx).await;
+
     // Spawn http server
     let server_tx = {
         let server_ctx = Arc::clone(&ctx);
@@ -155,6 +194,10 @@ async fn main() -> Result<()> {
         tracing::error!("Failed to close osu tracking loop!");
     }
 
+    if scheduler_tx.send(()).is_err() {
+        tracing::error!("Failed to close scheduler loop!");
+    }
+
     commands::twitch::twitch_sync_db(ctx.clone())
         .await
         .expect("Failed to sync checker list with db");
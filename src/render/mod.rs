@@ -0,0 +1,368 @@
+pub mod scoreboard;
+
+use std::time::Duration;
+
+use ab_glyph::{Font, FontRef, OutlineCurve, PxScale, ScaleFont};
+use bytes::Bytes;
+use eyre::Result;
+use once_cell::sync::Lazy;
+use tiny_skia::{
+    Color, ColorU8, FillRule, FilterQuality, IntSize, Paint, PathBuilder,
+    Pattern, Pixmap, SpreadMode, Transform,
+};
+
+static FONT_REGULAR: Lazy<FontRef<'static>> = Lazy::new(|| {
+    FontRef::try_from_slice(include_bytes!("../../assets/fonts/DejaVuSans.ttf"))
+        .expect("bundled font should be valid")
+});
+
+static FONT_BOLD: Lazy<FontRef<'static>> = Lazy::new(|| {
+    FontRef::try_from_slice(include_bytes!(
+        "../../assets/fonts/DejaVuSans-Bold.ttf"
+    ))
+    .expect("bundled font should be valid")
+});
+
+/// Images that take longer than that are not drawn
+const IMAGE_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy)]
+pub enum FontWeight {
+    Regular,
+    Bold,
+}
+
+impl FontWeight {
+    fn font(self) -> &'static FontRef<'static> {
+        match self {
+            Self::Regular => &FONT_REGULAR,
+            Self::Bold => &FONT_BOLD,
+        }
+    }
+}
+
+pub fn rgb(hex: u32) -> Color {
+    rgba(hex, 255)
+}
+
+pub fn rgba(hex: u32, alpha: u8) -> Color {
+    Color::from_rgba8((hex >> 16) as u8, (hex >> 8) as u8, hex as u8, alpha)
+}
+
+fn paint(color: Color) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color(color);
+    paint.anti_alias = true;
+
+    paint
+}
+
+/// Width of the text in pixels
+pub fn text_width(text: &str, size: f32, weight: FontWeight) -> f32 {
+    let font = weight.font().as_scaled(PxScale::from(size));
+
+    let mut width = 0.0;
+    let mut previous = None;
+
+    for c in text.chars() {
+        let id = font.glyph_id(c);
+
+        // Characters missing in the font (e.g. emojis) are skipped
+        if id.0 == 0 {
+            continue;
+        }
+
+        if let Some(previous) = previous {
+            width += font.kern(previous, id);
+        }
+
+        width += font.h_advance(id);
+        previous = Some(id);
+    }
+
+    width
+}
+
+/// Cuts the text with ellipsis so it fits into `max_width`
+pub fn fit_text(
+    text: &str,
+    size: f32,
+    weight: FontWeight,
+    max_width: f32,
+) -> String {
+    if text_width(text, size, weight) <= max_width {
+        return text.to_owned();
+    }
+
+    let mut fitted: String = text.to_owned();
+
+    while !fitted.is_empty() {
+        fitted.pop();
+
+        let candidate = format!("{}…", fitted.trim_end());
+
+        if text_width(&candidate, size, weight) <= max_width {
+            return candidate;
+        }
+    }
+
+    String::new()
+}
+
+/// Draws text with the baseline at `y`, returns its width
+pub fn draw_text(
+    pixmap: &mut Pixmap,
+    text: &str,
+    x: f32,
+    y: f32,
+    size: f32,
+    weight: FontWeight,
+    color: Color,
+) -> f32 {
+    let font = weight.font();
+    let scaled = font.as_scaled(PxScale::from(size));
+    let scale = scaled.scale_factor();
+
+    let mut builder = PathBuilder::new();
+    let mut caret = x;
+    let mut previous = None;
+
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+
+        if id.0 == 0 {
+            continue;
+        }
+
+        if let Some(previous) = previous {
+            caret += scaled.kern(previous, id);
+        }
+
+        if let Some(outline) = font.outline(id) {
+            let point = |p: ab_glyph::Point| {
+                (caret + p.x * scale.horizontal, y - p.y * scale.vertical)
+            };
+
+            let mut last = None;
+
+            for curve in &outline.curves {
+                let start = match curve {
+                    OutlineCurve::Line(p0, _)
+                    | OutlineCurve::Quad(p0, _, _)
+                    | OutlineCurve::Cubic(p0, _, _, _) => point(*p0),
+                };
+
+                if last != Some(start) {
+                    builder.move_to(start.0, start.1);
+                }
+
+                let end = match curve {
+                    OutlineCurve::Line(_, p1) => {
+                        let p1 = point(*p1);
+                        builder.line_to(p1.0, p1.1);
+                        p1
+                    }
+                    OutlineCurve::Quad(_, p1, p2) => {
+                        let (p1, p2) = (point(*p1), point(*p2));
+                        builder.quad_to(p1.0, p1.1, p2.0, p2.1);
+                        p2
+                    }
+                    OutlineCurve::Cubic(_, p1, p2, p3) => {
+                        let (p1, p2, p3) = (point(*p1), point(*p2), point(*p3));
+                        builder.cubic_to(p1.0, p1.1, p2.0, p2.1, p3.0, p3.1);
+                        p3
+                    }
+                };
+
+                last = Some(end);
+            }
+        }
+
+        caret += scaled.h_advance(id);
+        previous = Some(id);
+    }
+
+    if let Some(path) = builder.finish() {
+        pixmap.fill_path(
+            &path,
+            &paint(color),
+            FillRule::Winding,
+            Transform::identity(),
+            None,
+        );
+    }
+
+    caret - x
+}
+
+/// Same as [`draw_text`] but `x` is the right edge of the text
+pub fn draw_text_right(
+    pixmap: &mut Pixmap,
+    text: &str,
+    x: f32,
+    y: f32,
+    size: f32,
+    weight: FontWeight,
+    color: Color,
+) -> f32 {
+    let width = text_width(text, size, weight);
+    draw_text(pixmap, text, x - width, y, size, weight, color)
+}
+
+fn rounded_rect(
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+    radius: f32,
+) -> Option<tiny_skia::Path> {
+    let r = radius.min(w / 2.0).min(h / 2.0);
+
+    let mut builder = PathBuilder::new();
+    builder.move_to(x + r, y);
+    builder.line_to(x + w - r, y);
+    builder.quad_to(x + w, y, x + w, y + r);
+    builder.line_to(x + w, y + h - r);
+    builder.quad_to(x + w, y + h, x + w - r, y + h);
+    builder.line_to(x + r, y + h);
+    builder.quad_to(x, y + h, x, y + h - r);
+    builder.line_to(x, y + r);
+    builder.quad_to(x, y, x + r, y);
+    builder.close();
+
+    builder.finish()
+}
+
+pub fn fill_rounded_rect(
+    pixmap: &mut Pixmap,
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+    radius: f32,
+    color: Color,
+) {
+    if let Some(path) = rounded_rect(x, y, w, h, radius) {
+        pixmap.fill_path(
+            &path,
+            &paint(color),
+            FillRule::Winding,
+            Transform::identity(),
+            None,
+        );
+    }
+}
+
+/// Draws image stretched into the rect with rounded corners
+pub fn draw_image(
+    pixmap: &mut Pixmap,
+    image: &Pixmap,
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+    radius: f32,
+) {
+    let Some(path) = rounded_rect(x, y, w, h, radius) else {
+        return;
+    };
+
+    let transform = Transform::from_row(
+        w / image.width() as f32,
+        0.0,
+        0.0,
+        h / image.height() as f32,
+        x,
+        y,
+    );
+
+    let paint = Paint {
+        shader: Pattern::new(
+            image.as_ref(),
+            SpreadMode::Pad,
+            FilterQuality::Bicubic,
+            1.0,
+            transform,
+        ),
+        anti_alias: true,
+        ..Default::default()
+    };
+
+    pixmap.fill_path(
+        &path,
+        &paint,
+        FillRule::Winding,
+        Transform::identity(),
+        None,
+    );
+}
+
+/// Decodes png, jpeg or gif into the pixmap
+pub fn decode_image(bytes: &[u8]) -> Result<Pixmap> {
+    let image = image::load_from_memory(bytes)?.to_rgba8();
+
+    let size = IntSize::from_wh(image.width(), image.height())
+        .ok_or_else(|| eyre::eyre!("image is empty"))?;
+
+    let mut data = Vec::with_capacity(image.as_raw().len());
+
+    for pixel in image.pixels() {
+        let [r, g, b, a] = pixel.0;
+        let color = ColorU8::from_rgba(r, g, b, a).premultiply();
+
+        data.extend([color.red(), color.green(), color.blue(), color.alpha()]);
+    }
+
+    Pixmap::from_vec(data, size)
+        .ok_or_else(|| eyre::eyre!("invalid image size"))
+}
+
+/// Shared between renders to reuse connections
+static IMAGE_CLIENT: Lazy<reqwest::Client> = Lazy::new(reqwest::Client::new);
+
+/// Downloads images, failed ones are `None`. Decoding is left
+/// to the caller since it should be done off the executor
+pub async fn fetch_images(urls: Vec<String>) -> Vec<Option<Bytes>> {
+    let mut handles = Vec::with_capacity(urls.len());
+
+    for url in urls {
+        handles.push(tokio::spawn(async move {
+            IMAGE_CLIENT
+                .get(&url)
+                .timeout(IMAGE_TIMEOUT)
+                .send()
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await
+        }));
+    }
+
+    let mut images = Vec::with_capacity(handles.len());
+
+    for handle in handles {
+        let image = match handle.await {
+            Ok(Ok(image)) => Some(image),
+            Ok(Err(e)) => {
+                tracing::debug!("Failed to fetch image: {e}");
+                None
+            }
+            Err(_) => None,
+        };
+
+        images.push(image);
+    }
+
+    images
+}
+
+/// Decodes downloaded image, failed ones are `None`
+pub fn decode_fetched(bytes: Option<&Bytes>) -> Option<Pixmap> {
+    match decode_image(bytes?) {
+        Ok(image) => Some(image),
+        Err(e) => {
+            tracing::debug!("Failed to decode image: {e}");
+            None
+        }
+    }
+}
@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDateTime, Utc};
+use eyre::Result;
+use osu_api::models::OsuGrade;
+use tiny_skia::Pixmap;
+
+use crate::leaderboard_source::mods_acronyms;
+
+use super::{
+    decode_fetched, draw_image, draw_text, draw_text_right, fetch_images,
+    fill_rounded_rect, fit_text, rgb, rgba, text_width, FontWeight,
+};
+
+const WIDTH: u32 = 900;
+const PADDING: f32 = 16.0;
+const HEADER_HEIGHT: f32 = 76.0;
+const ROW_HEIGHT: f32 = 64.0;
+const ROW_GAP: f32 = 6.0;
+
+/// Right edge of the mods column
+const MODS_RIGHT: f32 = 700.0;
+
+const BACKGROUND: u32 = 0x1c1719;
+const ROW_BACKGROUND: u32 = 0x2e2529;
+const ROW_HIGHLIGHT: u32 = 0x4a3552;
+const TEXT_PRIMARY: u32 = 0xffffff;
+const TEXT_SECONDARY: u32 = 0xb8aab0;
+const PP_COLOR: u32 = 0xff66ab;
+
+/// Score row of the scoreboard, texts that depend
+/// on the leaderboard options are formatted by the caller
+pub struct ScoreboardRow {
+    pub position: usize,
+    pub osu_id: i64,
+    pub username: String,
+    pub country: String,
+    pub grade: OsuGrade,
+    /// Normalized acronyms, see `normalize_mods`
+    pub mods: String,
+    pub speed: Option<f32>,
+    pub accuracy: f32,
+    pub max_combo: i32,
+    pub score: String,
+    pub pp: String,
+    pub played_at: NaiveDateTime,
+    pub highlighted: bool,
+}
+
+pub struct Scoreboard {
+    pub title: String,
+    pub subtitle: String,
+    pub rows: Vec<ScoreboardRow>,
+}
+
+/// Text and color of the grade as shown in game
+fn grade_style(grade: OsuGrade) -> (&'static str, u32) {
+    match grade {
+        OsuGrade::GradeXH => ("SS", 0xe0e8f0),
+        OsuGrade::GradeX => ("SS", 0xffde59),
+        OsuGrade::GradeSH => ("S", 0xe0e8f0),
+        OsuGrade::GradeS => ("S", 0xffcc22),
+        OsuGrade::GradeA => ("A", 0x88da20),
+        OsuGrade::GradeB => ("B", 0x3fa9f5),
+        OsuGrade::GradeC => ("C", 0xd269ff),
+        OsuGrade::GradeD => ("D", 0xff5a5a),
+        OsuGrade::GradeF => ("F", 0x8a8a8a),
+    }
+}
+
+/// Colors of the mod types from the lazer mod select
+fn mod_color(acronym: &str) -> u32 {
+    match acronym {
+        "EZ" | "NF" | "HT" | "DC" | "NR" => 0xb2ff66,
+        "HR" | "SD" | "PF" | "DT" | "NC" | "HD" | "FL" | "BL" | "ST" | "AC"
+        | "FI" | "CO" => 0xff6666,
+        "AT" | "CN" | "RX" | "AP" | "SO" => 0x66ccff,
+        "TP" | "DA" | "CL" | "RD" | "MR" | "AL" | "SG" | "IN" | "CS" | "HO"
+        | "SW" => 0x8c66ff,
+        "TD" => 0xaaaaaa,
+        x if x.ends_with('K') => 0x8c66ff,
+        _ => 0xff66ab,
+    }
+}
+
+fn time_ago(played_at: NaiveDateTime) -> String {
+    let seconds = (Utc::now().naive_utc() - played_at).num_seconds().max(0);
+
+    match seconds {
+        0..60 => "now".to_owned(),
+        60..3600 => format!("{}m ago", seconds / 60),
+        3600..86400 => format!("{}h ago", seconds / 3600),
+        86400..2592000 => format!("{}d ago", seconds / 86400),
+        2592000..31536000 => format!("{}mo ago", seconds / 2592000),
+        _ => format!("{}y ago", seconds / 31536000),
+    }
+}
+
+/// Draws mod pills from right to left ending at `right`
+fn draw_mods(pixmap: &mut Pixmap, row: &ScoreboardRow, right: f32, y: f32) {
+    let mut pills: Vec<(String, u32)> = mods_acronyms(&row.mods)
+        .map(|x| (x.to_owned(), mod_color(x)))
+        .collect();
+
+    if let Some(speed) = row.speed {
+        pills.push((format!("x{speed}"), 0xaaaaaa));
+    }
+
+    let mut x = right;
+
+    for (text, color) in pills.iter().rev() {
+        let width = text_width(text, 12.0, FontWeight::Bold) + 12.0;
+        x -= width;
+
+        fill_rounded_rect(pixmap, x, y, width, 22.0, 11.0, rgb(*color));
+        draw_text(
+            pixmap,
+            text,
+            x + 6.0,
+            y + 16.0,
+            12.0,
+            FontWeight::Bold,
+            rgb(BACKGROUND),
+        );
+
+        x -= 4.0;
+    }
+}
+
+fn draw_row(
+    pixmap: &mut Pixmap,
+    row: &ScoreboardRow,
+    y: f32,
+    avatar: Option<&Pixmap>,
+    flag: Option<&Pixmap>,
+) {
+    let right = WIDTH as f32 - PADDING;
+
+    let background = if row.highlighted {
+        ROW_HIGHLIGHT
+    } else {
+        ROW_BACKGROUND
+    };
+
+    fill_rounded_rect(
+        pixmap,
+        PADDING,
+        y,
+        right - PADDING,
+        ROW_HEIGHT,
+        10.0,
+        rgb(background),
+    );
+
+    draw_text(
+        pixmap,
+        &format!("#{}", row.position),
+        PADDING + 12.0,
+        y + 39.0,
+        18.0,
+        FontWeight::Bold,
+        rgb(TEXT_SECONDARY),
+    );
+
+    let (grade, grade_color) = grade_style(row.grade);
+    draw_text(
+        pixmap,
+        grade,
+        80.0,
+        y + 42.0,
+        26.0,
+        FontWeight::Bold,
+        rgb(grade_color),
+    );
+
+    match avatar {
+        Some(avatar) => {
+            draw_image(pixmap, avatar, 128.0, y + 8.0, 48.0, 48.0, 8.0)
+        }
+        None => fill_rounded_rect(
+            pixmap,
+            128.0,
+            y + 8.0,
+            48.0,
+            48.0,
+            8.0,
+            rgba(TEXT_SECONDARY, 60),
+        ),
+    }
+
+    let mut name_x = 188.0;
+
+    if let Some(flag) = flag {
+        draw_image(pixmap, flag, name_x, y + 12.0, 27.0, 18.0, 3.0);
+        name_x += 34.0;
+    }
+
+    let username = fit_text(
+        &row.username,
+        19.0,
+        FontWeight::Bold,
+        MODS_RIGHT - name_x - 120.0,
+    );
+    draw_text(
+        pixmap,
+        &username,
+        name_x,
+        y + 28.0,
+        19.0,
+        FontWeight::Bold,
+        rgb(TEXT_PRIMARY),
+    );
+
+    let details = format!(
+        "{:.2}% • {}x • {}",
+        row.accuracy * 100.0,
+        row.max_combo,
+        time_ago(row.played_at)
+    );
+    draw_text(
+        pixmap,
+        &details,
+        188.0,
+        y + 52.0,
+        14.0,
+        FontWeight::Regular,
+        rgb(TEXT_SECONDARY),
+    );
+
+    draw_mods(pixmap, row, MODS_RIGHT, y + 21.0);
+
+    draw_text_right(
+        pixmap,
+        &row.score,
+        right - 14.0,
+        y + 28.0,
+        20.0,
+        FontWeight::Bold,
+        rgb(TEXT_PRIMARY),
+    );
+    draw_text_right(
+        pixmap,
+        &row.pp,
+        right - 14.0,
+        y + 52.0,
+        15.0,
+        FontWeight::Bold,
+        rgb(PP_COLOR),
+    );
+}
+
+fn draw_header(pixmap: &mut Pixmap, board: &Scoreboard) {
+    let max_width = WIDTH as f32 - PADDING * 3.0;
+
+    let title = fit_text(&board.title, 24.0, FontWeight::Bold, max_width);
+    draw_text(
+        pixmap,
+        &title,
+        PADDING + 8.0,
+        40.0,
+        24.0,
+        FontWeight::Bold,
+        rgb(TEXT_PRIMARY),
+    );
+
+    let subtitle =
+        fit_text(&board.subtitle, 15.0, FontWeight::Regular, max_width);
+    draw_text(
+        pixmap,
+        &subtitle,
+        PADDING + 8.0,
+        64.0,
+        15.0,
+        FontWeight::Regular,
+        rgb(TEXT_SECONDARY),
+    );
+}
+
+/// Renders scoreboard resembling the in-game one into a PNG
+pub async fn render_scoreboard(board: Scoreboard) -> Result<Vec<u8>> {
+    let avatars = fetch_images(
+        board
+            .rows
+            .iter()
+            .map(|x| format!("https://a.ppy.sh/{}", x.osu_id))
+            .collect(),
+    )
+    .await;
+
+    let mut countries: Vec<String> = board
+        .rows
+        .iter()
+        .map(|x| x.country.to_lowercase())
+        .collect();
+    countries.sort_unstable();
+    countries.dedup();
+
+    let flag_images = fetch_images(
+        countries
+            .iter()
+            .map(|x| format!("https://flagcdn.com/w40/{x}.png"))
+            .collect(),
+    )
+    .await;
+
+    // Decoding, drawing and encoding are cpu bound,
+    // so they are kept off the executor
+    tokio::task::spawn_blocking(move || {
+        let avatars: Vec<Option<Pixmap>> =
+            avatars.iter().map(|x| decode_fetched(x.as_ref())).collect();
+
+        let flags: HashMap<String, Pixmap> = countries
+            .into_iter()
+            .zip(&flag_images)
+            .filter_map(|(country, flag)| {
+                Some((country, decode_fetched(flag.as_ref())?))
+            })
+            .collect();
+
+        let height = HEADER_HEIGHT
+            + board.rows.len() as f32 * (ROW_HEIGHT + ROW_GAP)
+            + PADDING;
+
+        let mut pixmap = Pixmap::new(WIDTH, height.ceil() as u32)
+            .ok_or_else(|| eyre::eyre!("invalid scoreboard size"))?;
+
+        pixmap.fill(rgb(BACKGROUND));
+
+        draw_header(&mut pixmap, &board);
+
+        for (index, (row, avatar)) in
+            board.rows.iter().zip(&avatars).enumerate()
+        {
+            let y = HEADER_HEIGHT + index as f32 * (ROW_HEIGHT + ROW_GAP);
+            let flag = flags.get(&row.country.to_lowercase());
+
+            draw_row(&mut pixmap, row, y, avatar.as_ref(), flag);
+        }
+
+        Ok(pixmap.encode_png()?)
+    })
+    .await?
+}
@@ -0,0 +1,84 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Datelike, Days, TimeDelta, Utc, Weekday};
+use tokio::time::MissedTickBehavior;
+
+use crate::{commands::osu_tracking, fumo_context::FumoContext};
+
+const SCHEDULER_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Most recent moment that matches provided weekday and hour (UTC),
+/// `now` itself is included
+pub fn last_weekly_occurrence(
+    now: DateTime<Utc>,
+    weekday: Weekday,
+    hour: u32,
+) -> DateTime<Utc> {
+    let days_back = (now.weekday().num_days_from_monday() + 7
+        - weekday.num_days_from_monday())
+        % 7;
+
+    let occurrence = (now.date_naive() - Days::new(days_back.into()))
+        .and_hms_opt(hour, 0, 0)
+        .expect("hour should be valid")
+        .and_utc();
+
+    if occurrence > now {
+        occurrence - TimeDelta::weeks(1)
+    } else {
+        occurrence
+    }
+}
+
+/// Runs time based jobs, every job decides by itself
+/// whether it is time to do something
+pub async fn scheduler_worker(ctx: Arc<FumoContext>) {
+    tracing::info!("Starting scheduler worker!");
+
+    let mut interval = tokio::time::interval(SCHEDULER_INTERVAL);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        let now = Utc::now();
+
+        if let Err(e) = osu_tracking::send_due_digests(&ctx, now).await {
+            tracing::error!("Failed to send osu tracking digests: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_last_weekly_occurrence() {
+        // Wednesday
+        let now = Utc.with_ymd_and_hms(2024, 5, 15, 12, 30, 0).unwrap();
+
+        assert_eq!(
+            last_weekly_occurrence(now, Weekday::Mon, 18),
+            Utc.with_ymd_and_hms(2024, 5, 13, 18, 0, 0).unwrap()
+        );
+
+        assert_eq!(
+            last_weekly_occurrence(now, Weekday::Wed, 12),
+            Utc.with_ymd_and_hms(2024, 5, 15, 12, 0, 0).unwrap()
+        );
+
+        // Later on the same day is still a week ago
+        assert_eq!(
+            last_weekly_occurrence(now, Weekday::Wed, 13),
+            Utc.with_ymd_and_hms(2024, 5, 8, 13, 0, 0).unwrap()
+        );
+
+        assert_eq!(
+            last_weekly_occurrence(now, Weekday::Thu, 0),
+            Utc.with_ymd_and_hms(2024, 5, 9, 0, 0, 0).unwrap()
+        );
+    }
+}
@@ -5,6 +5,8 @@ pub struct BotStats {
     pub cmd: IntCounterVec,
     pub cache: IntCounterVec,
     pub discord_events: IntCounterVec,
+    /// Outcomes of the osu tracking deliveries
+    pub deliveries: IntCounterVec,
 }
 
 impl Default for BotStats {
@@ -23,10 +25,17 @@ impl Default for BotStats {
         let discord_events_counters =
             IntCounterVec::new(opts, &["kind"]).unwrap();
 
+        let opts = Opts::new(
+            "fumo_bot_tracking_deliveries",
+            "sent/retried/dropped tracking messages",
+        );
+        let deliveries_counters = IntCounterVec::new(opts, &["kind"]).unwrap();
+
         Self {
             cmd: command_counters,
             cache: cache_counters,
             discord_events: discord_events_counters,
+            deliveries: deliveries_counters,
         }
     }
 }
@@ -53,6 +62,9 @@ impl BotMetrics {
         registry
             .register(Box::new(bot_metrics.discord_events.clone()))
             .unwrap();
+        registry
+            .register(Box::new(bot_metrics.deliveries.clone()))
+            .unwrap();
 
         Self {
             registry,
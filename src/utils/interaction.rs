@@ -14,13 +14,18 @@ use twilight_model::{
 use twilight_model::channel::message::Message;
 
 use twilight_model::id::{
-    marker::{ChannelMarker, InteractionMarker},
+    marker::{ChannelMarker, GuildMarker, InteractionMarker},
     Id,
 };
 
-use twilight_model::application::interaction::{
-    application_command::CommandData,
-    message_component::MessageComponentInteractionData,
+use twilight_model::application::{
+    command::CommandOptionChoice,
+    interaction::{
+        application_command::{
+            CommandData, CommandDataOption, CommandOptionValue,
+        },
+        message_component::MessageComponentInteractionData,
+    },
 };
 use twilight_util::builder::InteractionResponseDataBuilder;
 
@@ -53,7 +58,7 @@ pub struct InteractionCommand {
     pub channel_id: Id<ChannelMarker>,
     pub data: CommandData,
     // pub kind: InteractionType,
-    // pub guild_id: Option<Id<GuildMarker>>,
+    pub guild_id: Option<Id<GuildMarker>>,
     pub id: Id<InteractionMarker>,
     pub token: String,
     pub member: Option<PartialMember>,
@@ -99,9 +104,13 @@ impl InteractionCommand {
         }
 
         if let Some(ref attachments) = builder.attachments {
-            req = req
-                .attachments(attachments.as_slice())
-                .expect("invalid embed!");
+            // Empty list removes files that were sent before
+            req = if attachments.is_empty() {
+                req.keep_attachment_ids(&[])
+            } else {
+                req.attachments(attachments.as_slice())
+                    .expect("invalid embed!")
+            };
         }
 
         req.into_future()
@@ -146,6 +155,40 @@ impl InteractionCommand {
             .into_future()
     }
 
+    /// Responds to the autocomplete interaction with provided choices
+    pub fn autocomplete(
+        &self,
+        ctx: &FumoContext,
+        choices: Vec<CommandOptionChoice>,
+    ) -> ResponseFuture<EmptyBody> {
+        let data = InteractionResponseDataBuilder::new().choices(choices);
+
+        let response = InteractionResponse {
+            kind: InteractionResponseType::ApplicationCommandAutocompleteResult,
+            data: Some(data.build()),
+        };
+
+        ctx.interaction()
+            .create_response(self.id, &self.token, &response)
+            .into_future()
+    }
+
+    /// Name and current input of the option that user is typing in
+    pub fn focused_option(&self) -> Option<(&str, &str)> {
+        fn find(options: &[CommandDataOption]) -> Option<(&str, &str)> {
+            options.iter().find_map(|option| match &option.value {
+                CommandOptionValue::Focused(value, _) => {
+                    Some((option.name.as_str(), value.as_str()))
+                }
+                CommandOptionValue::SubCommand(options)
+                | CommandOptionValue::SubCommandGroup(options) => find(options),
+                _ => None,
+            })
+        }
+
+        find(&self.data.options)
+    }
+
     pub fn user_id(&self) -> Option<Id<UserMarker>> {
         if let Some(member) = &self.member {
             if let Some(user) = &member.user {
@@ -1,3 +1,4 @@
+pub mod countries;
 pub mod interaction;
 pub mod searching;
 pub mod static_components;
@@ -34,8 +35,8 @@ macro_rules! define_regex {
 }
 
 define_regex! {
-    OSU_MAP_ID_NEW: r"https://osu.ppy.sh/beatmapsets/(\d+)(?:(?:#(?:osu|mania|taiko|fruits)|<#\d+>)/(\d+))?";
-    OSU_MAP_ID_OLD: r"https://osu.ppy.sh/b(?:eatmaps)?/(\d+)";
+    OSU_LINK: r"(?:https?://)?(?:osu|old)\.ppy\.sh/(?:<#\d+>|[^\s<>()\[\]|])*";
+    DISCORD_CHANNEL_MENTION: r"<#\d+>";
 }
 
 #[macro_export]
@@ -1,29 +1,778 @@
-use twilight_model::channel::Message;
+use std::collections::HashMap;
 
-use super::{OSU_MAP_ID_NEW, OSU_MAP_ID_OLD};
+use eyre::Result;
+use osu_api::models::{OsuGameMode, UserId};
+use twilight_model::{
+    channel::{Attachment, Message},
+    id::{marker::ChannelMarker, Id},
+};
 
-pub fn find_beatmap_link(msg: &Message) -> Option<&String> {
-    match msg.author.id.get() {
-        // owo bot
-        289066747443675143 => msg.embeds.first()?.author.as_ref()?.url.as_ref(),
-        // bath bot & mikaizuku
-        297073686916366336 | 839937716921565252 => {
-            msg.embeds.first()?.url.as_ref()
+use crate::fumo_context::FumoContext;
+
+use super::{DISCORD_CHANNEL_MENTION, OSU_LINK};
+
+/// Amount of recent messages looked through when beatmap isn't provided
+pub const RECENT_MESSAGES_LIMIT: u16 = 50;
+
+/// Attachments bigger than that are not downloaded
+const MAX_ATTACHMENT_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Place in the embed where bot puts link to the beatmap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BotLinkPlace {
+    Author,
+    Url,
+}
+
+/// Bots which beatmap links are preferred over
+/// any other links found in their messages
+pub struct KnownBots(HashMap<u64, BotLinkPlace>);
+
+impl Default for KnownBots {
+    fn default() -> Self {
+        Self(HashMap::from([
+            // owo bot
+            (289066747443675143, BotLinkPlace::Author),
+            // bath bot
+            (297073686916366336, BotLinkPlace::Url),
+            // mikaizuku
+            (839937716921565252, BotLinkPlace::Url),
+        ]))
+    }
+}
+
+impl KnownBots {
+    /// Parses comma separated `id:author` or `id:url` entries
+    /// on top of the default bots, invalid entries are skipped
+    pub fn new(config: &str) -> Self {
+        let mut bots = Self::default();
+
+        for entry in config.split(',').map(str::trim).filter(|x| !x.is_empty())
+        {
+            let parsed = entry.split_once(':').and_then(|(id, place)| {
+                let place = match place.trim() {
+                    "author" => BotLinkPlace::Author,
+                    "url" => BotLinkPlace::Url,
+                    _ => return None,
+                };
+
+                Some((id.trim().parse().ok()?, place))
+            });
+
+            match parsed {
+                Some((id, place)) => {
+                    bots.0.insert(id, place);
+                }
+                None => tracing::warn!("Invalid known bot entry: {entry}"),
+            }
+        }
+
+        bots
+    }
+
+    fn link<'a>(&self, msg: &'a Message) -> Option<&'a str> {
+        let embed = msg.embeds.first()?;
+
+        match self.0.get(&msg.author.id.get())? {
+            BotLinkPlace::Author => embed.author.as_ref()?.url.as_deref(),
+            BotLinkPlace::Url => embed.url.as_deref(),
         }
+    }
+}
+
+/// Link to the osu! website
+#[derive(Debug, Clone, PartialEq)]
+pub enum OsuLink {
+    Beatmap {
+        id: i32,
+        mode: Option<OsuGameMode>,
+    },
+    Beatmapset {
+        id: i32,
+        mode: Option<OsuGameMode>,
+    },
+    Score {
+        id: i64,
+        mode: Option<OsuGameMode>,
+    },
+    User {
+        user: UserId,
+        mode: Option<OsuGameMode>,
+    },
+    Match(i64),
+}
+
+fn parse_mode(mode: &str) -> Option<OsuGameMode> {
+    match mode {
+        "osu" | "0" => Some(OsuGameMode::Osu),
+        "taiko" | "1" => Some(OsuGameMode::Taiko),
+        "fruits" | "2" => Some(OsuGameMode::Fruits),
+        "mania" | "3" => Some(OsuGameMode::Mania),
         _ => None,
     }
 }
 
-pub fn parse_beatmap_link(str: &str) -> Option<i32> {
-    if !str.contains("https://osu.ppy.sh") {
+impl OsuLink {
+    /// Parses a single link, scheme is optional
+    pub fn parse(link: &str) -> Option<Self> {
+        // Discord turns `#osu` fragments of the copied links into mentions
+        let link = DISCORD_CHANNEL_MENTION.get().replace_all(link, "#");
+        let link = link.trim_end_matches(['.', ',', '!', '?', ';', ':']);
+
+        let link = link
+            .strip_prefix("https://")
+            .or_else(|| link.strip_prefix("http://"))
+            .unwrap_or(link);
+
+        let path = link
+            .strip_prefix("osu.ppy.sh/")
+            .or_else(|| link.strip_prefix("old.ppy.sh/"))?;
+
+        let (path, fragment) = path.split_once('#').unwrap_or((path, ""));
+        let (path, params) = path.split_once('?').unwrap_or((path, ""));
+
+        let query = |key: &str| {
+            params
+                .split('&')
+                .filter_map(|x| x.split_once('='))
+                .find_map(|(k, v)| (k == key).then_some(v))
+        };
+
+        let query_mode =
+            query("m").or_else(|| query("mode")).and_then(parse_mode);
+
+        let segments: Vec<&str> =
+            path.split('/').filter(|x| !x.is_empty()).collect();
+
+        let link = match segments.as_slice() {
+            ["b" | "beatmaps", id, ..] => Self::Beatmap {
+                id: id.parse().ok()?,
+                mode: query_mode,
+            },
+            ["beatmapsets", set, rest @ ..] => {
+                let set = set.parse().ok()?;
+
+                let (fragment_mode, fragment_id) =
+                    fragment.split_once('/').unwrap_or((fragment, ""));
+
+                let discussion_id = match rest {
+                    ["discussion", id, ..] => id.parse().ok(),
+                    _ => None,
+                };
+
+                match fragment_id.parse().ok().or(discussion_id) {
+                    Some(id) => Self::Beatmap {
+                        id,
+                        mode: parse_mode(fragment_mode).or(query_mode),
+                    },
+                    None => Self::Beatmapset {
+                        id: set,
+                        mode: parse_mode(fragment_mode).or(query_mode),
+                    },
+                }
+            }
+            ["s" | "d", set] => Self::Beatmapset {
+                // Download links without video end with `n`
+                id: set.trim_end_matches('n').parse().ok()?,
+                mode: query_mode,
+            },
+            ["p", "beatmap"] => {
+                if let Some(id) = query("b") {
+                    Self::Beatmap {
+                        id: id.parse().ok()?,
+                        mode: query_mode,
+                    }
+                } else {
+                    Self::Beatmapset {
+                        id: query("s")?.parse().ok()?,
+                        mode: query_mode,
+                    }
+                }
+            }
+            ["scores", id] => Self::Score {
+                id: id.parse().ok()?,
+                mode: None,
+            },
+            ["scores", mode, id] => Self::Score {
+                id: id.parse().ok()?,
+                mode: Some(parse_mode(mode)?),
+            },
+            ["u" | "users", user, rest @ ..] => {
+                let user = user.replace("%20", " ");
+                let mode = rest.first().and_then(|x| parse_mode(x));
+
+                Self::User {
+                    user: UserId::from(user.as_str()),
+                    mode: mode.or(query_mode),
+                }
+            }
+            ["community", "matches", id] | ["mp", id] => {
+                Self::Match(id.parse().ok()?)
+            }
+            _ => return None,
+        };
+
+        Some(link)
+    }
+
+    /// Whether link points to a beatmap, possibly through other entity
+    pub fn is_beatmap(&self) -> bool {
+        matches!(
+            self,
+            Self::Beatmap { .. } | Self::Beatmapset { .. } | Self::Score { .. }
+        )
+    }
+}
+
+/// Every osu! link found in the text in order
+pub fn find_osu_links(text: &str) -> impl Iterator<Item = OsuLink> + '_ {
+    OSU_LINK
+        .get()
+        .find_iter(text)
+        .filter_map(|x| OsuLink::parse(x.as_str()))
+}
+
+/// Resolves link to the beatmap id, beatmapsets resolve
+/// to the hardest difficulty of the `mode`
+pub async fn resolve_beatmap_link(
+    ctx: &FumoContext,
+    link: &OsuLink,
+    mode: Option<OsuGameMode>,
+) -> Result<Option<i32>> {
+    match link {
+        OsuLink::Beatmap { id, .. } => Ok(Some(*id)),
+        OsuLink::Beatmapset {
+            id,
+            mode: link_mode,
+        } => {
+            let beatmapset = ctx.osu_api.get_beatmapset(*id as i64).await?;
+
+            Ok(beatmapset.top_difficulty(link_mode.or(mode)).map(|x| x.id))
+        }
+        OsuLink::Score { id, mode } => {
+            let score = ctx.osu_api.get_score(*id, *mode).await?;
+
+            Ok(score.beatmap.map(|x| x.id))
+        }
+        OsuLink::User { .. } | OsuLink::Match(_) => Ok(None),
+    }
+}
+
+/// Finds first beatmap link in the provided text
+pub async fn parse_beatmap_link(
+    ctx: &FumoContext,
+    text: &str,
+    mode: Option<OsuGameMode>,
+) -> Result<Option<i32>> {
+    for link in find_osu_links(text).filter(OsuLink::is_beatmap) {
+        if let Some(bid) = resolve_beatmap_link(ctx, &link, mode).await? {
+            return Ok(Some(bid));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Every piece of the message text that might contain a beatmap link,
+/// link placed by a known bot goes first
+fn message_texts<'a>(
+    bots: &KnownBots,
+    msg: &'a Message,
+) -> impl Iterator<Item = &'a str> {
+    let embeds = msg.embeds.iter().flat_map(|embed| {
+        let author = embed.author.as_ref().and_then(|x| x.url.as_deref());
+        let fields = embed.fields.iter().map(|x| x.value.as_str());
+
+        embed
+            .url
+            .as_deref()
+            .into_iter()
+            .chain(author)
+            .chain(embed.title.as_deref())
+            .chain(embed.description.as_deref())
+            .chain(fields)
+    });
+
+    bots.link(msg)
+        .into_iter()
+        .chain(std::iter::once(msg.content.as_str()))
+        .chain(embeds)
+}
+
+/// Searches message content and embeds for beatmap link
+pub fn find_beatmap_link(bots: &KnownBots, msg: &Message) -> Option<OsuLink> {
+    message_texts(bots, msg)
+        .flat_map(find_osu_links)
+        .find(OsuLink::is_beatmap)
+}
+
+/// Beatmap MD5 checksum stored in the `.osr` header
+fn replay_checksum(bytes: &[u8]) -> Option<String> {
+    // mode byte and game version
+    let rest = bytes.get(5..)?;
+
+    if *rest.first()? != 0x0b {
         return None;
     }
 
-    let m = if let Some(o) = OSU_MAP_ID_OLD.get().captures(str) {
-        o.get(1)
-    } else {
-        OSU_MAP_ID_NEW.get().captures(str).and_then(|o| o.get(2))
+    let mut len = 0usize;
+    let mut shift = 0;
+    let mut pos = 1;
+
+    loop {
+        let byte = *rest.get(pos)?;
+        len |= ((byte & 0x7f) as usize) << shift;
+        pos += 1;
+
+        if byte & 0x80 == 0 {
+            break;
+        }
+
+        shift += 7;
+
+        if shift > 28 {
+            return None;
+        }
+    }
+
+    let checksum = std::str::from_utf8(rest.get(pos..pos + len)?).ok()?;
+
+    (len == 32 && checksum.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| checksum.to_owned())
+}
+
+/// `BeatmapID` from the metadata section of the `.osu` file
+fn osu_file_beatmap_id(bytes: &[u8]) -> Option<i32> {
+    String::from_utf8_lossy(bytes)
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("BeatmapID:"))
+        .and_then(|id| id.trim().parse().ok())
+        .filter(|&id| id > 0)
+}
+
+async fn attachment_beatmap(
+    ctx: &FumoContext,
+    attachment: &Attachment,
+) -> Result<Option<i32>> {
+    let filename = attachment.filename.to_lowercase();
+    let is_replay = filename.ends_with(".osr");
+
+    if !(is_replay || filename.ends_with(".osu"))
+        || attachment.size > MAX_ATTACHMENT_SIZE
+    {
+        return Ok(None);
+    }
+
+    let bytes = reqwest::get(&attachment.url).await?.bytes().await?;
+
+    if !is_replay {
+        return Ok(osu_file_beatmap_id(&bytes));
+    }
+
+    let Some(checksum) = replay_checksum(&bytes) else {
+        return Ok(None);
     };
 
-    m.and_then(|o| o.as_str().parse().ok())
+    let beatmap = ctx.osu_api.lookup_beatmap(&checksum).await?;
+
+    Ok(Some(beatmap.id as i32))
+}
+
+/// Searches message for beatmap, attachments are
+/// checked only if message text doesn't have any link
+pub async fn find_message_beatmap(
+    ctx: &FumoContext,
+    msg: &Message,
+    mode: Option<OsuGameMode>,
+) -> Option<i32> {
+    if let Some(link) = find_beatmap_link(&ctx.known_bots, msg) {
+        match resolve_beatmap_link(ctx, &link, mode).await {
+            Ok(Some(bid)) => return Some(bid),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to resolve {link:?}: {e}"),
+        }
+    }
+
+    for attachment in &msg.attachments {
+        match attachment_beatmap(ctx, attachment).await {
+            Ok(Some(bid)) => return Some(bid),
+            Ok(None) => {}
+            Err(e) => tracing::warn!(
+                filename = attachment.filename,
+                "Failed to get beatmap from attachment: {e}"
+            ),
+        }
+    }
+
+    None
+}
+
+/// Looks through recent messages of the channel for the latest beatmap
+pub async fn find_recent_beatmap(
+    ctx: &FumoContext,
+    channel_id: Id<ChannelMarker>,
+    mode: Option<OsuGameMode>,
+) -> Result<Option<i32>> {
+    let msgs = ctx
+        .http
+        .channel_messages(channel_id)
+        .limit(RECENT_MESSAGES_LIMIT)?
+        .await?
+        .models()
+        .await?;
+
+    for msg in msgs {
+        if let Some(bid) = find_message_beatmap(ctx, &msg, mode).await {
+            return Ok(Some(bid));
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use osu_api::models::{OsuBeatmapset, OsuBeatmapsetDifficulty};
+
+    fn beatmap(id: i32, mode: Option<OsuGameMode>) -> Option<OsuLink> {
+        Some(OsuLink::Beatmap { id, mode })
+    }
+
+    fn beatmapset(id: i32, mode: Option<OsuGameMode>) -> Option<OsuLink> {
+        Some(OsuLink::Beatmapset { id, mode })
+    }
+
+    #[test]
+    fn test_parse_beatmap() {
+        use OsuGameMode::*;
+
+        let cases = [
+            ("https://osu.ppy.sh/b/75", beatmap(75, None)),
+            ("https://osu.ppy.sh/b/75?m=1", beatmap(75, Some(Taiko))),
+            ("https://osu.ppy.sh/b/75&m=0", None),
+            ("https://osu.ppy.sh/beatmaps/75", beatmap(75, None)),
+            ("https://osu.ppy.sh/beatmaps/75/", beatmap(75, None)),
+            (
+                "https://osu.ppy.sh/beatmaps/75?mode=mania",
+                beatmap(75, Some(Mania)),
+            ),
+            ("http://osu.ppy.sh/beatmaps/75", beatmap(75, None)),
+            ("osu.ppy.sh/beatmaps/75", beatmap(75, None)),
+            ("https://old.ppy.sh/b/75", beatmap(75, None)),
+            (
+                "https://osu.ppy.sh/beatmapsets/1#osu/75",
+                beatmap(75, Some(Osu)),
+            ),
+            (
+                "https://osu.ppy.sh/beatmapsets/1#taiko/75",
+                beatmap(75, Some(Taiko)),
+            ),
+            (
+                "https://osu.ppy.sh/beatmapsets/1#fruits/75",
+                beatmap(75, Some(Fruits)),
+            ),
+            (
+                "https://osu.ppy.sh/beatmapsets/1#mania/75",
+                beatmap(75, Some(Mania)),
+            ),
+            (
+                "https://osu.ppy.sh/beatmapsets/1/#osu/75",
+                beatmap(75, Some(Osu)),
+            ),
+            (
+                "https://osu.ppy.sh/beatmapsets/1<#1337>/75",
+                beatmap(75, None),
+            ),
+            (
+                "https://osu.ppy.sh/beatmapsets/1/discussion/75/general",
+                beatmap(75, None),
+            ),
+            ("https://osu.ppy.sh/p/beatmap?b=75", beatmap(75, None)),
+            (
+                "https://osu.ppy.sh/p/beatmap?b=75&m=3",
+                beatmap(75, Some(Mania)),
+            ),
+            ("https://osu.ppy.sh/b/75.", beatmap(75, None)),
+            ("https://osu.ppy.sh/b/", None),
+            ("https://osu.ppy.sh/b/abc", None),
+            ("https://osu.ppy.sh/b/99999999999", None),
+        ];
+
+        for (link, expected) in cases {
+            assert_eq!(OsuLink::parse(link), expected, "{link}");
+        }
+    }
+
+    #[test]
+    fn test_parse_beatmapset() {
+        use OsuGameMode::*;
+
+        let cases = [
+            ("https://osu.ppy.sh/beatmapsets/1", beatmapset(1, None)),
+            ("https://osu.ppy.sh/beatmapsets/1/", beatmapset(1, None)),
+            ("https://osu.ppy.sh/beatmapsets/1#", beatmapset(1, None)),
+            (
+                "https://osu.ppy.sh/beatmapsets/1#osu",
+                beatmapset(1, Some(Osu)),
+            ),
+            (
+                "https://osu.ppy.sh/beatmapsets/1#mania",
+                beatmapset(1, Some(Mania)),
+            ),
+            (
+                "https://osu.ppy.sh/beatmapsets/1#fruits/",
+                beatmapset(1, Some(Fruits)),
+            ),
+            (
+                "https://osu.ppy.sh/beatmapsets/1/discussion/-/generalAll",
+                beatmapset(1, None),
+            ),
+            ("https://osu.ppy.sh/s/1", beatmapset(1, None)),
+            ("https://osu.ppy.sh/s/1?m=2", beatmapset(1, Some(Fruits))),
+            ("https://osu.ppy.sh/d/1", beatmapset(1, None)),
+            ("https://osu.ppy.sh/d/1n", beatmapset(1, None)),
+            ("https://osu.ppy.sh/p/beatmap?s=1", beatmapset(1, None)),
+            ("https://osu.ppy.sh/beatmapsets", None),
+            ("https://osu.ppy.sh/beatmapsets/abc#osu/75", None),
+            ("https://osu.ppy.sh/p/beatmap", None),
+            ("https://osu.ppy.sh/p/beatmap?s=abc", None),
+        ];
+
+        for (link, expected) in cases {
+            assert_eq!(OsuLink::parse(link), expected, "{link}");
+        }
+    }
+
+    #[test]
+    fn test_parse_score() {
+        let cases = [
+            (
+                "https://osu.ppy.sh/scores/1234567890",
+                Some(OsuLink::Score {
+                    id: 1234567890,
+                    mode: None,
+                }),
+            ),
+            (
+                "https://osu.ppy.sh/scores/osu/4200",
+                Some(OsuLink::Score {
+                    id: 4200,
+                    mode: Some(OsuGameMode::Osu),
+                }),
+            ),
+            (
+                "https://osu.ppy.sh/scores/mania/4200",
+                Some(OsuLink::Score {
+                    id: 4200,
+                    mode: Some(OsuGameMode::Mania),
+                }),
+            ),
+            ("https://osu.ppy.sh/scores/catch/4200", None),
+            ("https://osu.ppy.sh/scores", None),
+            ("https://osu.ppy.sh/scores/abc", None),
+        ];
+
+        for (link, expected) in cases {
+            assert_eq!(OsuLink::parse(link), expected, "{link}");
+        }
+    }
+
+    #[test]
+    fn test_parse_user() {
+        let user = |user: UserId, mode| Some(OsuLink::User { user, mode });
+
+        let cases = [
+            ("https://osu.ppy.sh/users/2", user(UserId::Id(2), None)),
+            ("https://osu.ppy.sh/u/2", user(UserId::Id(2), None)),
+            (
+                "https://osu.ppy.sh/users/2/taiko",
+                user(UserId::Id(2), Some(OsuGameMode::Taiko)),
+            ),
+            (
+                "https://osu.ppy.sh/u/2?m=3",
+                user(UserId::Id(2), Some(OsuGameMode::Mania)),
+            ),
+            (
+                "https://osu.ppy.sh/users/peppy",
+                user(UserId::Username("peppy".to_owned()), None),
+            ),
+            (
+                "https://osu.ppy.sh/u/some%20user",
+                user(UserId::Username("some user".to_owned()), None),
+            ),
+            (
+                "https://osu.ppy.sh/users/2/modding",
+                user(UserId::Id(2), None),
+            ),
+            ("https://osu.ppy.sh/users", None),
+        ];
+
+        for (link, expected) in cases {
+            assert_eq!(OsuLink::parse(link), expected, "{link}");
+        }
+    }
+
+    #[test]
+    fn test_parse_match() {
+        let cases = [
+            (
+                "https://osu.ppy.sh/community/matches/111555364",
+                Some(OsuLink::Match(111555364)),
+            ),
+            (
+                "https://osu.ppy.sh/mp/111555364",
+                Some(OsuLink::Match(111555364)),
+            ),
+            ("https://osu.ppy.sh/community/matches", None),
+            ("https://osu.ppy.sh/community/matches/abc", None),
+            ("https://osu.ppy.sh/community/forums/topics/1", None),
+        ];
+
+        for (link, expected) in cases {
+            assert_eq!(OsuLink::parse(link), expected, "{link}");
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let cases = [
+            "",
+            "https://osu.ppy.sh",
+            "https://osu.ppy.sh/",
+            "https://osu.ppy.sh/home",
+            "https://osu.ppy.sh/rankings/osu/performance",
+            "https://example.com/b/75",
+            "https://osu.ppy.sh.example.com/b/75",
+            "https://assets.ppy.sh/beatmaps/1/covers/list.jpg",
+            "ftp://osu.ppy.sh/b/75",
+            "not a link",
+        ];
+
+        for link in cases {
+            assert_eq!(OsuLink::parse(link), None, "{link}");
+        }
+    }
+
+    #[test]
+    fn test_find_osu_links() {
+        let text = "look at this https://osu.ppy.sh/b/75, \
+            [map](https://osu.ppy.sh/beatmapsets/1#osu/76) and \
+            <https://osu.ppy.sh/users/2> or https://osu.ppy.sh/home \
+            https://osu.ppy.sh/beatmapsets/1<#1337>/77|\
+            osu.ppy.sh/community/matches/5";
+
+        let links: Vec<OsuLink> = find_osu_links(text).collect();
+
+        assert_eq!(
+            links,
+            vec![
+                OsuLink::Beatmap { id: 75, mode: None },
+                OsuLink::Beatmap {
+                    id: 76,
+                    mode: Some(OsuGameMode::Osu)
+                },
+                OsuLink::User {
+                    user: UserId::Id(2),
+                    mode: None
+                },
+                OsuLink::Beatmap { id: 77, mode: None },
+                OsuLink::Match(5),
+            ]
+        );
+
+        assert_eq!(find_osu_links("nothing here").next(), None);
+    }
+
+    #[test]
+    fn test_is_beatmap() {
+        assert!(beatmap(1, None).unwrap().is_beatmap());
+        assert!(beatmapset(1, None).unwrap().is_beatmap());
+        assert!(OsuLink::Score { id: 1, mode: None }.is_beatmap());
+        assert!(!OsuLink::Match(1).is_beatmap());
+        assert!(!OsuLink::User {
+            user: UserId::Id(1),
+            mode: None
+        }
+        .is_beatmap());
+    }
+
+    #[test]
+    fn test_top_difficulty() {
+        let difficulty =
+            |id, mode, difficulty_rating| OsuBeatmapsetDifficulty {
+                id,
+                mode,
+                difficulty_rating,
+                version: String::new(),
+            };
+
+        let beatmapset = OsuBeatmapset {
+            id: 1,
+            title: String::new(),
+            artist: String::new(),
+            creator: String::new(),
+            beatmaps: vec![
+                difficulty(1, OsuGameMode::Osu, 2.5),
+                difficulty(2, OsuGameMode::Osu, 6.1),
+                difficulty(3, OsuGameMode::Mania, 7.3),
+                difficulty(4, OsuGameMode::Taiko, 4.0),
+            ],
+        };
+
+        let top = |mode| beatmapset.top_difficulty(mode).map(|x| x.id);
+
+        assert_eq!(top(Some(OsuGameMode::Osu)), Some(2));
+        assert_eq!(top(Some(OsuGameMode::Mania)), Some(3));
+        assert_eq!(top(Some(OsuGameMode::Taiko)), Some(4));
+        assert_eq!(top(Some(OsuGameMode::Fruits)), None);
+        assert_eq!(top(None), Some(3));
+    }
+
+    #[test]
+    fn test_replay_checksum() {
+        let checksum = "0123456789abcdef0123456789abcdef";
+
+        let mut replay = vec![0u8, 0x4f, 0x2b, 0x33, 0x01, 0x0b, 32];
+        replay.extend_from_slice(checksum.as_bytes());
+        replay.extend_from_slice(&[0x0b, 3, b'f', b'o', b'o']);
+
+        assert_eq!(replay_checksum(&replay).as_deref(), Some(checksum));
+
+        // Truncated
+        assert_eq!(replay_checksum(&replay[..20]), None);
+        assert_eq!(replay_checksum(&[]), None);
+
+        // Missing string marker
+        replay[5] = 0x00;
+        assert_eq!(replay_checksum(&replay), None);
+    }
+
+    #[test]
+    fn test_osu_file_beatmap_id() {
+        let file = b"osu file format v14\r\n\r\n[Metadata]\r\n\
+            Title:Yomi yori\r\nBeatmapID:1849224\r\nBeatmapSetID:868339\r\n";
+
+        assert_eq!(osu_file_beatmap_id(file), Some(1849224));
+
+        // Unsubmitted beatmaps
+        assert_eq!(osu_file_beatmap_id(b"[Metadata]\nBeatmapID:0\n"), None);
+        assert_eq!(osu_file_beatmap_id(b"[Metadata]\n"), None);
+    }
+
+    #[test]
+    fn test_known_bots() {
+        let bots = KnownBots::new("123:author, 456:url,bad,789:embed");
+
+        assert_eq!(bots.0.get(&123), Some(&BotLinkPlace::Author));
+        assert_eq!(bots.0.get(&456), Some(&BotLinkPlace::Url));
+        assert_eq!(bots.0.get(&789), None);
+
+        // Defaults are kept
+        assert_eq!(
+            bots.0.get(&289066747443675143),
+            Some(&BotLinkPlace::Author)
+        );
+    }
 }